@@ -0,0 +1,10 @@
+#!/bin/bash
+../target/release/bitcoff \
+consolidate \
+--maxInputs=50 \
+--keyfile="./encrypted-btc-private-key.gpg" \
+--network=Testnet \
+--fee=2 \
+--futureFee=23 \
+--outputPath="./consolidate-output" \
+--logLevel="debug"
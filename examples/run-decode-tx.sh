@@ -0,0 +1,7 @@
+#!/bin/bash
+../target/release/bitcoff \
+decodeTx \
+--txFile="./make-offline-tx-output" \
+--utxoFile="./get-utxos-output" \
+--network=Testnet \
+--logLevel="debug"
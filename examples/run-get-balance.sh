@@ -0,0 +1,6 @@
+#!/bin/bash
+../target/release/bitcoff \
+getBalance \
+2N2LHYbt8K1KDBogd6XUG9VBv5YM6xefdM2 \
+--network="testnet" \
+--logLevel="debug"
@@ -0,0 +1,8 @@
+#!/bin/bash
+../target/release/bitcoff \
+getHistory \
+2N2LHYbt8K1KDBogd6XUG9VBv5YM6xefdM2 \
+--limit=10 \
+--network="testnet" \
+--outputPath="./get-history-output" \
+--logLevel="debug"
@@ -0,0 +1,11 @@
+#!/bin/bash
+../target/release/bitcoff \
+makeOfflineTx \
+--payments="./payments.csv" \
+--utxoFile="./get-utxos-output" \
+--keyfile="./encrypted-btc-private-key.gpg" \
+--network=Testnet \
+--fee=23 \
+--change=moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE \
+--outputPath="./make-offline-batch-payments-output" \
+--logLevel="debug"
@@ -2,7 +2,7 @@
 ../target/release/bitcoff \
 makeOfflineOpReturnTx \
 mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM \
-1 \
+546 \
 decaff \
 --utxoFile="./get-utxos-output" \
 --network=Testnet \
@@ -0,0 +1,6 @@
+#!/bin/bash
+../target/release/bitcoff \
+readOpReturn \
+--txFile="./make-offline-op-return-output" \
+--network=Testnet \
+--logLevel="debug"
@@ -0,0 +1,9 @@
+#!/bin/bash
+../target/release/bitcoff \
+scanOpReturn \
+moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE \
+--prefix="text:bcff" \
+--network="testnet" \
+--output="json" \
+--outputPath="./scan-op-return-output" \
+--logLevel="debug"
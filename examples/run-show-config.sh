@@ -0,0 +1,6 @@
+#!/bin/bash
+../target/release/bitcoff \
+showConfig \
+--config="./config.toml" \
+--profile="testnet-dev" \
+--fee=5
@@ -0,0 +1,11 @@
+#!/bin/bash
+../target/release/bitcoff \
+timestamp \
+./payments.csv \
+./run-timestamp.sh \
+--network=Testnet \
+--change=moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE \
+--fee=23 \
+--outputPath="./timestamp-output" \
+--keyfile="./encrypted-btc-private-key.gpg" \
+--logLevel="debug"
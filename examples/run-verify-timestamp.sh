@@ -0,0 +1,7 @@
+#!/bin/bash
+jq -r .hex ./timestamp-output | \
+../target/release/bitcoff \
+verifyTimestamp \
+./payments.csv \
+--proof="./timestamp-output" \
+--logLevel="debug"
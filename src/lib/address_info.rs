@@ -0,0 +1,259 @@
+use std::fmt;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use crate::{
+    state::State,
+    errors::AppError,
+    backend::BtcBackend,
+    utils::format_sats,
+    output_format::OutputFormat,
+    types::{
+        Result,
+        UtxoStatus,
+    },
+};
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+pub struct AddressStats {
+    pub tx_count: u64,
+    pub spent_txo_sum: u64,
+    pub funded_txo_sum: u64,
+}
+
+impl AddressStats {
+    pub fn get_balance(&self) -> i64 {
+        self.funded_txo_sum as i64 - self.spent_txo_sum as i64
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct AddressInfo {
+    pub address: String,
+    pub chain_stats: AddressStats,
+    pub mempool_stats: AddressStats,
+}
+
+impl AddressInfo {
+    pub fn get_confirmed_balance(&self) -> u64 {
+        self.chain_stats.get_balance().max(0) as u64
+    }
+
+    pub fn get_unconfirmed_balance(&self) -> i64 {
+        self.mempool_stats.get_balance()
+    }
+
+    pub fn get_tx_count(&self) -> u64 {
+        self.chain_stats.tx_count + self.mempool_stats.tx_count
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "address": self.address,
+            "tx_count": self.get_tx_count(),
+            "confirmed": self.get_confirmed_balance(),
+            "unconfirmed": self.get_unconfirmed_balance(),
+            "total": self.get_confirmed_balance() as i64 + self.get_unconfirmed_balance(),
+        })
+    }
+}
+
+impl fmt::Display for AddressInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "❍ Balance ❍\n")?;
+        writeln!(f, "    Address:       {}", self.address)?;
+        writeln!(f, "    Confirmed:     {}", format_sats(self.get_confirmed_balance()))?;
+        writeln!(f, "    Unconfirmed:   {} sats", self.get_unconfirmed_balance())?;
+        write!(f, "    Transactions:  {}", self.get_tx_count())
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct EsploraTxOut {
+    pub value: u64,
+    #[serde(default)]
+    pub scriptpubkey: String,
+    pub scriptpubkey_address: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct EsploraTxIn {
+    pub prevout: Option<EsploraTxOut>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+pub struct EsploraTx {
+    pub fee: u64,
+    pub txid: String,
+    pub status: UtxoStatus,
+    pub vin: Vec<EsploraTxIn>,
+    pub vout: Vec<EsploraTxOut>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub fee: u64,
+    pub txid: String,
+    pub net_amount: i64,
+    pub confirmations: u64,
+    pub block_time: Option<u64>,
+}
+
+impl HistoryEntry {
+    pub fn new(tx: &EsploraTx, address: &str, tip_height: u64) -> Self {
+        let is_address = |output: &&EsploraTxOut| output.scriptpubkey_address.as_ref().map(|x| x == address).unwrap_or(false);
+        let received: u64 = tx.vout.iter().filter(is_address).map(|output| output.value).sum();
+        let sent: u64 = tx.vin.iter().filter_map(|input| input.prevout.as_ref()).filter(is_address).map(|output| output.value).sum();
+        HistoryEntry {
+            fee: tx.fee,
+            txid: tx.txid.clone(),
+            block_time: tx.status.block_time,
+            net_amount: received as i64 - sent as i64,
+            confirmations: tx.status.get_confirmations(tip_height),
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "fee": self.fee,
+            "txid": self.txid,
+            "block_time": self.block_time,
+            "net_amount": self.net_amount,
+            "confirmations": self.confirmations,
+        })
+    }
+}
+
+impl fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ❍ {:+} sats ❍ fee: {} sats ❍ ", self.txid, self.net_amount, self.fee)?;
+        match self.block_time {
+            Some(block_time) => write!(f, "{} confirmation(s) ❍ block time: {}", self.confirmations, block_time),
+            None => write!(f, "unconfirmed"),
+        }
+    }
+}
+
+pub fn parse_address_info_json_string(address_info_json_string: &str) -> Result<AddressInfo> {
+    info!("✔ Parsing address info JSON string...");
+    Ok(serde_json::from_str(address_info_json_string)?)
+}
+
+// NOTE: The first page holds any mempool txs plus the newest confirmed ones, & later pages are keyed by the last seen txid.
+pub fn get_address_txs(
+    backend: &dyn BtcBackend,
+    address: &str,
+    limit: usize,
+    maybe_after: Option<&str>,
+) -> Result<Vec<EsploraTx>> {
+    let mut txs = backend.get_address_txs_page(address, maybe_after)?;
+    while txs.len() < limit {
+        let last_seen_txid = match txs.iter().rev().find(|tx| tx.status.confirmed) {
+            Some(tx) => tx.txid.clone(),
+            None => break,
+        };
+        let page = backend.get_address_txs_page(address, Some(&last_seen_txid))?;
+        if page.is_empty() {
+            break
+        };
+        txs.extend(page);
+    }
+    txs.truncate(limit);
+    Ok(txs)
+}
+
+pub fn get_address_from_state(state: &State) -> Result<String> {
+    match &state.btc_address {
+        None => state.get_btc_address(),
+        Some(btc_address) => Ok(btc_address.clone()),
+    }
+}
+
+pub fn get_balance_output_from_state(state: State) -> Result<String> {
+    info!("✔ Getting balance output from state...");
+    let address_info = state.backend.get_address_info(&get_address_from_state(&state)?)?;
+    match state.output_format {
+        OutputFormat::Text => Ok(address_info.to_string()),
+        OutputFormat::Json => Ok(address_info.to_json().to_string()),
+    }
+}
+
+pub fn get_history_output_from_state(state: State, limit: usize, maybe_after: &Option<String>) -> Result<String> {
+    info!("✔ Getting history output from state...");
+    if limit == 0 {
+        return Err(AppError::Custom("✘ The history limit must be greater than zero!".to_string()))
+    };
+    let address = get_address_from_state(&state)?;
+    let txs = get_address_txs(&state.backend, &address, limit, maybe_after.as_deref())?;
+    let tip_height = state.backend.get_tip_height()?;
+    let entries = txs.iter().map(|tx| HistoryEntry::new(tx, &address, tip_height)).collect::<Vec<HistoryEntry>>();
+    let maybe_next_page = match entries.len() == limit {
+        true => entries.last().map(|entry| entry.txid.clone()),
+        false => None,
+    };
+    match state.output_format {
+        OutputFormat::Json => Ok(
+            json!({
+                "address": address,
+                "next_page": maybe_next_page,
+                "txs": entries.iter().map(HistoryEntry::to_json).collect::<Vec<JsonValue>>(),
+            }).to_string()
+        ),
+        OutputFormat::Text => {
+            let mut lines = entries.iter().map(HistoryEntry::to_string).collect::<Vec<String>>();
+            if let Some(next_page) = maybe_next_page {
+                lines.push(format!("❍ More txs available via: --after={}", next_page));
+            };
+            Ok(lines.join("\n"))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::EsploraBackend,
+        test_utils::{
+            SAMPLE_TESTNET_ENDPOINT,
+            SAMPLE_TARGET_BTC_ADDRESS,
+        },
+    };
+
+    const SAMPLE_ADDRESS_INFO_JSON: &str = "{\"address\":\"moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE\",\"chain_stats\":{\"funded_txo_count\":3,\"funded_txo_sum\":900000,\"spent_txo_count\":1,\"spent_txo_sum\":8832,\"tx_count\":3},\"mempool_stats\":{\"funded_txo_count\":1,\"funded_txo_sum\":5000,\"spent_txo_count\":1,\"spent_txo_sum\":891168,\"tx_count\":1}}";
+
+    const SAMPLE_ADDRESS_TX_JSON: &str = "{\"txid\":\"85f8faf4a3da404a833d0a21b1cea215da74a4b2c1ce8187cbf6379f42c02924\",\"fee\":226,\"status\":{\"confirmed\":true,\"block_height\":1000,\"block_time\":1570000000},\"vin\":[{\"prevout\":{\"scriptpubkey_address\":\"moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE\",\"value\":10000}}],\"vout\":[{\"scriptpubkey_address\":\"mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM\",\"value\":5000},{\"scriptpubkey_address\":\"moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE\",\"value\":4774}]}";
+
+    #[test]
+    fn should_parse_address_info_json_string() {
+        let result = parse_address_info_json_string(SAMPLE_ADDRESS_INFO_JSON).unwrap();
+        assert_eq!(result.address, SAMPLE_TARGET_BTC_ADDRESS);
+        assert_eq!(result.get_confirmed_balance(), 891168);
+        assert_eq!(result.get_unconfirmed_balance(), 5000 - 891168);
+        assert_eq!(result.get_tx_count(), 4);
+    }
+
+    #[test]
+    fn should_get_history_entry_from_tx() {
+        let tx: EsploraTx = serde_json::from_str(SAMPLE_ADDRESS_TX_JSON).unwrap();
+        let result = HistoryEntry::new(&tx, SAMPLE_TARGET_BTC_ADDRESS, 1005);
+        assert_eq!(result.net_amount, 4774 - 10000);
+        assert_eq!(result.fee, 226);
+        assert_eq!(result.confirmations, 6);
+        assert_eq!(result.block_time, Some(1570000000));
+    }
+
+    #[test]
+    fn should_get_address_info() {
+        let result = EsploraBackend::new(SAMPLE_TESTNET_ENDPOINT).get_address_info(SAMPLE_TARGET_BTC_ADDRESS).unwrap();
+        assert_eq!(result.address, SAMPLE_TARGET_BTC_ADDRESS);
+    }
+
+    #[test]
+    fn should_get_address_txs() {
+        let backend = EsploraBackend::new(SAMPLE_TESTNET_ENDPOINT);
+        let result = get_address_txs(&backend, SAMPLE_TARGET_BTC_ADDRESS, 5, None).unwrap();
+        assert!(result.len() <= 5);
+    }
+}
@@ -0,0 +1,124 @@
+use bitcoin::network::constants::Network as BtcNetwork;
+use crate::{
+    errors::AppError,
+    utils::make_api_call,
+    constants::BLOCK_EXPLORER_URL,
+    get_utxos_info::parse_utxo_list_json_string,
+    fee_estimates::{
+        FeeEstimates,
+        parse_fee_estimates_json_string,
+    },
+    address_info::{
+        EsploraTx,
+        AddressInfo,
+        parse_address_info_json_string,
+    },
+    types::{
+        Result,
+        UtxosInfo,
+    },
+};
+
+/// The chain data `bitcoff` needs from the outside world. Implement this to source it from somewhere other than a
+/// public block explorer, eg: your own node or a test fixture.
+pub trait BtcBackend {
+    /// All unspent outputs paying to `address`, including any still in the mempool.
+    fn get_utxos_info(&self, address: &str) -> Result<UtxosInfo>;
+
+    /// The raw hex of the transaction with id `tx_id`.
+    fn get_tx_hex(&self, tx_id: &str) -> Result<String>;
+
+    /// The height of the current chain tip.
+    fn get_tip_height(&self) -> Result<u64>;
+
+    /// Fee rates in sats-per-byte keyed by their confirmation target in blocks.
+    fn get_fee_estimates(&self) -> Result<FeeEstimates>;
+
+    /// The funding & spending totals of `address`.
+    fn get_address_info(&self, address: &str) -> Result<AddressInfo>;
+
+    /// A page of the transactions involving `address`, newest first. The first page includes any mempool txs, & later
+    /// pages start after the confirmed tx with id `maybe_last_seen_txid`.
+    fn get_address_txs_page(&self, address: &str, maybe_last_seen_txid: Option<&str>) -> Result<Vec<EsploraTx>>;
+}
+
+pub fn get_api_endpoint_for_network(network: BtcNetwork) -> String {
+    match network {
+        BtcNetwork::Testnet => format!("{}testnet/api/", BLOCK_EXPLORER_URL),
+        _ => format!("{}/api/", BLOCK_EXPLORER_URL),
+    }
+}
+
+/// A `BtcBackend` backed by an Esplora HTTP API, such as the one at https://blockstream.info.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EsploraBackend {
+    api_endpoint: String,
+}
+
+impl EsploraBackend {
+    /// Uses the Esplora API at `api_endpoint`, which must end in a `/`.
+    pub fn new(api_endpoint: &str) -> Self {
+        Self { api_endpoint: api_endpoint.to_string() }
+    }
+
+    pub fn for_network(network: BtcNetwork) -> Self {
+        Self::new(&get_api_endpoint_for_network(network))
+    }
+
+    pub fn get_api_endpoint(&self) -> &str {
+        &self.api_endpoint
+    }
+}
+
+impl BtcBackend for EsploraBackend {
+    fn get_utxos_info(&self, address: &str) -> Result<UtxosInfo> {
+        info!("✔ Getting UTXO info for address: {}", address);
+        make_api_call(&format!("{}address/{}/utxo", self.api_endpoint, address), "✘ Error getting UTXO list")
+            .and_then(|json| parse_utxo_list_json_string(&json))
+    }
+
+    fn get_tx_hex(&self, tx_id: &str) -> Result<String> {
+        info!("✔ Getting BTC tx in hex format for tx id: {}", tx_id);
+        make_api_call(&format!("{}tx/{}/hex", self.api_endpoint, tx_id), "✘ Error getting BTC tx in hex")
+    }
+
+    fn get_tip_height(&self) -> Result<u64> {
+        info!("✔ Getting chain tip height...");
+        make_api_call(&format!("{}blocks/tip/height", self.api_endpoint), "✘ Error getting chain tip height")
+            .and_then(|height| height.trim().parse::<u64>().map_err(|e| AppError::Custom(e.to_string())))
+    }
+
+    fn get_fee_estimates(&self) -> Result<FeeEstimates> {
+        info!("✔ Getting fee estimates...");
+        make_api_call(&format!("{}fee-estimates", self.api_endpoint), "✘ Error getting fee estimates")
+            .and_then(|json| parse_fee_estimates_json_string(&json))
+    }
+
+    fn get_address_info(&self, address: &str) -> Result<AddressInfo> {
+        info!("✔ Getting address info for address: {}", address);
+        make_api_call(&format!("{}address/{}", self.api_endpoint, address), "✘ Error getting address info")
+            .and_then(|json| parse_address_info_json_string(&json))
+    }
+
+    fn get_address_txs_page(&self, address: &str, maybe_last_seen_txid: Option<&str>) -> Result<Vec<EsploraTx>> {
+        let url = match maybe_last_seen_txid {
+            None => format!("{}address/{}/txs", self.api_endpoint, address),
+            Some(last_seen_txid) => format!("{}address/{}/txs/chain/{}", self.api_endpoint, address, last_seen_txid),
+        };
+        info!("✔ Getting page of txs for address: {}", address);
+        make_api_call(&url, "✘ Error getting address txs")
+            .and_then(|json| Ok(serde_json::from_str(&json)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::SAMPLE_TESTNET_ENDPOINT;
+
+    #[test]
+    fn should_get_api_endpoint_for_network() {
+        assert_eq!(get_api_endpoint_for_network(BtcNetwork::Testnet), SAMPLE_TESTNET_ENDPOINT);
+        assert_eq!(EsploraBackend::for_network(BtcNetwork::Testnet).get_api_endpoint(), SAMPLE_TESTNET_ENDPOINT);
+    }
+}
@@ -0,0 +1,240 @@
+use std::cmp::Reverse;
+use serde_json::Value as JsonValue;
+use bitcoin::blockdata::transaction::TxOut as BtcTxOut;
+use crate::{
+    state::State,
+    errors::AppError,
+    output_format::OutputFormat,
+    constants::MAX_STANDARD_TX_WEIGHT,
+    utils::{
+        create_new_tx_output,
+        get_change_address_from_state,
+        calculate_btc_tx_size_for_outputs,
+        calculate_btc_tx_fee_for_outputs,
+    },
+    types::{
+        Result,
+        BtcUtxoAndValue,
+        BtcUtxosAndValues,
+        BtcAddressAndAmount,
+        BtcAddressesAndAmounts,
+    },
+};
+
+pub type PaymentBatch = (BtcAddressesAndAmounts, BtcUtxosAndValues);
+
+pub fn tx_fits_weight_limit(num_inputs: usize, outputs: &[BtcTxOut]) -> bool {
+    calculate_btc_tx_size_for_outputs(num_inputs, outputs) * 4 <= MAX_STANDARD_TX_WEIGHT
+}
+
+fn get_num_utxos_required(
+    utxos_sorted_by_value: &[BtcUtxoAndValue],
+    target: u64,
+    outputs: &[BtcTxOut],
+    sats_per_byte: f64,
+) -> Option<usize> {
+    let mut utxo_total = 0;
+    for (i, utxo) in utxos_sorted_by_value.iter().enumerate() {
+        utxo_total += utxo.value;
+        if utxo_total >= target + calculate_btc_tx_fee_for_outputs(i + 1, outputs, sats_per_byte) {
+            return match tx_fits_weight_limit(i + 1, outputs) {
+                true => Some(i + 1),
+                false => None,
+            }
+        };
+    }
+    None
+}
+
+/// Splits the payments into batches that each fit in a standard tx alongside the `extra_outputs`, eg: its change.
+pub fn split_payments_into_batches(
+    recipients: &BtcAddressesAndAmounts,
+    utxos_and_values: &BtcUtxosAndValues,
+    sats_per_byte: f64,
+    extra_outputs: &[BtcTxOut],
+) -> Result<Vec<PaymentBatch>> {
+    info!("✔ Splitting {} payments into batches...", recipients.len());
+    let get_not_enough_value_err = |recipient: &BtcAddressAndAmount| AppError::Custom(
+        format!("✘ Not enough UTXO value left to pay {} sats to {}!", recipient.amount, recipient.address)
+    );
+    let mut remaining_utxos = utxos_and_values.to_vec();
+    remaining_utxos.sort_by_key(|utxo| Reverse(utxo.value));
+    let mut batches: Vec<PaymentBatch> = vec![];
+    let mut batch: Vec<BtcAddressAndAmount> = vec![];
+    // NOTE: The outputs of the batch so far, plus the next recipient's & the extra ones.
+    let mut batch_outputs = extra_outputs.to_vec();
+    let mut batch_total = 0;
+    let mut batch_num_utxos = 0;
+    for recipient in recipients.to_vec() {
+        let recipient_output = create_new_tx_output(&recipient)?;
+        batch_outputs.push(recipient_output.clone());
+        match get_num_utxos_required(&remaining_utxos, batch_total + recipient.amount, &batch_outputs, sats_per_byte) {
+            Some(num_utxos) => {
+                batch_num_utxos = num_utxos;
+                batch_total += recipient.amount;
+                batch.push(recipient);
+            },
+            None if batch.is_empty() => return Err(get_not_enough_value_err(&recipient)),
+            None => {
+                let batch_utxos = remaining_utxos.drain(..batch_num_utxos).collect();
+                batches.push((BtcAddressesAndAmounts(batch), BtcUtxosAndValues::from_vec(batch_utxos)));
+                batch_outputs = [extra_outputs, &[recipient_output]].concat();
+                batch_num_utxos =
+                    get_num_utxos_required(&remaining_utxos, recipient.amount, &batch_outputs, sats_per_byte)
+                        .ok_or_else(|| get_not_enough_value_err(&recipient))?;
+                batch_total = recipient.amount;
+                batch = vec![recipient];
+            },
+        }
+    }
+    if !batch.is_empty() {
+        let batch_utxos = remaining_utxos.drain(..batch_num_utxos).collect();
+        batches.push((BtcAddressesAndAmounts(batch), BtcUtxosAndValues::from_vec(batch_utxos)));
+    };
+    info!("✔ Split payments into {} batch(es)", batches.len());
+    Ok(batches)
+}
+
+pub fn split_state_into_payment_batches(state: State) -> Result<Vec<State>> {
+    let utxos = state.get_btc_utxos_and_values()?;
+    // NOTE: A change output is allowed for even when sweeping, so the size is never underestimated.
+    let change_output = create_new_tx_output(&BtcAddressAndAmount::new(&get_change_address_from_state(&state)?, 0)?)?;
+    let extra_outputs = state
+        .op_return_outputs
+        .iter()
+        .cloned()
+        .map(BtcTxOut::from)
+        .chain(std::iter::once(change_output))
+        .collect::<Vec<BtcTxOut>>();
+    let outputs = state
+        .addresses_and_amounts
+        .0
+        .iter()
+        .map(create_new_tx_output)
+        .chain(extra_outputs.iter().cloned().map(Ok))
+        .collect::<Result<Vec<BtcTxOut>>>()?;
+    if tx_fits_weight_limit(utxos.len(), &outputs) {
+        return Ok(vec![state])
+    };
+    info!("✔ Tx would exceed the standard weight limit, splitting into batches...");
+    if !state.op_return_outputs.is_empty() {
+        return Err(AppError::Custom("✘ Too many payments to fit into a single `OP_RETURN` tx!".to_string()))
+    };
+    if state.sweep || !state.subtract_fee_from.is_empty() {
+        return Err(AppError::Custom("✘ Cannot sweep or subtract fees from payments split across txs!".to_string()))
+    };
+    Ok(
+        split_payments_into_batches(&state.addresses_and_amounts, utxos, state.get_sats_per_byte()?, &extra_outputs)?
+            .into_iter()
+            .map(|(recipients, utxos)| state.to_batch_state(recipients, utxos))
+            .collect()
+    )
+}
+
+pub fn process_payment_batches<F>(states: Vec<State>, process_batch: F) -> Result<String>
+    where F: Fn(State) -> Result<String>
+{
+    let output_format = states.first().map(|state| state.output_format).unwrap_or(OutputFormat::Text);
+    let outputs = states.into_iter().map(process_batch).collect::<Result<Vec<String>>>()?;
+    match (outputs.len(), output_format) {
+        (1, _) => Ok(outputs[0].clone()),
+        (_, OutputFormat::Text) => Ok(outputs.join("\n")),
+        (_, OutputFormat::Json) => Ok(
+            JsonValue::Array(
+                outputs
+                    .iter()
+                    .map(|output| serde_json::from_str(output))
+                    .collect::<std::result::Result<Vec<JsonValue>, serde_json::Error>>()?
+            ).to_string()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_sample_utxo;
+
+    const SAMPLE_P2PKH_ADDRESS: &str = "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM";
+    const SAMPLE_P2WSH_ADDRESS: &str = "tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7";
+
+    fn get_sample_utxos(num_utxos: usize, value: u64) -> BtcUtxosAndValues {
+        BtcUtxosAndValues::from_vec(
+            (0..num_utxos)
+                .map(|i| {
+                    let mut utxo = get_sample_utxo();
+                    utxo.value = value;
+                    utxo.utxo.previous_output.vout = i as u32;
+                    utxo
+                })
+                .collect()
+        )
+    }
+
+    fn get_sample_recipients(num_recipients: usize, amount: u64) -> BtcAddressesAndAmounts {
+        get_sample_recipients_to(num_recipients, amount, SAMPLE_P2PKH_ADDRESS)
+    }
+
+    fn get_sample_recipients_to(num_recipients: usize, amount: u64, address: &str) -> BtcAddressesAndAmounts {
+        BtcAddressesAndAmounts(
+            (0..num_recipients)
+                .map(|_| BtcAddressAndAmount::new(address, amount).unwrap())
+                .collect()
+        )
+    }
+
+    fn get_sample_change_outputs() -> Vec<BtcTxOut> {
+        vec![create_new_tx_output(&BtcAddressAndAmount::new(SAMPLE_P2PKH_ADDRESS, 0).unwrap()).unwrap()]
+    }
+
+    fn get_sample_batch_outputs(recipients: &BtcAddressesAndAmounts) -> Vec<BtcTxOut> {
+        recipients
+            .0
+            .iter()
+            .map(|recipient| create_new_tx_output(recipient).unwrap())
+            .chain(get_sample_change_outputs())
+            .collect()
+    }
+
+    #[test]
+    fn should_keep_payments_that_fit_in_a_single_batch() {
+        let recipients = get_sample_recipients(10, 1000);
+        let utxos = get_sample_utxos(2, 100_000);
+        let result = split_payments_into_batches(&recipients, &utxos, 1.0, &get_sample_change_outputs()).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].0.len(), 10);
+    }
+
+    #[test]
+    fn should_split_payments_exceeding_weight_limit_into_batches() {
+        let num_recipients = 5000;
+        let recipients = get_sample_recipients(num_recipients, 1000);
+        let utxos = get_sample_utxos(10, 10_000_000);
+        let result = split_payments_into_batches(&recipients, &utxos, 1.0, &get_sample_change_outputs()).unwrap();
+        assert!(result.len() > 1);
+        assert_eq!(result.iter().map(|(recipients, _)| recipients.len()).sum::<usize>(), num_recipients);
+        result.iter().for_each(|(recipients, utxos)| {
+            assert!(tx_fits_weight_limit(utxos.len(), &get_sample_batch_outputs(recipients)));
+            assert!(utxos.sum() >= recipients.sum());
+        });
+    }
+
+    #[test]
+    fn should_size_batches_by_their_real_output_scripts() {
+        // NOTE: These would all fit in one tx were each output the size of a P2PKH one.
+        let recipients = get_sample_recipients_to(2500, 1000, SAMPLE_P2WSH_ADDRESS);
+        let utxos = get_sample_utxos(10, 10_000_000);
+        let result = split_payments_into_batches(&recipients, &utxos, 1.0, &get_sample_change_outputs()).unwrap();
+        assert!(result.len() > 1);
+        result.iter().for_each(|(recipients, utxos)| {
+            assert!(tx_fits_weight_limit(utxos.len(), &get_sample_batch_outputs(recipients)));
+        });
+    }
+
+    #[test]
+    fn should_err_if_not_enough_value_to_batch_payments() {
+        let recipients = get_sample_recipients(3, 60_000);
+        let utxos = get_sample_utxos(1, 100_000);
+        assert!(split_payments_into_batches(&recipients, &utxos, 1.0, &get_sample_change_outputs()).is_err());
+    }
+}
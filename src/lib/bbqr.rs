@@ -0,0 +1,263 @@
+use crate::{
+    errors::AppError,
+    types::{
+        Byte,
+        Bytes,
+        Result,
+    },
+};
+
+pub const BBQR_HEADER_LENGTH: usize = 8;
+pub const BBQR_HEADER_PREFIX: &str = "B$";
+pub const BBQR_MAX_NUM_PARTS: usize = 1295; // NOTE: "ZZ" in base36.
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const BASE36_ALPHABET: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BbqrFileType {
+    Psbt,
+    Json,
+    Unicode,
+    Transaction,
+}
+
+impl BbqrFileType {
+    fn to_char(self) -> char {
+        match self {
+            BbqrFileType::Psbt => 'P',
+            BbqrFileType::Json => 'J',
+            BbqrFileType::Unicode => 'U',
+            BbqrFileType::Transaction => 'T',
+        }
+    }
+
+    fn from_char(file_type: char) -> Result<Self> {
+        match file_type {
+            'P' => Ok(BbqrFileType::Psbt),
+            'J' => Ok(BbqrFileType::Json),
+            'U' => Ok(BbqrFileType::Unicode),
+            'T' => Ok(BbqrFileType::Transaction),
+            _ => Err(AppError::Custom(format!("✘ Unsupported BBQr file type: '{}'", file_type))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BbqrPart {
+    pub index: usize,
+    pub total: usize,
+    pub encoding: char,
+    pub payload: String,
+    pub file_type: BbqrFileType,
+}
+
+pub fn encode_base32(bytes: &[Byte]) -> String {
+    let mut encoded = String::new();
+    let mut buffer: u32 = 0;
+    let mut num_bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        num_bits += 8;
+        while num_bits >= 5 {
+            num_bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> num_bits) & 31) as usize] as char);
+        }
+        buffer &= (1 << num_bits) - 1;
+    }
+    if num_bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - num_bits)) & 31) as usize] as char);
+    };
+    encoded
+}
+
+pub fn decode_base32(encoded: &str) -> Result<Bytes> {
+    let mut bytes = vec![];
+    let mut buffer: u32 = 0;
+    let mut num_bits = 0;
+    for c in encoded.trim_end_matches('=').chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|x| *x as char == c)
+            .ok_or_else(|| AppError::Custom(format!("✘ Not a valid base32 character: '{}'", c)))?;
+        buffer = (buffer << 5) | value as u32;
+        num_bits += 5;
+        if num_bits >= 8 {
+            num_bits -= 8;
+            bytes.push((buffer >> num_bits) as u8);
+        };
+        buffer &= (1 << num_bits) - 1;
+    }
+    Ok(bytes)
+}
+
+fn encode_base36(num: usize) -> String {
+    format!("{}{}", BASE36_ALPHABET[num / 36] as char, BASE36_ALPHABET[num % 36] as char)
+}
+
+fn decode_base36(encoded: &str) -> Result<usize> {
+    usize::from_str_radix(encoded, 36).map_err(|_| AppError::Custom(format!("✘ Not a valid BBQr number: '{}'", encoded)))
+}
+
+pub fn encode_bbqr(bytes: &[Byte], file_type: BbqrFileType, max_part_size: usize) -> Result<Vec<String>> {
+    info!("✔ Encoding {} bytes as BBQr...", bytes.len());
+    // NOTE: Every part but the last holds a multiple of 8 base32 chars so each one decodes to whole bytes.
+    let payload_size = (max_part_size.saturating_sub(BBQR_HEADER_LENGTH) / 8) * 8;
+    if payload_size == 0 {
+        return Err(AppError::Custom(format!("✘ BBQr part size must exceed {} chars!", BBQR_HEADER_LENGTH + 7)))
+    };
+    let encoded = encode_base32(bytes);
+    let chunks = encoded.as_bytes().chunks(payload_size).collect::<Vec<&[u8]>>();
+    if chunks.len() > BBQR_MAX_NUM_PARTS {
+        return Err(AppError::Custom(format!("✘ Data needs {} BBQr parts but the max is {}!", chunks.len(), BBQR_MAX_NUM_PARTS)))
+    };
+    Ok(
+        chunks
+            .iter()
+            .enumerate()
+            .map(|(i, chunk)|
+                format!(
+                    "{}2{}{}{}{}",
+                    BBQR_HEADER_PREFIX,
+                    file_type.to_char(),
+                    encode_base36(chunks.len()),
+                    encode_base36(i),
+                    String::from_utf8_lossy(chunk),
+                )
+            )
+            .collect()
+    )
+}
+
+fn parse_bbqr_part(part: &str) -> Result<BbqrPart> {
+    let part = part.trim();
+    if !part.is_ascii() || part.len() < BBQR_HEADER_LENGTH || !part.starts_with(BBQR_HEADER_PREFIX) {
+        return Err(AppError::Custom(format!("✘ Not a BBQr part: '{}'", part)))
+    };
+    let bbqr_part = BbqrPart {
+        encoding: part[2..3].chars().next().unwrap_or_default(),
+        file_type: BbqrFileType::from_char(part[3..4].chars().next().unwrap_or_default())?,
+        total: decode_base36(&part[4..6])?,
+        index: decode_base36(&part[6..8])?,
+        payload: part[BBQR_HEADER_LENGTH..].to_string(),
+    };
+    match bbqr_part.index < bbqr_part.total {
+        true => Ok(bbqr_part),
+        false => Err(AppError::Custom(format!("✘ BBQr part index {} is out of range!", bbqr_part.index))),
+    }
+}
+
+fn decode_bbqr_payload(encoding: char, payload: &str) -> Result<Bytes> {
+    match encoding {
+        '2' => decode_base32(payload),
+        'H' => Ok(hex::decode(payload)?),
+        _ => Err(AppError::Custom(format!("✘ Unsupported BBQr encoding: '{}'", encoding))),
+    }
+}
+
+pub fn decode_bbqr(parts: &[String]) -> Result<(BbqrFileType, Bytes)> {
+    info!("✔ Decoding {} BBQr part(s)...", parts.len());
+    let parsed_parts = parts.iter().map(|part| parse_bbqr_part(part)).collect::<Result<Vec<BbqrPart>>>()?;
+    let first_part = parsed_parts.first().ok_or_else(|| AppError::Custom("✘ No BBQr parts found!".to_string()))?;
+    let mut ordered_payloads: Vec<Option<String>> = vec![None; first_part.total];
+    for part in &parsed_parts {
+        if part.total != first_part.total || part.encoding != first_part.encoding || part.file_type != first_part.file_type {
+            return Err(AppError::Custom("✘ BBQr parts are from different sets!".to_string()))
+        };
+        match &ordered_payloads[part.index] {
+            // NOTE: Scanning an animated QR can capture the same part more than once.
+            Some(payload) if *payload != part.payload => return Err(AppError::Custom(
+                format!("✘ Conflicting BBQr parts for index {}!", part.index)
+            )),
+            _ => ordered_payloads[part.index] = Some(part.payload.clone()),
+        }
+    }
+    let missing_indices = ordered_payloads
+        .iter()
+        .enumerate()
+        .filter(|(_, payload)| payload.is_none())
+        .map(|(i, _)| (i + 1).to_string())
+        .collect::<Vec<String>>();
+    if !missing_indices.is_empty() {
+        return Err(AppError::Custom(
+            format!("✘ Missing BBQr part(s) {} of {}!", missing_indices.join(", "), first_part.total)
+        ))
+    };
+    let payload = ordered_payloads.into_iter().flatten().collect::<String>();
+    Ok((first_part.file_type, decode_bbqr_payload(first_part.encoding, &payload)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_encode_base32() {
+        assert_eq!(encode_base32(b""), "");
+        assert_eq!(encode_base32(b"f"), "MY");
+        assert_eq!(encode_base32(b"foobar"), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn should_decode_base32() {
+        assert_eq!(decode_base32("MZXW6YTBOI").unwrap(), b"foobar".to_vec());
+        assert_eq!(decode_base32("MZXW6YTBOI======").unwrap(), b"foobar".to_vec());
+        assert!(decode_base32("mzxw").is_err());
+    }
+
+    #[test]
+    fn should_encode_bbqr_in_one_part() {
+        let result = encode_bbqr(b"foobar", BbqrFileType::Unicode, 400).unwrap();
+        assert_eq!(result, vec!["B$2U0100MZXW6YTBOI".to_string()]);
+    }
+
+    #[test]
+    fn should_encode_bbqr_in_many_parts() {
+        let result = encode_bbqr(&[0xab; 100], BbqrFileType::Transaction, 24).unwrap();
+        assert_eq!(result.len(), 10);
+        assert!(result.iter().all(|part| part.len() <= 24));
+        assert!(result[0].starts_with("B$2T0A00"));
+        assert!(result[9].starts_with("B$2T0A09"));
+    }
+
+    #[test]
+    fn should_err_encoding_bbqr_with_tiny_part_size() {
+        assert!(encode_bbqr(b"foobar", BbqrFileType::Unicode, 15).is_err());
+    }
+
+    #[test]
+    fn should_decode_bbqr_parts_in_any_order_with_duplicates() {
+        let bytes = (0..=255).collect::<Bytes>();
+        let mut parts = encode_bbqr(&bytes, BbqrFileType::Psbt, 50).unwrap();
+        parts.reverse();
+        parts.push(parts[0].clone());
+        let (file_type, result) = decode_bbqr(&parts).unwrap();
+        assert_eq!(file_type, BbqrFileType::Psbt);
+        assert_eq!(result, bytes);
+    }
+
+    #[test]
+    fn should_decode_hex_encoded_bbqr() {
+        let (file_type, result) = decode_bbqr(&["B$HJ0100".to_string() + &hex::encode("{}")]).unwrap();
+        assert_eq!(file_type, BbqrFileType::Json);
+        assert_eq!(result, b"{}".to_vec());
+    }
+
+    #[test]
+    fn should_err_if_bbqr_part_missing() {
+        let mut parts = encode_bbqr(&[0xab; 100], BbqrFileType::Transaction, 24).unwrap();
+        parts.remove(3);
+        assert!(decode_bbqr(&parts).is_err());
+    }
+
+    #[test]
+    fn should_err_if_bbqr_parts_from_different_sets() {
+        let mut parts = encode_bbqr(&[0xab; 100], BbqrFileType::Transaction, 24).unwrap();
+        parts[1] = encode_bbqr(&[0xab; 100], BbqrFileType::Psbt, 24).unwrap()[1].clone();
+        assert!(decode_bbqr(&parts).is_err());
+    }
+
+    #[test]
+    fn should_err_on_non_bbqr_part() {
+        assert!(decode_bbqr(&["not a bbqr part".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    types::Result,
+    errors::AppError,
+    constants::MAX_BTC_SUPPLY_IN_SATS,
+};
+
+const BTC_DECIMALS: u32 = 8;
+const MBTC_DECIMALS: u32 = 5;
+const SATS_DECIMALS: u32 = 0;
+const SWEEP_AMOUNT_KEYWORD: &str = "max";
+
+fn split_amount_and_unit(amount: &str) -> (&str, u32) {
+    let units: [(&str, u32); 5] = [
+        ("mbtc", MBTC_DECIMALS),
+        ("btc", BTC_DECIMALS),
+        ("sats", SATS_DECIMALS),
+        ("sat", SATS_DECIMALS),
+        ("", SATS_DECIMALS),
+    ];
+    units
+        .iter()
+        .find(|(unit, _)| amount.ends_with(unit))
+        .map(|(unit, decimals)| (amount[..amount.len() - unit.len()].trim(), *decimals))
+        .unwrap_or((amount, SATS_DECIMALS))
+}
+
+fn get_overflow_err(amount: &str) -> AppError {
+    AppError::Custom(format!("✘ Amount '{}' is too large!", amount))
+}
+
+fn parse_decimal_amount(amount: &str, number: &str, decimals: u32) -> Result<u64> {
+    let (integer_part, fractional_part) = match number.find('.') {
+        Some(i) => (&number[..i], &number[i + 1..]),
+        None => (number, ""),
+    };
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (integer_part.is_empty() && fractional_part.is_empty()) || !is_digits(integer_part) || !is_digits(fractional_part) {
+        return Err(AppError::Custom(format!("✘ Not a valid amount: '{}'", amount)))
+    };
+    if fractional_part.len() > decimals as usize {
+        return Err(AppError::Custom(
+            format!("✘ Amount '{}' has more precision than a Satoshi allows!", amount)
+        ))
+    };
+    let integer_value = match integer_part.is_empty() {
+        true => 0,
+        false => integer_part.parse::<u64>().map_err(|_| get_overflow_err(amount))?,
+    };
+    let fractional_value = match fractional_part.is_empty() {
+        true => 0,
+        false => format!("{:0<width$}", fractional_part, width = decimals as usize)
+            .parse::<u64>()
+            .map_err(|_| get_overflow_err(amount))?,
+    };
+    integer_value
+        .checked_mul(10u64.pow(decimals))
+        .and_then(|sats| sats.checked_add(fractional_value))
+        .ok_or_else(|| get_overflow_err(amount))
+}
+
+pub fn is_sweep_amount(amount: &str) -> bool {
+    amount.trim().eq_ignore_ascii_case(SWEEP_AMOUNT_KEYWORD)
+}
+
+/// Whether a tx to the given recipients sweeps all its UTXOs, either via the `sweep` flag or a `max` amount.
+pub fn is_sweep(
+    sweep_flag: bool,
+    amounts: &[String],
+    num_recipients: usize,
+    subtract_fee_from: &[usize],
+) -> Result<bool> {
+    let sweep = sweep_flag || amounts.iter().any(|amount| is_sweep_amount(amount));
+    if sweep && num_recipients != 1 {
+        return Err(AppError::Custom("✘ Sweeping requires exactly one recipient!".to_string()))
+    };
+    match sweep && !subtract_fee_from.is_empty() {
+        true => Err(AppError::Custom("✘ Cannot subtract the fee from recipients when sweeping!".to_string())),
+        false => Ok(sweep),
+    }
+}
+
+pub fn parse_btc_amount(amount: &str) -> Result<u64> {
+    if is_sweep_amount(amount) {
+        return Ok(0)
+    };
+    let lowercase_amount = amount.trim().to_lowercase();
+    let (number, decimals) = split_amount_and_unit(&lowercase_amount);
+    let sats = parse_decimal_amount(amount, number, decimals)?;
+    match sats > MAX_BTC_SUPPLY_IN_SATS {
+        true => Err(AppError::Custom(format!("✘ Amount '{}' exceeds the total BTC supply!", amount))),
+        false => Ok(sats),
+    }
+}
+
+pub fn parse_btc_amounts(amounts: &[String]) -> Result<Vec<u64>> {
+    amounts.iter().map(|amount| parse_btc_amount(amount)).collect()
+}
+
+pub fn parse_fee_rate(fee_rate: &str) -> Result<f64> {
+    match fee_rate.trim().parse::<f64>() {
+        Ok(sats_per_byte) if sats_per_byte.is_finite() && sats_per_byte >= 0.0 => Ok(sats_per_byte),
+        _ => Err(AppError::Custom(format!("✘ Not a valid fee rate: '{}'", fee_rate))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_plain_sats_amount() {
+        assert_eq!(parse_btc_amount("2500").unwrap(), 2500);
+    }
+
+    #[test]
+    fn should_parse_sats_amount_with_suffix() {
+        assert_eq!(parse_btc_amount("2500sat").unwrap(), 2500);
+        assert_eq!(parse_btc_amount("2500 sats").unwrap(), 2500);
+    }
+
+    #[test]
+    fn should_parse_btc_amount() {
+        assert_eq!(parse_btc_amount("0.015btc").unwrap(), 1_500_000);
+        assert_eq!(parse_btc_amount("1BTC").unwrap(), 100_000_000);
+        assert_eq!(parse_btc_amount(".00000001btc").unwrap(), 1);
+    }
+
+    #[test]
+    fn should_parse_mbtc_amount() {
+        assert_eq!(parse_btc_amount("1.5mbtc").unwrap(), 150_000);
+        assert_eq!(parse_btc_amount("0.00001mBTC").unwrap(), 1);
+    }
+
+    #[test]
+    fn should_err_on_too_much_precision() {
+        assert!(parse_btc_amount("0.000000001btc").is_err());
+        assert!(parse_btc_amount("0.000001mbtc").is_err());
+        assert!(parse_btc_amount("1.5sat").is_err());
+        assert!(parse_btc_amount("1.5").is_err());
+    }
+
+    #[test]
+    fn should_err_on_overflowing_amount() {
+        assert!(parse_btc_amount("184467440737095516160").is_err());
+        assert!(parse_btc_amount("184467440738btc").is_err());
+    }
+
+    #[test]
+    fn should_err_on_amount_exceeding_supply() {
+        assert!(parse_btc_amount("21000001btc").is_err());
+        assert_eq!(parse_btc_amount("21000000btc").unwrap(), MAX_BTC_SUPPLY_IN_SATS);
+    }
+
+    #[test]
+    fn should_err_on_invalid_amount() {
+        assert!(parse_btc_amount("").is_err());
+        assert!(parse_btc_amount("btc").is_err());
+        assert!(parse_btc_amount("-1").is_err());
+        assert!(parse_btc_amount("1.2.3btc").is_err());
+        assert!(parse_btc_amount("1e8").is_err());
+    }
+
+    #[test]
+    fn should_parse_sweep_amount_keyword_as_zero() {
+        assert_eq!(parse_btc_amount("max").unwrap(), 0);
+        assert_eq!(parse_btc_amount("MAX").unwrap(), 0);
+    }
+
+    #[test]
+    fn should_parse_fractional_fee_rate() {
+        assert_eq!(parse_fee_rate("23").unwrap(), 23.0);
+        assert_eq!(parse_fee_rate("1.5").unwrap(), 1.5);
+    }
+
+    #[test]
+    fn should_err_on_invalid_fee_rate() {
+        assert!(parse_fee_rate("-1").is_err());
+        assert!(parse_fee_rate("NaN").is_err());
+        assert!(parse_fee_rate("fast").is_err());
+    }
+}
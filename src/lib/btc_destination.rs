@@ -0,0 +1,344 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+use bitcoin::{
+    network::constants::Network as BtcNetwork,
+    util::address::Address as BtcAddress,
+    blockdata::{
+        opcodes,
+        script::{
+            Script as BtcScript,
+            Builder as BtcScriptBuilder,
+        },
+    },
+};
+use crate::{
+    errors::AppError,
+    utils::is_witness_program,
+    types::{
+        Byte,
+        Bytes,
+        Result,
+    },
+};
+
+const SCRIPT_PREFIX: &str = "script:";
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CHECKSUM_LENGTH: usize = 6;
+const BECH32M_CONSTANT: u32 = 0x2bc8_30a3;
+const BECH32_GENERATORS: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+
+/// Somewhere an output can pay to: an address of any type, or a raw `script_pubkey`, prefixed with `script:`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BtcDestination {
+    Address(BtcAddress),
+    /// A segwit v1+ address, eg: P2TR. These use bech32m, which the `bitcoin` crate's address parser predates.
+    Bech32mAddress(String, BtcScript),
+    Script(BtcScript),
+}
+
+impl BtcDestination {
+    pub fn script_pubkey(&self) -> BtcScript {
+        match self {
+            BtcDestination::Address(address) => address.script_pubkey(),
+            BtcDestination::Bech32mAddress(_, script) | BtcDestination::Script(script) => script.clone(),
+        }
+    }
+
+    pub fn get_script_type(&self) -> &'static str {
+        get_script_type(&self.script_pubkey())
+    }
+
+    /// The network an address is for, or `None` for a raw script, which could be for any.
+    pub fn get_network(&self) -> Option<BtcNetwork> {
+        match self {
+            BtcDestination::Address(address) => Some(address.network),
+            BtcDestination::Bech32mAddress(address, _) => get_bech32_network(address),
+            BtcDestination::Script(_) => None,
+        }
+    }
+
+    /// Errs if the destination is an address for a network other than `network`, or if an output of `amount` sats
+    /// paying to it wouldn't be relayed, ie: it's a non-standard script, or an `OP_RETURN` script with a value,
+    /// unless `allow_non_standard` is set.
+    pub fn check(&self, amount: u64, network: BtcNetwork, allow_non_standard: bool) -> Result<()> {
+        match (self.get_network(), self.get_script_type()) {
+            (Some(address_network), _) if address_network != network => Err(AppError::Custom(
+                format!("✘ Address {} is for {} not {}!", self, address_network, network)
+            )),
+            (_, "nonstandard") if !allow_non_standard => Err(AppError::Custom(
+                format!("✘ Script {} is non-standard, use `--allowNonStandard` to pay to it anyway!", self)
+            )),
+            (_, "op_return") if amount > 0 && !allow_non_standard => Err(AppError::Custom(format!(
+                "✘ `OP_RETURN` script {} would burn {} sats, use `--allowNonStandard` to pay to it anyway!",
+                self,
+                amount,
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromStr for BtcDestination {
+    type Err = AppError;
+
+    fn from_str(destination: &str) -> Result<Self> {
+        let destination = destination.trim();
+        if let Some(script_hex) = destination.strip_prefix(SCRIPT_PREFIX) {
+            return match hex::decode(script_hex) {
+                Ok(bytes) if !bytes.is_empty() => Ok(BtcDestination::Script(BtcScript::from(bytes))),
+                _ => Err(AppError::Custom(format!("✘ '{}' is not a valid hex script!", destination))),
+            }
+        };
+        match BtcAddress::from_str(destination) {
+            Ok(address) => Ok(BtcDestination::Address(address)),
+            Err(address_err) => match decode_bech32m_address(destination) {
+                Ok(script) => Ok(BtcDestination::Bech32mAddress(destination.to_lowercase(), script)),
+                Err(_) => Err(AppError::Custom(format!(
+                    "✘ '{}' is not an address: {} (prefix raw scripts with `{}`)",
+                    destination,
+                    address_err,
+                    SCRIPT_PREFIX,
+                ))),
+            },
+        }
+    }
+}
+
+impl fmt::Display for BtcDestination {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BtcDestination::Address(address) => write!(f, "{}", address),
+            BtcDestination::Bech32mAddress(address, _) => write!(f, "{}", address),
+            BtcDestination::Script(script) => write!(f, "{}{}", SCRIPT_PREFIX, hex::encode(script.as_bytes())),
+        }
+    }
+}
+
+fn get_witness_version(script: &BtcScript) -> Option<u8> {
+    match is_witness_program(script) {
+        false => None,
+        true => match script.as_bytes()[0] {
+            0 => Some(0),
+            opcode => Some(opcode - opcodes::all::OP_PUSHNUM_1.into_u8() + 1),
+        },
+    }
+}
+
+pub fn get_script_type(script: &BtcScript) -> &'static str {
+    if script.is_p2pkh() {
+        "p2pkh"
+    } else if script.is_p2sh() {
+        "p2sh"
+    } else if script.is_v0_p2wpkh() {
+        "p2wpkh"
+    } else if script.is_v0_p2wsh() {
+        "p2wsh"
+    } else if get_witness_version(script) == Some(1) && script.len() == 34 {
+        "p2tr"
+    } else if get_witness_version(script).is_some() {
+        "witness_unknown"
+    } else if script.is_op_return() {
+        "op_return"
+    } else if script.is_p2pk() {
+        "p2pk"
+    } else {
+        "nonstandard"
+    }
+}
+
+fn get_bech32_hrp(network: BtcNetwork) -> &'static str {
+    match network {
+        BtcNetwork::Bitcoin => "bc",
+        BtcNetwork::Testnet => "tb",
+        BtcNetwork::Regtest => "bcrt",
+    }
+}
+
+fn get_bech32_network(address: &str) -> Option<BtcNetwork> {
+    let hrp = &address[..address.rfind('1')?];
+    [BtcNetwork::Bitcoin, BtcNetwork::Testnet, BtcNetwork::Regtest]
+        .iter()
+        .find(|network| get_bech32_hrp(**network).eq_ignore_ascii_case(hrp))
+        .copied()
+}
+
+fn get_bech32_polymod(values: &[Byte]) -> u32 {
+    values.iter().fold(1, |checksum, value| {
+        let top = checksum >> 25;
+        BECH32_GENERATORS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold((checksum & 0x1ff_ffff) << 5 ^ u32::from(*value), |checksum, (_, generator)| checksum ^ generator)
+    })
+}
+
+fn expand_bech32_hrp(hrp: &str) -> Bytes {
+    hrp.bytes().map(|byte| byte >> 5).chain(vec![0]).chain(hrp.bytes().map(|byte| byte & 31)).collect()
+}
+
+fn convert_bits(data: &[Byte], from: u32, to: u32, pad: bool) -> Result<Bytes> {
+    let mut accumulator = 0u32;
+    let mut num_bits = 0;
+    let max_value = (1 << to) - 1;
+    let mut converted = vec![];
+    for value in data {
+        accumulator = (accumulator << from) | u32::from(*value);
+        num_bits += from;
+        while num_bits >= to {
+            num_bits -= to;
+            converted.push(((accumulator >> num_bits) & max_value) as Byte);
+        }
+    }
+    match pad {
+        true => if num_bits > 0 {
+            converted.push(((accumulator << (to - num_bits)) & max_value) as Byte);
+        },
+        false => if num_bits >= from || (accumulator << (to - num_bits)) & max_value != 0 {
+            return Err(AppError::Custom("✘ Invalid padding in bech32m data!".to_string()))
+        },
+    };
+    Ok(converted)
+}
+
+fn get_witness_program_script(version: u8, program: &[Byte]) -> BtcScript {
+    BtcScriptBuilder::new()
+        .push_opcode((opcodes::all::OP_PUSHNUM_1.into_u8() + version - 1).into())
+        .push_slice(program)
+        .into_script()
+}
+
+pub fn decode_bech32m_address(address: &str) -> Result<BtcScript> {
+    let err = |reason: &str| AppError::Custom(format!("✘ Not a valid bech32m address: '{}' - {}", address, reason));
+    if address.to_lowercase() != address && address.to_uppercase() != address {
+        return Err(err("mixed case"))
+    };
+    let address = address.to_lowercase();
+    let separator_index = address.rfind('1').ok_or_else(|| err("no separator"))?;
+    let hrp = &address[..separator_index];
+    if get_bech32_network(&address).is_none() {
+        return Err(err("unknown network prefix"))
+    };
+    let data = address[separator_index + 1..]
+        .bytes()
+        .map(|byte| BECH32_CHARSET.iter().position(|char| *char == byte).map(|position| position as Byte))
+        .collect::<Option<Bytes>>()
+        .ok_or_else(|| err("invalid character"))?;
+    if data.len() <= BECH32_CHECKSUM_LENGTH {
+        return Err(err("too short"))
+    };
+    if get_bech32_polymod(&[expand_bech32_hrp(hrp), data.clone()].concat()) != BECH32M_CONSTANT {
+        return Err(err("invalid checksum"))
+    };
+    let version = data[0];
+    let program = convert_bits(&data[1..data.len() - BECH32_CHECKSUM_LENGTH], 5, 8, false)?;
+    match (1..=16).contains(&version) && program.len() >= 2 && program.len() <= 40 {
+        true => Ok(get_witness_program_script(version, &program)),
+        false => Err(err("invalid witness version or program length")),
+    }
+}
+
+fn encode_bech32m_address(version: u8, program: &[Byte], network: BtcNetwork) -> Result<String> {
+    let hrp = get_bech32_hrp(network);
+    let data = [vec![version], convert_bits(program, 8, 5, true)?].concat();
+    let polymod = get_bech32_polymod(&[expand_bech32_hrp(hrp), data.clone(), vec![0; BECH32_CHECKSUM_LENGTH]].concat())
+        ^ BECH32M_CONSTANT;
+    let checksum = (0..BECH32_CHECKSUM_LENGTH).map(|i| ((polymod >> (5 * (5 - i))) & 31) as Byte);
+    Ok(
+        hrp.chars()
+            .chain(vec!['1'])
+            .chain(data.into_iter().chain(checksum).map(|value| BECH32_CHARSET[value as usize] as char))
+            .collect()
+    )
+}
+
+pub fn get_address_from_script(script: &BtcScript, network: BtcNetwork) -> Option<String> {
+    match get_witness_version(script) {
+        Some(version) if version >= 1 => encode_bech32m_address(version, &script.as_bytes()[2..], network).ok(),
+        _ => BtcAddress::from_script(script, network).map(|address| address.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::SAMPLE_TARGET_BTC_ADDRESS;
+
+    const SAMPLE_P2TR_ADDRESS: &str = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+    const SAMPLE_P2TR_SCRIPT: &str = "512079be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798";
+
+    #[test]
+    fn should_parse_destinations_of_every_type() {
+        let cases = [
+            (SAMPLE_TARGET_BTC_ADDRESS, "p2pkh"),
+            ("2N3oefVeg6stiTb5Kh3ozCSkaqmx91FDbsm", "p2sh"),
+            ("tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx", "p2wpkh"),
+            ("tb1qrp33g0q5c5txsp9arysrx4k6zdkfs4nce4xj0gdcccefvpysxf3q0sl5k7", "p2wsh"),
+            (SAMPLE_P2TR_ADDRESS, "p2tr"),
+            ("script:6a0568656c6c6f", "op_return"),
+            ("script:51", "nonstandard"),
+        ];
+        cases.iter().for_each(|(destination, expected_type)| {
+            let result = BtcDestination::from_str(destination).unwrap();
+            assert_eq!(result.get_script_type(), *expected_type, "Wrong type for {}", destination);
+            assert_eq!(result.to_string(), *destination);
+        });
+    }
+
+    #[test]
+    fn should_decode_bech32m_address() {
+        let result = decode_bech32m_address(SAMPLE_P2TR_ADDRESS).unwrap();
+        assert_eq!(hex::encode(result.as_bytes()), SAMPLE_P2TR_SCRIPT);
+        assert!(decode_bech32m_address(&SAMPLE_P2TR_ADDRESS.to_uppercase()).is_ok());
+    }
+
+    #[test]
+    fn should_err_decoding_bech32m_address_with_bad_checksum() {
+        let address = SAMPLE_P2TR_ADDRESS.replace("jj0", "jj2");
+        assert!(decode_bech32m_address(&address).is_err());
+    }
+
+    #[test]
+    fn should_err_decoding_bech32_address_as_bech32m() {
+        assert!(decode_bech32m_address("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4").is_err());
+    }
+
+    #[test]
+    fn should_get_address_from_script() {
+        let script = BtcScript::from(hex::decode(SAMPLE_P2TR_SCRIPT).unwrap());
+        assert_eq!(get_address_from_script(&script, BtcNetwork::Bitcoin).unwrap(), SAMPLE_P2TR_ADDRESS);
+        let script = BtcDestination::from_str(SAMPLE_TARGET_BTC_ADDRESS).unwrap().script_pubkey();
+        assert_eq!(get_address_from_script(&script, BtcNetwork::Testnet).unwrap(), SAMPLE_TARGET_BTC_ADDRESS);
+    }
+
+    #[test]
+    fn should_err_parsing_invalid_destination() {
+        assert!(BtcDestination::from_str("not-an-address").is_err());
+        assert!(BtcDestination::from_str("").is_err());
+        assert!(BtcDestination::from_str("6a0568656c6c6f").is_err());
+        assert!(BtcDestination::from_str("script:").is_err());
+    }
+
+    #[test]
+    fn should_err_checking_address_for_wrong_network() {
+        let p2tr = BtcDestination::from_str(SAMPLE_P2TR_ADDRESS).unwrap();
+        assert_eq!(p2tr.get_network(), Some(BtcNetwork::Bitcoin));
+        assert!(p2tr.check(546, BtcNetwork::Bitcoin, false).is_ok());
+        assert!(p2tr.check(546, BtcNetwork::Testnet, false).is_err());
+        let p2pkh = BtcDestination::from_str(SAMPLE_TARGET_BTC_ADDRESS).unwrap();
+        assert!(p2pkh.check(546, BtcNetwork::Bitcoin, false).is_err());
+    }
+
+    #[test]
+    fn should_err_checking_non_standard_destinations_unless_allowed() {
+        let non_standard = BtcDestination::from_str("script:51").unwrap();
+        assert!(non_standard.check(546, BtcNetwork::Bitcoin, false).is_err());
+        assert!(non_standard.check(546, BtcNetwork::Bitcoin, true).is_ok());
+        let op_return = BtcDestination::from_str("script:6a0568656c6c6f").unwrap();
+        assert!(op_return.check(0, BtcNetwork::Bitcoin, false).is_ok());
+        assert!(op_return.check(1, BtcNetwork::Bitcoin, false).is_err());
+        assert!(op_return.check(1, BtcNetwork::Bitcoin, true).is_ok());
+    }
+}
@@ -19,7 +19,7 @@ use secp256k1::{
         PublicKey,
     },
 };
-use crate::lib::{
+use crate::{
     utils::generate_random_private_key,
     types::{
         Bytes,
@@ -42,7 +42,7 @@ impl BtcPrivateKey {
                 PrivateKey {
                     network,
                     compressed: true,
-                    key: SecretKey::from_slice(&slice)?
+                    key: SecretKey::from_slice(slice)?
                 }
             )
         )
@@ -124,7 +124,7 @@ impl fmt::Display for BtcPrivateKey {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::lib::test_utils::{
+    use crate::test_utils::{
         SAMPLE_BTC_PUBLIC_KEY,
         SAMPLE_TARGET_BTC_ADDRESS,
         get_sample_btc_private_key,
@@ -1,79 +1,29 @@
-use crate::lib::{
-    errors::AppError,
+use crate::{
     btc_private_key::BtcPrivateKey,
-    utils::{
-        get_script_sig,
-        create_new_tx_output,
-        calculate_btc_tx_fee,
-    },
+    utils::get_script_sig,
     types::{
         Bytes,
         Result,
-        BtcUtxosAndValues,
-        BtcAddressAndAmount,
-        BtcAddressesAndAmounts,
     },
 };
-use bitcoin::{
-    blockdata::{
-        transaction::{
-            TxIn as BtcUtxo,
-            TxOut as BtcTxOut,
-            Transaction as BtcTransaction,
-        },
-    }
+use bitcoin::blockdata::transaction::{
+    TxIn as BtcUtxo,
+    Transaction as BtcTransaction,
 };
 
 pub const VERSION: u32 = 1;
-pub const LOCK_TIME: u32 = 0;
 pub const SIGN_ALL_HASH_TYPE: u8 = 1;
+pub const MAX_SWEEP_ITERATIONS: usize = 5;
 
-pub fn create_signed_raw_btc_tx_for_n_input_n_outputs(
-    sats_per_byte: usize,
-    recipient_addresses_and_amounts: BtcAddressesAndAmounts,
-    remainder_btc_address: &str,
-    btc_private_key: BtcPrivateKey,
-    utxos_and_values: &BtcUtxosAndValues,
-    maybe_op_return_output: Option<BtcTxOut>,
-) -> Result<BtcTransaction> {
-    let total_to_spend = recipient_addresses_and_amounts.sum();
-    let fee = calculate_btc_tx_fee(
-        utxos_and_values.len(),
-        match &maybe_op_return_output {
-            None => recipient_addresses_and_amounts.len(),
-            Some(_) => recipient_addresses_and_amounts.len() + 1,
-        },
-        sats_per_byte
-    );
-    let utxo_total = utxos_and_values.sum();
-    info!("✔ UTXO(s) total:  {}", utxo_total);
-    info!("✔ Outgoing total: {}", total_to_spend);
-    info!("✔ Change amount:  {}", utxo_total - (total_to_spend + fee));
-    info!("✔ Tx fee:         {}", fee);
-    if total_to_spend + fee > utxo_total {
-        return Err(AppError::Custom("✘ Not enough UTXO value to make transaction!".to_string()))
-    }
-    let mut outputs = recipient_addresses_and_amounts
-        .to_vec()
-        .iter()
-        .map(create_new_tx_output)
-        .collect::<Result<Vec<BtcTxOut>>>()?;
-    if let Some(op_return_output) = maybe_op_return_output {
-        outputs.push(op_return_output);
-    };
-    let change = utxo_total - total_to_spend - fee;
-    if change > 0 {
-        outputs.push(create_new_tx_output(&BtcAddressAndAmount::new(remainder_btc_address, change)?)?)
-    };
-    let utxos = utxos_and_values.get_utxos();
-    let tx = BtcTransaction { output: outputs, version: VERSION, lock_time: LOCK_TIME, input: utxos.clone() };
+pub fn sign_btc_tx(tx: BtcTransaction, btc_private_key: &BtcPrivateKey) -> Result<BtcTransaction> {
+    let utxos = tx.input.clone();
     let signatures = utxos
         .iter()
         .enumerate()
         .map(|(i, utxo)| tx.signature_hash(i, &utxo.script_sig, SIGN_ALL_HASH_TYPE as u32))
         .map(|hash| hash.to_vec())
         .map(|tx_hash_to_sign|
-            btc_private_key.sign_hash_and_append_btc_hash_type(tx_hash_to_sign.to_vec(), SIGN_ALL_HASH_TYPE as u8)
+            btc_private_key.sign_hash_and_append_btc_hash_type(tx_hash_to_sign.to_vec(), SIGN_ALL_HASH_TYPE)
         )
         .collect::<Result<Vec<Bytes>>>()?;
     let utxos_with_signatures = utxos
@@ -97,35 +47,3 @@ pub fn create_signed_raw_btc_tx_for_n_input_n_outputs(
         }
     )
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use bitcoin::consensus::encode::serialize as btc_serialize;
-    use crate::lib::test_utils::{
-        get_sample_utxo,
-        get_sample_btc_private_key,
-    };
-
-    #[test]
-    fn should_create_tx_correctly() {
-        let expected_result = "01000000016e3fa15afcd9b579b7ed082e0ee8cfba1f27a6cf007cb7ca95b06ab0fda2880c020000006b483045022100d5dec195ae624af5708ca7834b9e052b93bcf935c92c19dcf1be39ce1aa60d31022019db89b0938e14c82f1f66ad8ab7377fe5895c78293df5b3a50ffa221fa04700012103d2a5e3b162eb580fe2ce023cd5e0dddbb6286923acde77e3e5468314dc9373f7ffffffff0289130000000000001976a9149ae6e42c56f1ea319cfc704ad50db0683015029b88ac333a0d00000000001976a91454102783c8640c5144d039cea53eb7dbb470081488ac00000000";
-        let sats_per_byte = 100;
-        let recipient_and_amount = BtcAddressAndAmount::new("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", 5001).unwrap();
-        let recipients_and_amounts = BtcAddressesAndAmounts(vec![recipient_and_amount]);
-        let remainder_btc_address = "moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE";
-        let btc_private_key = get_sample_btc_private_key();
-        let utxos_and_values = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
-        let maybe_op_return_output = None;
-        let result = create_signed_raw_btc_tx_for_n_input_n_outputs(
-            sats_per_byte,
-            recipients_and_amounts,
-            remainder_btc_address,
-            btc_private_key,
-            &utxos_and_values,
-            maybe_op_return_output,
-        ).unwrap();
-        let result_hex = hex::encode(btc_serialize(&result));
-        assert_eq!(result_hex, expected_result);
-    }
-}
@@ -0,0 +1,508 @@
+use std::{
+    fmt,
+    slice,
+    fs::File,
+    io::{
+        Write,
+        BufRead,
+        BufReader,
+    },
+};
+use bitcoin::blockdata::transaction::{
+    TxOut as BtcTxOut,
+    Transaction as BtcTransaction,
+};
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use crate::{
+    state::State,
+    errors::AppError,
+    decode_btc_tx::get_btc_tx_vsize,
+    create_tx::get_tx_builder_from_state,
+    verify_btc_tx::get_dust_threshold,
+    utils::{
+        format_sats,
+        create_new_tx_output,
+        calculate_btc_tx_size_for_outputs,
+    },
+    types::{
+        Result,
+        BtcUtxosAndValues,
+        BtcAddressAndAmount,
+        BtcAddressesAndAmounts,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BtcTxSummary {
+    pub fee: u64,
+    pub change: u64,
+    pub num_inputs: usize,
+    pub utxo_total: u64,
+    pub estimated_size: u64,
+    pub sats_per_byte: f64,
+    pub outgoing_total: u64,
+    pub change_address: String,
+    pub recipients: BtcAddressesAndAmounts,
+}
+
+pub fn subtract_fee_from_recipients(
+    recipients: &BtcAddressesAndAmounts,
+    indices: &[usize],
+    fee: u64,
+) -> Result<BtcAddressesAndAmounts> {
+    let mut unique_indices = indices.to_vec();
+    unique_indices.sort_unstable();
+    unique_indices.dedup();
+    if unique_indices.len() != indices.len() {
+        return Err(AppError::Custom("✘ Cannot subtract the fee from the same recipient twice!".to_string()))
+    };
+    if let Some(i) = indices.iter().find(|i| **i >= recipients.len()) {
+        return Err(AppError::Custom(format!("✘ No recipient at index {} to subtract the fee from!", i)))
+    };
+    let indices_total = indices.iter().map(|i| u128::from(recipients.0[*i].amount)).sum::<u128>();
+    if indices_total == 0 {
+        return Err(AppError::Custom("✘ Recipients to subtract the fee from have no amounts to cover it!".to_string()))
+    };
+    // NOTE: Each recipient pays a share of the fee in proportion to its amount. The few sats left over from rounding
+    // the shares down are taken one each from the recipients in the order their indices were given.
+    let fee_shares = indices
+        .iter()
+        .map(|i| (u128::from(fee) * u128::from(recipients.0[*i].amount) / indices_total) as u64)
+        .collect::<Vec<u64>>();
+    let fee_remainder = fee - fee_shares.iter().sum::<u64>();
+    let mut adjusted_recipients = recipients.to_vec();
+    for (n, (i, fee_share)) in indices.iter().zip(fee_shares).enumerate() {
+        let deduction = match (n as u64) < fee_remainder {
+            true => fee_share + 1,
+            false => fee_share,
+        };
+        let recipient = &mut adjusted_recipients[*i];
+        recipient.amount = recipient
+            .amount
+            .checked_sub(deduction)
+            .ok_or_else(|| AppError::Custom(format!("✘ Recipient #{}'s amount cannot cover its share of the fee!", i)))?;
+        let output = create_new_tx_output(recipient)?;
+        if output.value < get_dust_threshold(&output) {
+            return Err(AppError::Custom(
+                format!("✘ Recipient #{}'s amount of {} sats would be dust after subtracting the fee!", i, output.value)
+            ))
+        };
+    }
+    Ok(BtcAddressesAndAmounts(adjusted_recipients))
+}
+
+fn get_outputs_total(outputs: &[BtcTxOut]) -> u64 {
+    outputs.iter().map(|output| output.value).sum()
+}
+
+fn get_estimated_tx_size(
+    num_inputs: usize,
+    recipients: &[BtcAddressAndAmount],
+    extra_outputs: &[BtcTxOut],
+) -> Result<u64> {
+    let mut outputs = recipients.iter().map(create_new_tx_output).collect::<Result<Vec<BtcTxOut>>>()?;
+    outputs.extend_from_slice(extra_outputs);
+    Ok(calculate_btc_tx_size_for_outputs(num_inputs, &outputs))
+}
+
+impl BtcTxSummary {
+    pub fn new(
+        sats_per_byte: f64,
+        recipients: &BtcAddressesAndAmounts,
+        change_address: &str,
+        utxos_and_values: &BtcUtxosAndValues,
+        extra_outputs: &[BtcTxOut],
+        subtract_fee_from: &[usize],
+    ) -> Result<Self> {
+        let utxo_total = utxos_and_values.sum();
+        let get_recipients_after_fee = |fee: u64| match subtract_fee_from.is_empty() {
+            true => Ok(recipients.clone()),
+            false => subtract_fee_from_recipients(recipients, subtract_fee_from, fee),
+        };
+        let summary = BtcTxSummary {
+            fee: 0,
+            change: 0,
+            utxo_total,
+            sats_per_byte,
+            estimated_size: 0,
+            outgoing_total: 0,
+            recipients: recipients.clone(),
+            num_inputs: utxos_and_values.len(),
+            change_address: change_address.to_string(),
+        };
+        // NOTE: The change output is sized in if there'll be change worth creating, else any leftover goes to the fee.
+        let change_output = create_new_tx_output(&BtcAddressAndAmount::new(change_address, 0)?)?;
+        let outputs_with_change = [extra_outputs, slice::from_ref(&change_output)].concat();
+        let size_with_change = get_estimated_tx_size(utxos_and_values.len(), &recipients.0, &outputs_with_change)?;
+        let fee_with_change = (size_with_change as f64 * sats_per_byte).ceil() as u64;
+        if let Ok(recipients) = get_recipients_after_fee(fee_with_change) {
+            let outgoing_total = recipients.sum() + get_outputs_total(extra_outputs);
+            let change = utxo_total.saturating_sub(outgoing_total + fee_with_change);
+            if change > 0 && change >= get_dust_threshold(&BtcTxOut { value: change, ..change_output }) {
+                return Ok(
+                    BtcTxSummary {
+                        change,
+                        recipients,
+                        outgoing_total,
+                        fee: fee_with_change,
+                        estimated_size: size_with_change,
+                        ..summary
+                    }
+                )
+            };
+        };
+        let estimated_size = get_estimated_tx_size(utxos_and_values.len(), &recipients.0, extra_outputs)?;
+        let fee = (estimated_size as f64 * sats_per_byte).ceil() as u64;
+        let recipients = get_recipients_after_fee(fee)?;
+        let outgoing_total = recipients.sum() + get_outputs_total(extra_outputs);
+        if outgoing_total + fee > utxo_total {
+            return Err(AppError::Custom("✘ Not enough UTXO value to make transaction!".to_string()))
+        };
+        Ok(BtcTxSummary { recipients, estimated_size, outgoing_total, fee: utxo_total - outgoing_total, ..summary })
+    }
+
+    pub fn new_sweep(
+        sats_per_byte: f64,
+        recipient: &BtcAddressAndAmount,
+        utxos_and_values: &BtcUtxosAndValues,
+        extra_outputs: &[BtcTxOut],
+    ) -> Result<Self> {
+        let utxo_total = utxos_and_values.sum();
+        let estimated_size = get_estimated_tx_size(utxos_and_values.len(), slice::from_ref(recipient), extra_outputs)?;
+        let fee = (estimated_size as f64 * sats_per_byte).ceil() as u64;
+        let outgoing_total = utxo_total
+            .checked_sub(fee)
+            .ok_or_else(|| AppError::Custom("✘ Not enough UTXO value to pay the sweep tx's fee!".to_string()))?;
+        let swept_amount = outgoing_total
+            .checked_sub(get_outputs_total(extra_outputs))
+            .ok_or_else(|| AppError::Custom("✘ Not enough UTXO value to pay the sweep tx's outputs!".to_string()))?;
+        Ok(
+            BtcTxSummary {
+                fee,
+                utxo_total,
+                sats_per_byte,
+                estimated_size,
+                outgoing_total,
+                change: 0,
+                num_inputs: utxos_and_values.len(),
+                change_address: recipient.address.to_string(),
+                recipients: BtcAddressesAndAmounts(vec![BtcAddressAndAmount { amount: swept_amount, ..recipient.clone() }]),
+            }
+        )
+    }
+
+    /// The summary with the fee & size of the signed `tx` it describes, which can differ from the estimates.
+    pub fn with_signed_tx(self, tx: &BtcTransaction) -> Self {
+        let outputs_total = get_outputs_total(&tx.output);
+        BtcTxSummary {
+            estimated_size: get_btc_tx_vsize(tx),
+            fee: self.utxo_total.saturating_sub(outputs_total),
+            outgoing_total: outputs_total.saturating_sub(self.change),
+            ..self
+        }
+    }
+
+    pub fn get_fee_percent(&self) -> f64 {
+        match self.outgoing_total {
+            0 => 100.0,
+            _ => self.fee as f64 / self.outgoing_total as f64 * 100.0,
+        }
+    }
+
+    pub fn check_fee_caps(&self, maybe_max_fee: Option<u64>, maybe_max_fee_percent: Option<f64>) -> Result<()> {
+        if let Some(max_fee) = maybe_max_fee {
+            if self.fee > max_fee {
+                return Err(AppError::Custom(
+                    format!("✘ Tx fee of {} sats exceeds the maximum of {} sats!", self.fee, max_fee)
+                ))
+            }
+        };
+        if let Some(max_fee_percent) = maybe_max_fee_percent {
+            if self.get_fee_percent() > max_fee_percent {
+                return Err(AppError::Custom(
+                    format!(
+                        "✘ Tx fee of {:.2}% of the outgoing total exceeds the maximum of {}%!",
+                        self.get_fee_percent(),
+                        max_fee_percent,
+                    )
+                ))
+            }
+        };
+        Ok(())
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "fee": self.fee,
+            "change": self.change,
+            "num_inputs": self.num_inputs,
+            "utxo_total": self.utxo_total,
+            "sats_per_byte": self.sats_per_byte,
+            "estimated_size": self.estimated_size,
+            "outgoing_total": self.outgoing_total,
+            "change_address": self.change_address,
+            "recipients": self
+                .recipients
+                .to_vec()
+                .iter()
+                .map(|recipient| json!({
+                    "label": recipient.label,
+                    "amount": recipient.amount,
+                    "address": recipient.address.to_string(),
+                    "type": recipient.address.get_script_type(),
+                }))
+                .collect::<Vec<JsonValue>>(),
+        })
+    }
+}
+
+impl fmt::Display for BtcTxSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "❍ Transaction Summary ❍\n")?;
+        for (i, recipient) in self.recipients.to_vec().iter().enumerate() {
+            match &recipient.label {
+                Some(label) => writeln!(
+                    f,
+                    "    Recipient #{}: {} [{}] ({}) ❍ {}",
+                    i + 1,
+                    recipient.address,
+                    recipient.address.get_script_type(),
+                    label,
+                    format_sats(recipient.amount),
+                )?,
+                None => writeln!(
+                    f,
+                    "    Recipient #{}: {} [{}] ❍ {}",
+                    i + 1,
+                    recipient.address,
+                    recipient.address.get_script_type(),
+                    format_sats(recipient.amount),
+                )?,
+            }
+        }
+        writeln!(f, "    Change:        {} ❍ {}", self.change_address, format_sats(self.change))?;
+        writeln!(f, "    Inputs:        {} UTXO(s) ❍ {}", self.num_inputs, format_sats(self.utxo_total))?;
+        writeln!(f, "    Outgoing:      {}", format_sats(self.outgoing_total))?;
+        write!(
+            f,
+            "    Fee:           {} ❍ {} sats-per-byte for ~{} bytes",
+            format_sats(self.fee),
+            self.sats_per_byte,
+            self.estimated_size,
+        )
+    }
+}
+
+pub fn get_tx_summary_and_add_to_state(state: State) -> Result<State> {
+    info!("✔ Getting tx summary and adding to state...");
+    get_tx_builder_from_state(&state)
+        .and_then(|tx_builder| tx_builder.get_summary())
+        .and_then(|summary| state.add_btc_tx_summary(summary))
+}
+
+fn get_user_confirmation(prompt: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", prompt);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    // NOTE: Stdin may already have been consumed by a piped input, so prefer the terminal.
+    match File::open("/dev/tty") {
+        Ok(tty) => BufReader::new(tty).read_line(&mut answer)?,
+        Err(_) => std::io::stdin().lock().read_line(&mut answer)?,
+    };
+    Ok(matches!(&answer.trim().to_lowercase()[..], "y" | "yes"))
+}
+
+pub fn maybe_confirm_tx_summary_in_state(state: State) -> Result<State> {
+    info!("✔ Maybe confirming tx summary in state...");
+    let summary = state.get_btc_tx_summary()?;
+    info!("✔ {}", summary);
+    summary.check_fee_caps(state.max_fee, state.max_fee_pct)?;
+    match state.yes {
+        true => {
+            info!("✔ Skipping confirmation prompt!");
+            Ok(state)
+        },
+        false => {
+            eprintln!("{}\n", summary);
+            match get_user_confirmation("Sign this transaction?")? {
+                true => Ok(state),
+                false => Err(AppError::Custom("✘ Transaction signing cancelled!".to_string())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        types::BtcAddressAndAmount,
+        test_utils::{
+            get_sample_utxo,
+            SAMPLE_TARGET_BTC_ADDRESS,
+        },
+    };
+
+    fn get_sample_summary() -> BtcTxSummary {
+        let recipient = BtcAddressAndAmount::new("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", 5001).unwrap();
+        BtcTxSummary::new(
+            100.0,
+            &BtcAddressesAndAmounts(vec![recipient]),
+            SAMPLE_TARGET_BTC_ADDRESS,
+            &BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]),
+            &[],
+            &[],
+        ).unwrap()
+    }
+
+    #[test]
+    fn should_get_tx_summary() {
+        let expected_fee = 22700;
+        let expected_change = 891168 - 5001 - expected_fee;
+        let result = get_sample_summary();
+        assert_eq!(result.fee, expected_fee);
+        assert_eq!(result.change, expected_change);
+        assert_eq!(result.num_inputs, 1);
+        assert_eq!(result.estimated_size, 227);
+    }
+
+    #[test]
+    fn should_give_leftover_to_fee_if_too_little_for_change() {
+        let fee_without_change = 19300;
+        let recipient = BtcAddressAndAmount::new("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", 891168 - fee_without_change - 100)
+            .unwrap();
+        let result = BtcTxSummary::new(
+            100.0,
+            &BtcAddressesAndAmounts(vec![recipient]),
+            SAMPLE_TARGET_BTC_ADDRESS,
+            &BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]),
+            &[],
+            &[],
+        ).unwrap();
+        assert_eq!(result.change, 0);
+        assert_eq!(result.estimated_size, 193);
+        assert_eq!(result.fee, fee_without_change + 100);
+    }
+
+    #[test]
+    fn should_size_outputs_by_their_script_type() {
+        let p2tr_address = "bc1p0xlxvlhemja6c4dqv22uapctqupfhlxm9h8z3k2e72q4k9hcz7vqzk5jj0";
+        let recipient = BtcAddressAndAmount::new(p2tr_address, 5001).unwrap();
+        let result = BtcTxSummary::new(
+            100.0,
+            &BtcAddressesAndAmounts(vec![recipient]),
+            SAMPLE_TARGET_BTC_ADDRESS,
+            &BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]),
+            &[],
+            &[],
+        ).unwrap();
+        assert_eq!(result.estimated_size, 236);
+        assert!(result.to_string().contains("[p2tr]"));
+        assert_eq!(result.to_json()["recipients"][0]["type"], "p2tr");
+    }
+
+    #[test]
+    fn should_err_if_not_enough_utxo_value_for_summary() {
+        let recipient = BtcAddressAndAmount::new("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", 891168).unwrap();
+        let result = BtcTxSummary::new(
+            100.0,
+            &BtcAddressesAndAmounts(vec![recipient]),
+            SAMPLE_TARGET_BTC_ADDRESS,
+            &BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]),
+            &[],
+            &[],
+        );
+        assert!(result.is_err());
+    }
+
+    fn get_sample_recipients() -> BtcAddressesAndAmounts {
+        BtcAddressesAndAmounts(vec![
+            BtcAddressAndAmount::new("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", 10000).unwrap(),
+            BtcAddressAndAmount::new("mwqqpopcLxEFUCPGGPV7zpfm3xrSAjRcyq", 20000).unwrap(),
+        ])
+    }
+
+    #[test]
+    fn should_subtract_fee_from_single_recipient() {
+        let result = subtract_fee_from_recipients(&get_sample_recipients(), &[1], 5001).unwrap();
+        assert_eq!(result.0[0].amount, 10000);
+        assert_eq!(result.0[1].amount, 14999);
+    }
+
+    #[test]
+    fn should_split_fee_between_recipients_in_proportion_to_their_amounts() {
+        let result = subtract_fee_from_recipients(&get_sample_recipients(), &[1, 0], 5001).unwrap();
+        assert_eq!(result.0[0].amount, 10000 - 1667);
+        assert_eq!(result.0[1].amount, 20000 - 3334);
+    }
+
+    #[test]
+    fn should_take_rounding_remainder_of_fee_split_from_first_index() {
+        let result = subtract_fee_from_recipients(&get_sample_recipients(), &[1, 0], 5000).unwrap();
+        assert_eq!(result.0[0].amount, 10000 - 1666);
+        assert_eq!(result.0[1].amount, 20000 - 3334);
+    }
+
+    #[test]
+    fn should_err_subtracting_fee_if_recipient_would_be_dust() {
+        assert!(subtract_fee_from_recipients(&get_sample_recipients(), &[0], 9500).is_err());
+        assert!(subtract_fee_from_recipients(&get_sample_recipients(), &[0], 10001).is_err());
+    }
+
+    #[test]
+    fn should_err_subtracting_fee_from_invalid_indices() {
+        assert!(subtract_fee_from_recipients(&get_sample_recipients(), &[2], 5000).is_err());
+        assert!(subtract_fee_from_recipients(&get_sample_recipients(), &[0, 0], 5000).is_err());
+    }
+
+    #[test]
+    fn should_keep_change_when_subtracting_fee_from_recipients() {
+        let utxos = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        let result = BtcTxSummary::new(
+            10.0,
+            &get_sample_recipients(),
+            SAMPLE_TARGET_BTC_ADDRESS,
+            &utxos,
+            &[],
+            &[0, 1],
+        ).unwrap();
+        assert_eq!(result.outgoing_total + result.fee, 30000);
+        assert_eq!(result.change, utxos.sum() - 30000);
+    }
+
+    #[test]
+    fn should_get_sweep_tx_summary() {
+        let expected_fee = 19300;
+        let recipient = BtcAddressAndAmount::new("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", 0).unwrap();
+        let result = BtcTxSummary::new_sweep(
+            100.0,
+            &recipient,
+            &BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]),
+            &[],
+        ).unwrap();
+        assert_eq!(result.fee, expected_fee);
+        assert_eq!(result.change, 0);
+        assert_eq!(result.outgoing_total, 891168 - expected_fee);
+        assert_eq!(result.recipients.0[0].amount, 891168 - expected_fee);
+    }
+
+    #[test]
+    fn should_pass_fee_caps_if_none_set() {
+        assert!(get_sample_summary().check_fee_caps(None, None).is_ok());
+    }
+
+    #[test]
+    fn should_refuse_fee_above_absolute_cap() {
+        assert!(get_sample_summary().check_fee_caps(Some(22699), None).is_err());
+        assert!(get_sample_summary().check_fee_caps(Some(22700), None).is_ok());
+    }
+
+    #[test]
+    fn should_refuse_fee_above_percentage_cap() {
+        assert!(get_sample_summary().check_fee_caps(None, Some(10.0)).is_err());
+        assert!(get_sample_summary().check_fee_caps(None, Some(500.0)).is_ok());
+    }
+}
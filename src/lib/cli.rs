@@ -0,0 +1,71 @@
+//! The `bitcoff` command line interface. Each command turns its typed args into a `State` & pipes it through the
+//! library's steps, returning the output to print.
+pub use crate::{
+    output_format::OutputFormat,
+    commands::{
+        Cli,
+        Command,
+        parse_command,
+    },
+};
+use crate::{
+    types::Result,
+    config::apply_config_to_command,
+    commands::check_at_most_one_input_uses_stdin,
+    initialize_logger::maybe_initialize_logger_and_return_command,
+    get_utxos::get_utxos,
+    get_balance::get_balance,
+    get_history::get_history,
+    encode_qr::encode_qr,
+    decode_qr::decode_qr,
+    man_pages::get_man_pages,
+    show_config::show_config,
+    completions::get_completions,
+    get_version_info::get_version_info,
+    consolidate_utxos::consolidate_utxos,
+    decode_transaction::decode_transaction,
+    read_op_return::read_op_return,
+    scan_op_return::scan_op_return,
+    verify_timestamp::verify_timestamp,
+    timestamp_files::timestamp_files,
+    verify_transaction::verify_transaction,
+    get_utxos_for_address::get_utxos_for_address,
+    make_online_transaction::make_online_transaction,
+    make_offline_transaction::make_offline_transaction,
+    make_online_op_return_transaction::make_online_op_return_transaction,
+    make_offline_op_return_transaction::make_offline_op_return_transaction,
+};
+
+/// Parses the command line & fills in any flags not passed from the config profile.
+pub fn get_command() -> Result<Command> {
+    parse_command()
+        .and_then(apply_config_to_command)
+        .and_then(check_at_most_one_input_uses_stdin)
+        .and_then(maybe_initialize_logger_and_return_command)
+}
+
+pub fn run_cli_command(command: &Command) -> Result<String> {
+    match command {
+        Command::Version(args) => get_version_info(args),
+        Command::GetUtxos(args) => get_utxos(args),
+        Command::GetBalance(args) => get_balance(args),
+        Command::GetHistory(args) => get_history(args),
+        Command::Consolidate(args) => consolidate_utxos(args),
+        Command::DecodeTx(args) => decode_transaction(args),
+        Command::EncodeQr(args) => encode_qr(args),
+        Command::DecodeQr(args) => decode_qr(args),
+        Command::ShowConfig(args) => show_config(args),
+        Command::VerifyTx(args) => verify_transaction(args),
+        Command::Timestamp(args) => timestamp_files(args),
+        Command::VerifyTimestamp(args) => verify_timestamp(args),
+        Command::ReadOpReturn(args) => read_op_return(args),
+        Command::ScanOpReturn(args) => scan_op_return(args),
+        Command::MakeOnlineTx(args) => make_online_transaction(args),
+        Command::MakeOfflineTx(args) => make_offline_transaction(args),
+        Command::GetUtxosForAddress(args) => get_utxos_for_address(args),
+        Command::MakeOnlineOpReturnTx(args) => make_online_op_return_transaction(args),
+        Command::MakeOfflineOpReturnTx(args) => make_offline_op_return_transaction(args),
+        Command::Completions(args) => get_completions(args.shell),
+        Command::ManPage(args) => get_man_pages(&args.out_dir),
+    }
+}
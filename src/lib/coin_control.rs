@@ -0,0 +1,136 @@
+use std::cmp::Reverse;
+use bitcoin::blockdata::transaction::OutPoint as BtcOutPoint;
+use crate::{
+    state::State,
+    errors::AppError,
+    utils::calculate_btc_tx_fee,
+    types::{
+        Result,
+        BtcUtxosAndValues,
+    },
+};
+
+pub fn select_utxos_by_outpoint(utxos: &BtcUtxosAndValues, outpoints: &[BtcOutPoint]) -> Result<BtcUtxosAndValues> {
+    info!("✔ Selecting {} UTXO(s) by outpoint...", outpoints.len());
+    outpoints
+        .iter()
+        .enumerate()
+        .map(|(i, outpoint)| {
+            if outpoints[..i].contains(outpoint) {
+                return Err(AppError::Custom(format!("✘ UTXO {} was selected more than once!", outpoint)))
+            };
+            utxos
+                .get_utxo_and_value_for_outpoint(outpoint)
+                .cloned()
+                .ok_or_else(|| AppError::Custom(format!("✘ Selected UTXO {} is not in the available UTXO set!", outpoint)))
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(BtcUtxosAndValues::from_vec)
+}
+
+pub fn add_extra_utxos_as_needed(
+    selected_utxos: BtcUtxosAndValues,
+    available_utxos: &BtcUtxosAndValues,
+    target: u64,
+    num_outputs: usize,
+    sats_per_byte: f64,
+) -> BtcUtxosAndValues {
+    let mut utxos = selected_utxos.to_vec();
+    let mut extra_utxos = available_utxos
+        .to_vec()
+        .into_iter()
+        .filter(|utxo| !selected_utxos.0.contains(utxo))
+        .collect::<Vec<_>>();
+    // NOTE: Largest first, so as few extra inputs as possible are added.
+    extra_utxos.sort_by_key(|utxo| Reverse(utxo.value));
+    for extra_utxo in extra_utxos {
+        let total: u64 = utxos.iter().map(|utxo| utxo.value).sum();
+        if total >= target + calculate_btc_tx_fee(utxos.len(), num_outputs, sats_per_byte) {
+            break
+        };
+        info!("✔ Adding extra UTXO {} to cover the tx", extra_utxo.utxo.previous_output);
+        utxos.push(extra_utxo);
+    }
+    BtcUtxosAndValues::from_vec(utxos)
+}
+
+pub fn maybe_apply_coin_control(utxos: BtcUtxosAndValues, state: &State) -> Result<BtcUtxosAndValues> {
+    if state.selected_utxos.is_empty() {
+        return Ok(utxos)
+    };
+    info!("✔ Applying coin control...");
+    let selected_utxos = select_utxos_by_outpoint(&utxos, &state.selected_utxos)?;
+    match state.allow_extra_inputs && !state.sweep {
+        false => Ok(selected_utxos),
+        true => Ok(
+            add_extra_utxos_as_needed(
+                selected_utxos,
+                &utxos,
+                state.addresses_and_amounts.sum(),
+                state.addresses_and_amounts.len() + state.op_return_outputs.len() + 1,
+                state.get_sats_per_byte()?,
+            )
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        types::BtcUtxoAndValue,
+        test_utils::get_sample_utxo,
+    };
+
+    fn get_sample_utxo_with_vout_and_value(vout: u32, value: u64) -> BtcUtxoAndValue {
+        let mut utxo = get_sample_utxo();
+        utxo.utxo.previous_output.vout = vout;
+        utxo.value = value;
+        utxo
+    }
+
+    fn get_sample_utxos() -> BtcUtxosAndValues {
+        BtcUtxosAndValues::from_vec(vec![
+            get_sample_utxo_with_vout_and_value(0, 10_000),
+            get_sample_utxo_with_vout_and_value(1, 50_000),
+            get_sample_utxo_with_vout_and_value(2, 20_000),
+        ])
+    }
+
+    fn get_sample_outpoint(vout: u32) -> BtcOutPoint {
+        BtcOutPoint { vout, ..get_sample_utxo().utxo.previous_output }
+    }
+
+    #[test]
+    fn should_select_utxos_by_outpoint() {
+        let result = select_utxos_by_outpoint(&get_sample_utxos(), &[get_sample_outpoint(2), get_sample_outpoint(0)])
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.sum(), 30_000);
+    }
+
+    #[test]
+    fn should_err_if_selected_utxo_is_missing() {
+        assert!(select_utxos_by_outpoint(&get_sample_utxos(), &[get_sample_outpoint(3)]).is_err());
+    }
+
+    #[test]
+    fn should_err_if_utxo_selected_twice() {
+        assert!(select_utxos_by_outpoint(&get_sample_utxos(), &[get_sample_outpoint(1), get_sample_outpoint(1)]).is_err());
+    }
+
+    #[test]
+    fn should_not_add_extra_utxos_if_selected_ones_suffice() {
+        let selected = select_utxos_by_outpoint(&get_sample_utxos(), &[get_sample_outpoint(1)]).unwrap();
+        let result = add_extra_utxos_as_needed(selected.clone(), &get_sample_utxos(), 20_000, 2, 10.0);
+        assert_eq!(result, selected);
+    }
+
+    #[test]
+    fn should_add_largest_extra_utxos_first_when_needed() {
+        let selected = select_utxos_by_outpoint(&get_sample_utxos(), &[get_sample_outpoint(0)]).unwrap();
+        let result = add_extra_utxos_as_needed(selected, &get_sample_utxos(), 20_000, 2, 10.0);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.0[1].utxo.previous_output.vout, 1);
+    }
+}
@@ -0,0 +1,1593 @@
+//! The `bitcoff` command line, as one typed subcommand per command. Each command's args are built from the shared
+//! groups of flags below, so that a flag is described once however many commands take it.
+use std::path::Path;
+use clap::{
+    Args,
+    Parser,
+    Subcommand,
+    builder::{
+        TypedValueParser,
+        PossibleValuesParser,
+    },
+};
+use clap_complete::Shell;
+use bitcoin::network::constants::Network as BtcNetwork;
+use crate::{
+    state::State,
+    types::Result,
+    errors::AppError,
+    btc_amount::is_sweep,
+    output_format::OutputFormat,
+    fee_estimates::{
+        AUTO_FEE_RATE,
+        maybe_parse_fee_rate,
+    },
+    get_payments::{
+        get_addresses_and_amounts,
+        check_payment_destinations,
+    },
+    config::{
+        Profile,
+        ApplyProfile,
+    },
+    backend::{
+        EsploraBackend,
+        get_api_endpoint_for_network,
+    },
+    utxo_filters::{
+        UtxoFilters,
+        parse_outpoints,
+    },
+    op_return::{
+        OpReturnPolicy,
+        get_op_return_outputs,
+        parse_op_return_magic,
+    },
+    constants::{
+        DEFAULT_FEE,
+        DEFAULT_NETWORK,
+        DEFAULT_LOG_LEVEL,
+        DEFAULT_CONF_TARGET,
+        DEFAULT_KEYFILE_PATH,
+        CONSOLIDATION_CONF_TARGET,
+        DEFAULT_CHANGE_ADDRESS,
+    },
+};
+
+/// An on or offline BTC transaction signer!
+#[derive(Clone, Debug, Parser)]
+#[command(name = "bitcoff", version, author = "Greg Kapka <greg@kapka.co.uk>")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum Command {
+    /// Show version info.
+    #[command(name = "version")]
+    Version(VersionArgs),
+
+    /// Makes API call to get all UTXOs associated with address derived from the encrypted private key.
+    ///
+    /// UTXOs are presented in the following JSON format, where segwit UTXOs carry their `script_pubkey` in place of
+    /// the full `previous_tx`, & the checksum is the SHA256 hash of the rest of the JSON, to catch corrupted files:
+    /// { version: 3, network: <network>, address: <address>, block_height: <uint>, created_at: <unix-timestamp>,
+    /// checksum: <hash>, utxos: [{ serialized_utxo: <0x...>, value: <value-in-Satoshis>, previous_tx: <0x...> },...] }
+    #[command(name = "getUtxos", visible_alias = "get-utxos")]
+    GetUtxos(GetUtxosArgs),
+
+    /// Makes API call to get all UTXOs associated with supplied BTC address.
+    ///
+    /// UTXOs are presented in the same JSON format as the `getUtxos` command returns.
+    #[command(name = "getUtxosForAddress", visible_alias = "get-utxos-for-address")]
+    GetUtxosForAddress(GetUtxosForAddressArgs),
+
+    /// Makes API call to get the confirmed & unconfirmed balances of an address.
+    ///
+    /// The address is the supplied <BTC_ADDRESS>, else the one derived from the encrypted private key.
+    #[command(name = "getBalance", visible_alias = "get-balance")]
+    GetBalance(GetBalanceArgs),
+
+    /// Makes API call to list the transactions touching an address, newest first.
+    ///
+    /// The address is the supplied <BTC_ADDRESS>, else the one derived from the encrypted private key. Each
+    /// transaction is listed with its net amount, fee, confirmations & block time. Use `--limit` & `--after` to page
+    /// through long histories.
+    #[command(name = "getHistory", visible_alias = "get-history")]
+    GetHistory(GetHistoryArgs),
+
+    /// Create a simple BTC transaction to one or more addresses, pulling the UTXOs from a block explorer.
+    ///
+    /// This online version will grab the UTXO set for the private key you provide via an API call.
+    #[command(name = "makeOnlineTx", visible_alias = "make-online-tx")]
+    MakeOnlineTx(MakeOnlineTxArgs),
+
+    /// Create a simple BTC transaction to one or more addresses, from UTXOs passed in.
+    ///
+    /// In this offline version, the UTXOs must be passed in via as either a JSON string, or from a file, both of
+    /// which must use the same JSON format as the `getUtxos` command returns. The file's checksum is checked, its
+    /// network & address must match the `--network` & the key's address, & each UTXO's value must match its previous
+    /// transaction's output. The older format of: [{ serialized_utxo: <0x...>, value: <value-in-Satoshis> },...] can't
+    /// be checked, so is only accepted with `--allowUnverifiedUtxos`.
+    #[command(name = "makeOfflineTx", visible_alias = "make-offline-tx")]
+    MakeOfflineTx(MakeOfflineTxArgs),
+
+    /// Create a transaction with an `OP_RETURN` output of the <DATA>, pulling the UTXOs from a block explorer.
+    ///
+    /// The <DATA> is passed after the <TO AMOUNT> pairs, else one output is added per `--data`. In this online
+    /// version, available UTXOs for the address of the private-key supplied are pulled from a block explorer.
+    #[command(name = "makeOnlineOpReturnTx", visible_alias = "make-online-op-return-tx")]
+    MakeOnlineOpReturnTx(MakeOnlineOpReturnTxArgs),
+
+    /// Create a transaction with an `OP_RETURN` output of the <DATA>, from UTXOs passed in.
+    ///
+    /// The <DATA> is passed after the <TO AMOUNT> pairs, else one output is added per `--data`. In this offline
+    /// version, the UTXOs required must be passed in via as either a JSON string, or from a file, both of which must
+    /// use the same JSON format as the `getUtxos` command returns.
+    #[command(name = "makeOfflineOpReturnTx", visible_alias = "make-offline-op-return-tx")]
+    MakeOfflineOpReturnTx(MakeOfflineOpReturnTxArgs),
+
+    /// Merge the UTXOs of the address derived from the encrypted private key into a single output.
+    ///
+    /// The UTXOs are merged to the `--change` address, smallest first. Use `--maxInputs` to merge at most that many
+    /// UTXOs, or `--maxValue` to merge only those below a value threshold. Merges too large for one transaction are
+    /// split into several. The fee per input & the projected savings of later spending one output instead of many
+    /// are reported. Unless a `--fee` is set, the fee rate is estimated to confirm within 144 blocks, or the
+    /// `--confTarget`, since consolidating is rarely urgent.
+    #[command(name = "consolidate")]
+    Consolidate(ConsolidateArgs),
+
+    /// Decode a raw BTC transaction into JSON.
+    ///
+    /// Shows its inputs, outputs, addresses, script types, `OP_RETURN` payloads, size, vsize, weight, txid, wtxid &
+    /// whether it signals RBF. The transaction is read from the <TX_HEX> argument, else from the `--txFile`, else
+    /// from stdin. If a UTXO file is supplied, the fee & fee-rate are shown too.
+    #[command(name = "decodeTx", visible_alias = "decode-tx")]
+    DecodeTx(DecodeTxArgs),
+
+    /// Verify a signed BTC transaction against the UTXO set it spends.
+    ///
+    /// Runs each input's `script_sig` & witness against the referenced `script_pubkey`, checking the input & output
+    /// values balance & that the standardness rules (dust, `OP_RETURN` size, max weight) hold. Per-input failures are
+    /// reported, & an invalid transaction exits with a `TxVerificationError`. Signed transactions made by this tool are
+    /// automatically verified in this way before being output.
+    #[command(name = "verifyTx", visible_alias = "verify-tx")]
+    VerifyTx(VerifyTxArgs),
+
+    /// Encode a UTXO file, PSBT, signed transaction or any other text as QR codes.
+    ///
+    /// Lets the data cross an air gap optically. The input is read from the `--inFile`, else from stdin. Large inputs
+    /// are split into multi-part BBQr codes, which are printed to the terminal one after another, animated in the
+    /// terminal via `--animate`, or saved as PNGs in the `--qrDir` directory. The QR codes are the output, so there's
+    /// no `--output` flag.
+    #[command(name = "encodeQr", visible_alias = "encode-qr")]
+    EncodeQr(EncodeQrArgs),
+
+    /// Decode the QR code(s) in the given image files.
+    ///
+    /// Multi-part BBQr codes are reassembled in whatever order they are supplied. Transactions & PSBTs are output as
+    /// hex, else as text, whatever the data, so there's no `--output` flag.
+    #[command(name = "decodeQr", visible_alias = "decode-qr")]
+    DecodeQr(DecodeQrArgs),
+
+    /// Prove the given files existed at a point in time by committing to their hashes in an `OP_RETURN` output.
+    ///
+    /// Several files are combined into a Merkle tree of their SHA256 hashes so that only its root is published.
+    /// Always outputs JSON, so there's no `--output` flag, holding the signed transaction & an inclusion proof per
+    /// file, which must be kept to later verify each file. UTXOs are pulled from a block explorer unless a
+    /// `--utxoFile` or `--utxos` is supplied.
+    #[command(name = "timestamp")]
+    Timestamp(TimestampArgs),
+
+    /// Check a file against an inclusion proof from the `timestamp` command & the transaction it was anchored in.
+    ///
+    /// The transaction is read from the <TX_HEX> argument, else from the `--txFile`, else from stdin. The `--proof`
+    /// may be a single file's proof or the whole `timestamp` output. Always outputs JSON, so there's no `--output`
+    /// flag.
+    #[command(name = "verifyTimestamp", visible_alias = "verify-timestamp")]
+    VerifyTimestamp(VerifyTimestampArgs),
+
+    /// Show the `OP_RETURN` payloads of a transaction as hex, & as text where they're valid UTF-8.
+    ///
+    /// The <TX> may be a txid, in which case the transaction is fetched from a block explorer, or a raw transaction in
+    /// hex, else it's read from the `--txFile`, else from stdin.
+    #[command(name = "readOpReturn", visible_alias = "read-op-return")]
+    ReadOpReturn(ReadOpReturnArgs),
+
+    /// Makes API calls to list every `OP_RETURN` payload in the history of an address, newest first.
+    ///
+    /// The address is the supplied <BTC_ADDRESS>, else the one derived from the encrypted private key. Use `--prefix`
+    /// to list only those starting with a given protocol tag.
+    #[command(name = "scanOpReturn", visible_alias = "scan-op-return")]
+    ScanOpReturn(ScanOpReturnArgs),
+
+    /// Show the settings commands would run with, once the config profile & any flags passed are combined.
+    #[command(name = "showConfig", visible_alias = "show-config")]
+    ShowConfig(ShowConfigArgs),
+
+    /// Print a completion script for the given shell.
+    ///
+    /// Eg: `bitcoff completions bash > /etc/bash_completion.d/bitcoff`, or for zsh, save it as `_bitcoff` somewhere in
+    /// your `$fpath`, or for fish, as `~/.config/fish/completions/bitcoff.fish`.
+    #[command(name = "completions")]
+    Completions(CompletionsArgs),
+
+    /// Print the man page, or save one per command in the `--outDir` directory.
+    #[command(name = "manPage", visible_alias = "man-page")]
+    ManPage(ManPageArgs),
+}
+
+fn parse_fee_arg(fee: &str) -> std::result::Result<String, String> {
+    maybe_parse_fee_rate(fee).map(|_| fee.to_string()).map_err(|e| e.to_string())
+}
+
+pub const NETWORK_NAMES: [&str; 2] = ["Bitcoin", "Testnet"];
+
+/// The network name in any case as it's spelt in the help, eg: `testnet` as `Testnet`, if it's a supported one.
+pub fn get_canonical_network_name(network: &str) -> Option<&'static str> {
+    NETWORK_NAMES.iter().find(|name| name.eq_ignore_ascii_case(network.trim())).copied()
+}
+
+/// Accepts a network name in any case, but stores it as it's spelt in the help, eg: `testnet` as `Testnet`.
+fn network_value_parser() -> impl TypedValueParser<Value = String> {
+    PossibleValuesParser::new(NETWORK_NAMES)
+        .map(|network| get_canonical_network_name(&network).unwrap_or(DEFAULT_NETWORK).to_string())
+}
+
+fn parse_prefix_arg(prefix: &str) -> std::result::Result<String, String> {
+    parse_op_return_magic(prefix).map(|_| prefix.to_string()).map_err(|e| e.to_string())
+}
+
+fn is_stdin_path(maybe_path: &Option<String>) -> bool {
+    maybe_path.as_deref() == Some("-")
+}
+
+// NOTE: Flags which a config profile may also set fall back to their defaults in these getters, not at parse time.
+
+#[derive(Clone, Debug, Args)]
+pub struct CommonArgs {
+    /// Save the tool's output to given path.
+    #[arg(long = "outputPath", value_name = "PATH")]
+    pub output_path: Option<String>,
+
+    /// Path to a TOML config file of named profiles.
+    ///
+    /// Each profile sets defaults for the `--network`, `--keyfile`, `--fee`, `--confTarget`, `--change`,
+    /// `--logLevel`, `--apiEndpoint`, `--maxFee` & `--maxFeePct` flags. Flags passed on the command line override the
+    /// profile. Defaults to `$XDG_CONFIG_HOME/bitcoff/config.toml`, else `~/.config/bitcoff/config.toml`, if it
+    /// exists.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<String>,
+
+    /// Name of the config file's profile to use. Defaults to the config's `defaultProfile`, if any.
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Level of logging in the tool's output. Logs are written to stderr so stdout can be piped. Defaults to `none`.
+    #[arg(long = "logLevel", value_name = "LEVEL", value_parser = ["none", "info", "warn", "debug", "trace", "error"])]
+    pub log_level: Option<String>,
+}
+
+impl CommonArgs {
+    pub fn get_log_level(&self) -> &str {
+        self.log_level.as_deref().unwrap_or(DEFAULT_LOG_LEVEL)
+    }
+}
+
+impl ApplyProfile for CommonArgs {
+    fn apply_profile(&mut self, profile: &Profile) {
+        self.profile = profile.name.clone();
+        self.log_level = self.log_level.take().or_else(|| profile.log_level.clone());
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct OutputArgs {
+    /// Format of the tool's output.
+    ///
+    /// In `json` mode, transaction commands return an object with the txid, hex, fee, vsize, inputs, outputs &
+    /// change, and errors are returned as: { error: { kind: <kind>, message: <message> } }
+    #[arg(long, value_name = "FORMAT", value_enum, ignore_case = true, default_value = "text")]
+    pub output: OutputFormat,
+}
+
+#[derive(Clone, Debug, Default, Args)]
+pub struct NetworkArgs {
+    /// BTC network. Defaults to `Bitcoin`.
+    #[arg(long, value_name = "NETWORK", ignore_case = true, value_parser = network_value_parser())]
+    pub network: Option<String>,
+}
+
+impl NetworkArgs {
+    pub fn get_network_name(&self) -> &str {
+        self.network.as_deref().unwrap_or(DEFAULT_NETWORK)
+    }
+
+    pub fn get_network(&self) -> BtcNetwork {
+        info!("✔ Using network: '{}'", self.get_network_name());
+        match self.get_network_name() {
+            "Testnet" | "testnet" => BtcNetwork::Testnet,
+            _ => BtcNetwork::Bitcoin,
+        }
+    }
+
+    /// A `State` for the `--network`, whose settings are otherwise at their defaults.
+    fn to_state(&self, api_endpoint: &ApiEndpointArgs, output_format: OutputFormat) -> State {
+        let network = self.get_network();
+        State::init(network, EsploraBackend::new(&api_endpoint.get_api_endpoint(network)), output_format)
+    }
+}
+
+impl ApplyProfile for NetworkArgs {
+    fn apply_profile(&mut self, profile: &Profile) {
+        self.network = self.network.take().or_else(|| profile.network.clone());
+    }
+}
+
+#[derive(Clone, Debug, Default, Args)]
+pub struct ApiEndpointArgs {
+    /// URL of the Esplora API to pull chain data from, eg: a self-hosted one.
+    ///
+    /// Defaults to the blockstream.info API for the `--network`.
+    #[arg(long = "apiEndpoint", value_name = "URL")]
+    pub api_endpoint: Option<String>,
+}
+
+impl ApiEndpointArgs {
+    pub fn get_api_endpoint(&self, network: BtcNetwork) -> String {
+        info!("✔ Getting API endpoint...");
+        let api_url = match &self.api_endpoint {
+            Some(api_endpoint) if api_endpoint.ends_with('/') => api_endpoint.clone(),
+            Some(api_endpoint) => format!("{}/", api_endpoint),
+            None => get_api_endpoint_for_network(network),
+        };
+        info!("✔ Using API endpoint: `{}`", api_url);
+        api_url
+    }
+}
+
+impl ApplyProfile for ApiEndpointArgs {
+    fn apply_profile(&mut self, profile: &Profile) {
+        self.api_endpoint = self.api_endpoint.take().or_else(|| profile.api_endpoint.clone());
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct KeyfileArgs {
+    /// Path to GPG-encrypted BTC private key in wallet import format (`WIF`).
+    ///
+    /// Defaults to `./encrypted-btc-private-key.gpg`.
+    #[arg(long, value_name = "PATH")]
+    pub keyfile: Option<String>,
+}
+
+impl KeyfileArgs {
+    pub fn get_keyfile(&self) -> &str {
+        self.keyfile.as_deref().unwrap_or(DEFAULT_KEYFILE_PATH)
+    }
+}
+
+impl ApplyProfile for KeyfileArgs {
+    fn apply_profile(&mut self, profile: &Profile) {
+        self.keyfile = self.keyfile.take().or_else(|| profile.keyfile.clone());
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct FeeArgs {
+    /// Fee to pay in Satoshis-per-byte, or `auto`. Defaults to `23`.
+    ///
+    /// Fractional rates are allowed. Use `auto` in the online commands to estimate a rate from the block explorer's
+    /// fee estimates. Estimates are floored at the minimum relay fee rate of 1, whereas explicit rates below it are
+    /// refused, as is any rate above a sanity ceiling.
+    #[arg(long, value_name = "RATE", value_parser = parse_fee_arg)]
+    pub fee: Option<String>,
+
+    /// Address, or hex `script_pubkey` prefixed with `script:`, to send any change to.
+    ///
+    /// Defaults to address of the private key used for the transaction.
+    #[arg(long, value_name = "ADDRESS")]
+    pub change: Option<String>,
+
+    /// Refuse to sign a transaction whose fee exceeds this many Satoshis.
+    #[arg(long = "maxFee", value_name = "SATS")]
+    pub max_fee: Option<u64>,
+
+    /// Refuse to sign a transaction whose fee exceeds this percentage of the outgoing total.
+    #[arg(long = "maxFeePct", value_name = "PCT")]
+    pub max_fee_pct: Option<f64>,
+}
+
+impl FeeArgs {
+    pub fn get_fee(&self) -> &str {
+        self.get_fee_or(DEFAULT_FEE)
+    }
+
+    /// The `--fee`, else the given default for the command rather than the usual one.
+    pub fn get_fee_or<'a>(&'a self, default_fee: &'a str) -> &'a str {
+        self.fee.as_deref().unwrap_or(default_fee)
+    }
+
+    pub fn get_change(&self) -> &str {
+        self.change.as_deref().unwrap_or(DEFAULT_CHANGE_ADDRESS)
+    }
+}
+
+impl ApplyProfile for FeeArgs {
+    fn apply_profile(&mut self, profile: &Profile) {
+        self.fee = self.fee.take().or_else(|| profile.fee.as_ref().map(|fee| fee.to_cli_arg()));
+        self.change = self.change.take().or_else(|| profile.change.clone());
+        self.max_fee = self.max_fee.or(profile.max_fee);
+        self.max_fee_pct = self.max_fee_pct.or(profile.max_fee_pct);
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ConfTargetArgs {
+    /// Number of blocks within which an `auto` fee rate should aim to confirm the transaction. Defaults to `6`.
+    #[arg(long = "confTarget", value_name = "BLOCKS")]
+    pub conf_target: Option<u64>,
+}
+
+impl ConfTargetArgs {
+    pub fn get_conf_target(&self) -> u64 {
+        self.conf_target.unwrap_or(DEFAULT_CONF_TARGET)
+    }
+}
+
+impl ApplyProfile for ConfTargetArgs {
+    fn apply_profile(&mut self, profile: &Profile) {
+        self.conf_target = self.conf_target.or(profile.conf_target);
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ConfirmArgs {
+    /// Skip the confirmation prompt shown before a transaction is signed. Useful for scripting.
+    #[arg(long)]
+    pub yes: bool,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct MinConfArgs {
+    /// Only spend UTXOs with at least this many confirmations.
+    ///
+    /// Use `1` to avoid spending unconfirmed coins.
+    #[arg(long = "minConf", value_name = "BLOCKS")]
+    pub min_conf: Option<u64>,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct UtxoFilterArgs {
+    /// Only spend UTXOs worth at least this amount, eg: to skip dust.
+    #[arg(long = "minValue", value_name = "AMOUNT")]
+    pub min_value: Option<String>,
+
+    /// Only spend UTXOs worth at most this amount.
+    #[arg(long = "maxValue", value_name = "AMOUNT")]
+    pub max_value: Option<String>,
+
+    /// A UTXO in the form <txid>:<vout> to always keep, regardless of the other UTXO filters. May be repeated.
+    #[arg(long, value_name = "OUTPOINT")]
+    pub include: Vec<String>,
+
+    /// A UTXO in the form <txid>:<vout> to never spend, eg: a tainted coin. May be repeated.
+    #[arg(long, value_name = "OUTPOINT")]
+    pub exclude: Vec<String>,
+}
+
+impl UtxoFilterArgs {
+    fn get_utxo_filters(&self, min_conf: &MinConfArgs) -> Result<UtxoFilters> {
+        UtxoFilters::parse(min_conf.min_conf, &self.min_value, &self.max_value, &self.include, &self.exclude)
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct CoinControlArgs {
+    /// A UTXO in the form <txid>:<vout> to spend. May be repeated.
+    ///
+    /// When given, only the selected UTXOs are spent & it is an error if any of them are missing from the available
+    /// UTXO set (after any UTXO filters are applied).
+    #[arg(long, value_name = "OUTPOINT")]
+    pub utxo: Vec<String>,
+
+    /// Allow other UTXOs to be added to those selected via `--utxo` if they don't cover the transaction.
+    ///
+    /// Largest UTXOs are added first.
+    #[arg(long = "allowExtraInputs", requires = "utxo")]
+    pub allow_extra_inputs: bool,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct PaymentArgs {
+    /// Pairs of addresses to pay & amounts to pay them.
+    ///
+    /// Any address type for the `--network` is accepted, incl. `p2sh`, segwit & taproot (`p2tr`), or pass a raw
+    /// `script_pubkey` as hex prefixed with `script:`, eg: `script:51`, to pay to a custom script. Plain amounts are
+    /// Satoshis, else use a unit suffix of `btc`, `mbtc` or `sat`, eg: `0.015btc`, `1.5mbtc` or `2500sat`. Use `max`
+    /// to sweep all UTXOs to a single recipient.
+    #[arg(value_name = "TO AMOUNT", num_args = 1.., required_unless_present = "payments", conflicts_with = "payments")]
+    pub recipients: Vec<String>,
+
+    /// Path to a CSV or JSON file of payments to batch into one transaction, used instead of the <TO AMOUNT> pairs.
+    ///
+    /// CSV rows are in the form: <address>,<amount>[,<label>], & JSON is in the form: [{ address: <address>, amount:
+    /// <amount>, label: <label> },...], where <address> may be of any type, or a `script:` prefixed hex
+    /// `script_pubkey`, as for <TO>. Payments to the same address are merged, & payments too large for one transaction
+    /// are split into several. Use `-` for stdin. Only one input may be read from stdin at a time.
+    #[arg(long, value_name = "PATH")]
+    pub payments: Option<String>,
+
+    /// Send everything minus the fee to a single recipient, ignoring its <AMOUNT>.
+    ///
+    /// The output's value is calculated from the real size of the signed transaction so no change is created. Useful
+    /// for emptying a key during key rotation.
+    #[arg(long)]
+    pub sweep: bool,
+
+    /// Index (zero-based) of a <TO AMOUNT> pair whose recipient should pay the fee instead of the change.
+    ///
+    /// May be repeated, in which case the fee is split between them in proportion to their amounts.
+    #[arg(long = "subtractFeeFrom", value_name = "INDEX")]
+    pub subtract_fee_from: Vec<usize>,
+
+    /// Allow paying to non-standard scripts, & `OP_RETURN` scripts with a value, which would otherwise be rejected.
+    ///
+    /// Nodes won't relay a transaction with such outputs, & any value sent to an `OP_RETURN` script is burnt.
+    #[arg(long = "allowNonStandard")]
+    pub allow_non_standard: bool,
+}
+
+impl PaymentArgs {
+    /// The <TO> addresses & their <AMOUNT>s, from the pairs of positional args.
+    pub fn get_to_and_amounts(&self) -> Result<(Vec<String>, Vec<String>)> {
+        if !self.recipients.len().is_multiple_of(2) {
+            return Err(AppError::Custom(
+                format!("✘ Each <TO> address needs an <AMOUNT>, got {} args!", self.recipients.len())
+            ))
+        };
+        Ok((
+            self.recipients.iter().step_by(2).cloned().collect(),
+            self.recipients.iter().skip(1).step_by(2).cloned().collect(),
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+#[group(required = true, multiple = false)]
+pub struct OfflineUtxoArgs {
+    /// Path to a file containing BTC UTXOs in the same JSON format as the `getUtxos` command returns.
+    ///
+    /// Use `-` for stdin, eg: `bitcoff getUtxos | bitcoff makeOfflineTx --utxoFile=-`.
+    #[arg(long = "utxoFile", value_name = "PATH")]
+    pub utxo_file: Option<String>,
+
+    /// The UTXOs, as a JSON string in the same format as the `getUtxos` command returns.
+    #[arg(long, value_name = "JSON")]
+    pub utxos: Option<String>,
+}
+
+/// The same flags as `OfflineUtxoArgs`, for commands that pull the UTXOs from a block explorer unless they're given.
+#[derive(Clone, Debug, Args)]
+#[group(multiple = false)]
+pub struct MaybeOfflineUtxoArgs {
+    /// Path to a file containing the BTC UTXOs to spend, to run offline. Use `-` for stdin.
+    #[arg(long = "utxoFile", value_name = "PATH")]
+    pub utxo_file: Option<String>,
+
+    /// The UTXOs to spend, to run offline, as a JSON string in the same format as the `getUtxos` command returns.
+    #[arg(long, value_name = "JSON")]
+    pub utxos: Option<String>,
+}
+
+impl MaybeOfflineUtxoArgs {
+    /// The supplied UTXOs, if any, else `None` to pull them from a block explorer.
+    pub fn get_offline_utxo_args(&self) -> Option<OfflineUtxoArgs> {
+        match self.utxo_file.is_some() || self.utxos.is_some() {
+            true => Some(OfflineUtxoArgs { utxo_file: self.utxo_file.clone(), utxos: self.utxos.clone() }),
+            false => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct OpReturnArgs {
+    /// Data for an `OP_RETURN` output, used instead of a <DATA> arg. May be repeated, adding one output each.
+    ///
+    /// Hex by default, else prefix it with `text:` for UTF-8 text, eg: `text:gm`, or with `file:` to use a file's raw
+    /// bytes, eg: `file:./contract.pdf`. Use `--data` rather than a <DATA> arg when paying the `--payments` file.
+    #[arg(long, value_name = "DATA")]
+    pub data: Vec<String>,
+
+    /// A 4-byte protocol tag, as 8 hex characters, to prepend to every `OP_RETURN` payload.
+    #[arg(long, value_name = "HEX", value_parser = parse_prefix_arg)]
+    pub prefix: Option<String>,
+
+    /// Commit to the SHA256 hash of each `OP_RETURN` payload rather than the payload itself.
+    ///
+    /// OpenTimestamps-style. Handy with `file:` data.
+    #[arg(long = "hashData")]
+    pub hash_data: bool,
+
+    #[command(flatten)]
+    pub large_data: LargeDataArgs,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct LargeDataArgs {
+    /// Check `OP_RETURN` outputs against the newer, larger datacarrier relay policy.
+    ///
+    /// Allows several outputs totalling up to 100,000 bytes. By default only one output with at most 80 bytes of
+    /// data is allowed, since many nodes & miners won't relay more.
+    #[arg(long = "allowLargeData")]
+    pub allow_large_data: bool,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct TxInputArgs {
+    /// Path to a file containing a raw BTC transaction in hex format. Use `-` for stdin.
+    #[arg(long = "txFile", value_name = "PATH")]
+    pub tx_file: Option<String>,
+}
+
+impl TxInputArgs {
+    /// Whether the tx is read from stdin, as it is when neither the `maybe_tx` arg nor a `--txFile` is passed.
+    fn reads_from_stdin(&self, maybe_tx: &Option<String>) -> bool {
+        maybe_tx.is_none() && (self.tx_file.is_none() || is_stdin_path(&self.tx_file))
+    }
+}
+
+/// Sets the key, fee & confirmation settings of a command that signs a transaction.
+fn with_signing_args(state: State, keyfile: &KeyfileArgs, fee: &FeeArgs, confirm: &ConfirmArgs) -> Result<State> {
+    Ok(State {
+        yes: confirm.yes,
+        max_fee: fee.max_fee,
+        max_fee_pct: fee.max_fee_pct,
+        keyfile: keyfile.get_keyfile().to_string(),
+        change: fee.get_change().to_string(),
+        sats_per_byte: maybe_parse_fee_rate(fee.get_fee())?,
+        ..state
+    })
+}
+
+/// Sets the recipients, & the UTXOs selected to pay them, of a command that makes payments.
+fn with_payment_args(state: State, payments: &PaymentArgs, coin_control: &CoinControlArgs) -> Result<State> {
+    let (to, amounts) = payments.get_to_and_amounts()?;
+    let sweep = is_sweep(payments.sweep, &amounts, to.len(), &payments.subtract_fee_from)?;
+    let addresses_and_amounts = get_addresses_and_amounts(&to, &amounts, &payments.payments, sweep)?;
+    check_payment_destinations(&addresses_and_amounts, state.network, payments.allow_non_standard)?;
+    Ok(State {
+        sweep,
+        addresses_and_amounts,
+        subtract_fee_from: payments.subtract_fee_from.clone(),
+        selected_utxos: parse_outpoints(&coin_control.utxo)?,
+        allow_extra_inputs: coin_control.allow_extra_inputs,
+        ..state
+    })
+}
+
+impl OpReturnArgs {
+    /// Splits a trailing <DATA> arg off the <TO AMOUNT> pairs, returning the data & the payments left.
+    ///
+    /// NOTE: The <DATA> arg can't be told apart from the positional pairs at parse time, so it's picked off here.
+    fn get_data_and_payments(&self, payments: &PaymentArgs) -> Result<(Vec<String>, PaymentArgs)> {
+        let has_trailing_data = !payments.recipients.len().is_multiple_of(2);
+        match (self.data.is_empty(), payments.recipients.split_last()) {
+            (false, _) => Ok((self.data.clone(), payments.clone())),
+            (true, Some((data, recipients))) if has_trailing_data => {
+                Ok((vec![data.clone()], PaymentArgs { recipients: recipients.to_vec(), ..payments.clone() }))
+            },
+            _ => Err(AppError::Custom(
+                "✘ No `OP_RETURN` data supplied, pass a <DATA> arg after the <TO AMOUNT> pairs or use `--data`!"
+                    .to_string()
+            )),
+        }
+    }
+}
+
+fn with_op_return_args(state: State, data: &[String], op_return: &OpReturnArgs) -> Result<State> {
+    Ok(State {
+        op_return_outputs: get_op_return_outputs(data, &op_return.prefix, op_return.hash_data)?,
+        op_return_policy: OpReturnPolicy::new(op_return.large_data.allow_large_data),
+        ..state
+    })
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct VersionArgs {
+    #[command(flatten)]
+    pub output: OutputArgs,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct GetUtxosArgs {
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub min_conf: MinConfArgs,
+    #[command(flatten)]
+    pub filters: UtxoFilterArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl GetUtxosArgs {
+    pub fn to_state(&self) -> Result<State> {
+        Ok(State {
+            keyfile: self.keyfile.get_keyfile().to_string(),
+            utxo_filters: self.filters.get_utxo_filters(&self.min_conf)?,
+            ..self.network.to_state(&self.api_endpoint, self.output.output)
+        })
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct GetUtxosForAddressArgs {
+    /// A bitcoin address.
+    #[arg(value_name = "BTC_ADDRESS")]
+    pub btc_address: String,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub min_conf: MinConfArgs,
+    #[command(flatten)]
+    pub filters: UtxoFilterArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl GetUtxosForAddressArgs {
+    pub fn to_state(&self) -> Result<State> {
+        Ok(State {
+            btc_address: Some(self.btc_address.clone()),
+            utxo_filters: self.filters.get_utxo_filters(&self.min_conf)?,
+            ..self.network.to_state(&self.api_endpoint, self.output.output)
+        })
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct GetBalanceArgs {
+    /// A bitcoin address. Defaults to the address derived from the encrypted private key.
+    #[arg(value_name = "BTC_ADDRESS")]
+    pub btc_address: Option<String>,
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl GetBalanceArgs {
+    pub fn to_state(&self) -> Result<State> {
+        Ok(State {
+            btc_address: self.btc_address.clone(),
+            keyfile: self.keyfile.get_keyfile().to_string(),
+            ..self.network.to_state(&self.api_endpoint, self.output.output)
+        })
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct GetHistoryArgs {
+    /// A bitcoin address. Defaults to the address derived from the encrypted private key.
+    #[arg(value_name = "BTC_ADDRESS")]
+    pub btc_address: Option<String>,
+
+    /// Maximum number of transactions to list.
+    #[arg(long, value_name = "UINT", default_value_t = 25)]
+    pub limit: usize,
+
+    /// List the confirmed transactions older than this one, eg: to get the next page of history.
+    #[arg(long, value_name = "TXID")]
+    pub after: Option<String>,
+
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl GetHistoryArgs {
+    pub fn to_state(&self) -> Result<State> {
+        Ok(State {
+            btc_address: self.btc_address.clone(),
+            keyfile: self.keyfile.get_keyfile().to_string(),
+            ..self.network.to_state(&self.api_endpoint, self.output.output)
+        })
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct MakeOnlineTxArgs {
+    #[command(flatten)]
+    pub payments: PaymentArgs,
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub fee: FeeArgs,
+    #[command(flatten)]
+    pub conf_target: ConfTargetArgs,
+    #[command(flatten)]
+    pub confirm: ConfirmArgs,
+    #[command(flatten)]
+    pub min_conf: MinConfArgs,
+    #[command(flatten)]
+    pub filters: UtxoFilterArgs,
+    #[command(flatten)]
+    pub coin_control: CoinControlArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl MakeOnlineTxArgs {
+    pub fn to_state(&self) -> Result<State> {
+        let state = State {
+            conf_target: self.conf_target.get_conf_target(),
+            utxo_filters: self.filters.get_utxo_filters(&self.min_conf)?,
+            ..self.network.to_state(&self.api_endpoint, self.output.output)
+        };
+        with_signing_args(state, &self.keyfile, &self.fee, &self.confirm)
+            .and_then(|state| with_payment_args(state, &self.payments, &self.coin_control))
+    }
+
+    fn get_profile_args_mut(&mut self) -> Vec<&mut dyn ApplyProfile> {
+        vec![
+            &mut self.keyfile,
+            &mut self.network,
+            &mut self.api_endpoint,
+            &mut self.fee,
+            &mut self.conf_target,
+            &mut self.common,
+        ]
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct MakeOfflineTxArgs {
+    #[command(flatten)]
+    pub payments: PaymentArgs,
+    #[command(flatten)]
+    pub utxos: OfflineUtxoArgs,
+    /// Sign with a version 1 or 2 UTXO file, whose UTXOs can't be checked against the key's network & address.
+    #[arg(long = "allowUnverifiedUtxos")]
+    pub allow_unverified_utxos: bool,
+
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub fee: FeeArgs,
+    #[command(flatten)]
+    pub confirm: ConfirmArgs,
+    #[command(flatten)]
+    pub filters: UtxoFilterArgs,
+    #[command(flatten)]
+    pub coin_control: CoinControlArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl MakeOfflineTxArgs {
+    pub fn to_state(&self) -> Result<State> {
+        let state = State {
+            allow_unverified_utxos: self.allow_unverified_utxos,
+            utxo_filters: self.filters.get_utxo_filters(&MinConfArgs { min_conf: None })?,
+            ..self.network.to_state(&ApiEndpointArgs::default(), self.output.output)
+        };
+        with_signing_args(state, &self.keyfile, &self.fee, &self.confirm)
+            .and_then(|state| with_payment_args(state, &self.payments, &self.coin_control))
+    }
+
+    fn get_profile_args_mut(&mut self) -> Vec<&mut dyn ApplyProfile> {
+        vec![&mut self.keyfile, &mut self.network, &mut self.fee, &mut self.common]
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct MakeOnlineOpReturnTxArgs {
+    #[command(flatten)]
+    pub op_return: OpReturnArgs,
+    #[command(flatten)]
+    pub tx: MakeOnlineTxArgs,
+}
+
+impl MakeOnlineOpReturnTxArgs {
+    pub fn to_state(&self) -> Result<State> {
+        let (data, payments) = self.op_return.get_data_and_payments(&self.tx.payments)?;
+        MakeOnlineTxArgs { payments, ..self.tx.clone() }
+            .to_state()
+            .and_then(|state| with_op_return_args(state, &data, &self.op_return))
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct MakeOfflineOpReturnTxArgs {
+    #[command(flatten)]
+    pub op_return: OpReturnArgs,
+    #[command(flatten)]
+    pub tx: MakeOfflineTxArgs,
+}
+
+impl MakeOfflineOpReturnTxArgs {
+    pub fn to_state(&self) -> Result<State> {
+        let (data, payments) = self.op_return.get_data_and_payments(&self.tx.payments)?;
+        MakeOfflineTxArgs { payments, ..self.tx.clone() }
+            .to_state()
+            .and_then(|state| with_op_return_args(state, &data, &self.op_return))
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ConsolidateArgs {
+    /// Maximum number of UTXOs to consolidate.
+    #[arg(long = "maxInputs", value_name = "UINT")]
+    pub max_inputs: Option<usize>,
+
+    /// Fee rate in Satoshis-per-byte at which the consolidated UTXOs would otherwise be spent later.
+    ///
+    /// Used to project the savings of consolidating now.
+    #[arg(long = "futureFee", value_name = "RATE", default_value = "23")]
+    pub future_fee: String,
+
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub fee: FeeArgs,
+    #[command(flatten)]
+    pub conf_target: ConfTargetArgs,
+    #[command(flatten)]
+    pub confirm: ConfirmArgs,
+    #[command(flatten)]
+    pub min_conf: MinConfArgs,
+    #[command(flatten)]
+    pub filters: UtxoFilterArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl ConsolidateArgs {
+    pub fn to_state(&self) -> Result<State> {
+        let state = State {
+            conf_target: self.conf_target.conf_target.unwrap_or(CONSOLIDATION_CONF_TARGET),
+            utxo_filters: self.filters.get_utxo_filters(&self.min_conf)?,
+            ..self.network.to_state(&self.api_endpoint, self.output.output)
+        };
+        let fee = FeeArgs { fee: Some(self.fee.get_fee_or(AUTO_FEE_RATE).to_string()), ..self.fee.clone() };
+        with_signing_args(state, &self.keyfile, &fee, &self.confirm)
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct DecodeTxArgs {
+    /// A raw BTC transaction in hex format.
+    #[arg(value_name = "TX_HEX")]
+    pub tx_hex: Option<String>,
+
+    #[command(flatten)]
+    pub tx_input: TxInputArgs,
+
+    /// Path to a file containing the UTXOs the transaction spends, to show its fee & fee-rate.
+    #[arg(long = "utxoFile", value_name = "PATH")]
+    pub utxo_file: Option<String>,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl DecodeTxArgs {
+    pub fn to_state(&self) -> Result<State> {
+        Ok(self.network.to_state(&ApiEndpointArgs::default(), self.output.output))
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct VerifyTxArgs {
+    /// A raw BTC transaction in hex format.
+    #[arg(value_name = "TX_HEX")]
+    pub tx_hex: Option<String>,
+
+    #[command(flatten)]
+    pub tx_input: TxInputArgs,
+
+    /// Path to a file containing the UTXOs the transaction spends. Use `-` for stdin.
+    #[arg(long = "utxoFile", value_name = "PATH")]
+    pub utxo_file: String,
+
+    #[command(flatten)]
+    pub large_data: LargeDataArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl VerifyTxArgs {
+    pub fn to_state(&self) -> Result<State> {
+        Ok(State {
+            op_return_policy: OpReturnPolicy::new(self.large_data.allow_large_data),
+            ..self.network.to_state(&ApiEndpointArgs::default(), self.output.output)
+        })
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct EncodeQrArgs {
+    /// Path to a file to read the input from. Use `-` for stdin.
+    #[arg(long = "inFile", value_name = "PATH")]
+    pub in_file: Option<String>,
+
+    /// Directory to save QR codes in as PNG files, one per part.
+    #[arg(long = "qrDir", value_name = "PATH")]
+    pub qr_dir: Option<String>,
+
+    /// Maximum number of characters per QR code, above which the data is split into several parts.
+    #[arg(long = "partSize", value_name = "UINT", default_value_t = 400)]
+    pub part_size: usize,
+
+    /// Cycle through the QR code parts in the terminal until interrupted.
+    #[arg(long)]
+    pub animate: bool,
+
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct DecodeQrArgs {
+    /// Path to an image file, eg: a PNG or a photo, containing one or more QR codes.
+    #[arg(value_name = "IMAGE", required = true)]
+    pub images: Vec<String>,
+
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct TimestampArgs {
+    /// Path to a file, eg: a document, to timestamp.
+    #[arg(value_name = "FILE", required = true)]
+    pub files: Vec<String>,
+
+    /// A 4-byte protocol tag, as 8 hex characters, to prepend to the Merkle root.
+    #[arg(long, value_name = "HEX", value_parser = parse_prefix_arg)]
+    pub prefix: Option<String>,
+
+    #[command(flatten)]
+    pub utxos: MaybeOfflineUtxoArgs,
+    /// Sign with a version 1 or 2 UTXO file, whose UTXOs can't be checked against the key's network & address.
+    #[arg(long = "allowUnverifiedUtxos")]
+    pub allow_unverified_utxos: bool,
+
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub fee: FeeArgs,
+    #[command(flatten)]
+    pub conf_target: ConfTargetArgs,
+    #[command(flatten)]
+    pub confirm: ConfirmArgs,
+    #[command(flatten)]
+    pub min_conf: MinConfArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl TimestampArgs {
+    pub fn to_state(&self) -> Result<State> {
+        let state = State {
+            conf_target: self.conf_target.get_conf_target(),
+            allow_unverified_utxos: self.allow_unverified_utxos,
+            utxo_filters: UtxoFilters { min_conf: self.min_conf.min_conf, ..UtxoFilters::default() },
+            ..self.network.to_state(&self.api_endpoint, OutputFormat::Text)
+        };
+        with_signing_args(state, &self.keyfile, &self.fee, &self.confirm)
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct VerifyTimestampArgs {
+    /// Path to the file to verify.
+    ///
+    /// Only one file is verified at a time, so verify each file of a multi-file timestamp separately, eg: against the
+    /// same whole `timestamp` output as the `--proof`.
+    #[arg(value_name = "FILE")]
+    pub file: String,
+
+    /// A raw BTC transaction in hex format.
+    #[arg(value_name = "TX_HEX")]
+    pub tx_hex: Option<String>,
+
+    /// Path to an inclusion proof from the `timestamp` command. Use `-` for stdin.
+    #[arg(long, value_name = "PATH")]
+    pub proof: String,
+
+    #[command(flatten)]
+    pub tx_input: TxInputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl VerifyTimestampArgs {
+    pub fn to_state(&self) -> Result<State> {
+        // NOTE: A second file would otherwise be taken as the <TX_HEX> & fail to parse confusingly.
+        if let Some(path) = self.tx_hex.as_ref().filter(|tx_hex| Path::new(tx_hex).is_file()) {
+            return Err(AppError::Custom(
+                format!("✘ Only one <FILE> can be verified at a time, but got '{}' too!", path)
+            ))
+        };
+        Ok(NetworkArgs::default().to_state(&ApiEndpointArgs::default(), OutputFormat::Text))
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ReadOpReturnArgs {
+    /// A txid, or a raw BTC transaction in hex format.
+    #[arg(value_name = "TX")]
+    pub tx: Option<String>,
+
+    #[command(flatten)]
+    pub tx_input: TxInputArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl ReadOpReturnArgs {
+    pub fn to_state(&self) -> Result<State> {
+        Ok(self.network.to_state(&self.api_endpoint, self.output.output))
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ScanOpReturnArgs {
+    /// A bitcoin address. Defaults to the address derived from the encrypted private key.
+    #[arg(value_name = "BTC_ADDRESS")]
+    pub btc_address: Option<String>,
+
+    /// List only the payloads tagged with this 4-byte protocol tag, as 8 hex characters, as set by the `--prefix` of
+    /// the commands making `OP_RETURN` txs.
+    #[arg(long, value_name = "HEX", value_parser = parse_prefix_arg)]
+    pub prefix: Option<String>,
+
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+impl ScanOpReturnArgs {
+    pub fn to_state(&self) -> Result<State> {
+        Ok(State {
+            btc_address: self.btc_address.clone(),
+            keyfile: self.keyfile.get_keyfile().to_string(),
+            ..self.network.to_state(&self.api_endpoint, self.output.output)
+        })
+    }
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ShowConfigArgs {
+    #[command(flatten)]
+    pub keyfile: KeyfileArgs,
+    #[command(flatten)]
+    pub network: NetworkArgs,
+    #[command(flatten)]
+    pub api_endpoint: ApiEndpointArgs,
+    #[command(flatten)]
+    pub fee: FeeArgs,
+    #[command(flatten)]
+    pub conf_target: ConfTargetArgs,
+    #[command(flatten)]
+    pub output: OutputArgs,
+    #[command(flatten)]
+    pub common: CommonArgs,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct CompletionsArgs {
+    /// The shell to print a completion script for.
+    #[arg(value_name = "SHELL", value_enum)]
+    pub shell: Shell,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct ManPageArgs {
+    /// Directory to save a man page per command in, rather than printing the top-level one.
+    #[arg(long = "outDir", value_name = "PATH")]
+    pub out_dir: Option<String>,
+}
+
+impl Command {
+    /// The flags every command that reads a config takes, or `None` for those that don't.
+    pub fn get_common_args(&self) -> Option<&CommonArgs> {
+        match self {
+            Command::Version(_) | Command::Completions(_) | Command::ManPage(_) => None,
+            Command::GetUtxos(args) => Some(&args.common),
+            Command::GetUtxosForAddress(args) => Some(&args.common),
+            Command::GetBalance(args) => Some(&args.common),
+            Command::GetHistory(args) => Some(&args.common),
+            Command::MakeOnlineTx(args) => Some(&args.common),
+            Command::MakeOfflineTx(args) => Some(&args.common),
+            Command::MakeOnlineOpReturnTx(args) => Some(&args.tx.common),
+            Command::MakeOfflineOpReturnTx(args) => Some(&args.tx.common),
+            Command::Consolidate(args) => Some(&args.common),
+            Command::DecodeTx(args) => Some(&args.common),
+            Command::VerifyTx(args) => Some(&args.common),
+            Command::EncodeQr(args) => Some(&args.common),
+            Command::DecodeQr(args) => Some(&args.common),
+            Command::Timestamp(args) => Some(&args.common),
+            Command::VerifyTimestamp(args) => Some(&args.common),
+            Command::ReadOpReturn(args) => Some(&args.common),
+            Command::ScanOpReturn(args) => Some(&args.common),
+            Command::ShowConfig(args) => Some(&args.common),
+        }
+    }
+
+    /// The groups of the command's flags which a config profile may set.
+    pub fn get_profile_args_mut(&mut self) -> Vec<&mut dyn ApplyProfile> {
+        match self {
+            Command::Version(_) | Command::Completions(_) | Command::ManPage(_) => vec![],
+            Command::GetUtxos(args) => {
+                vec![&mut args.keyfile, &mut args.network, &mut args.api_endpoint, &mut args.common]
+            },
+            Command::GetUtxosForAddress(args) => vec![&mut args.network, &mut args.api_endpoint, &mut args.common],
+            Command::GetBalance(args) => {
+                vec![&mut args.keyfile, &mut args.network, &mut args.api_endpoint, &mut args.common]
+            },
+            Command::GetHistory(args) => {
+                vec![&mut args.keyfile, &mut args.network, &mut args.api_endpoint, &mut args.common]
+            },
+            Command::MakeOnlineTx(args) => args.get_profile_args_mut(),
+            Command::MakeOfflineTx(args) => args.get_profile_args_mut(),
+            Command::MakeOnlineOpReturnTx(args) => args.tx.get_profile_args_mut(),
+            Command::MakeOfflineOpReturnTx(args) => args.tx.get_profile_args_mut(),
+            Command::Consolidate(args) => vec![
+                &mut args.keyfile,
+                &mut args.network,
+                &mut args.api_endpoint,
+                &mut args.fee,
+                &mut args.conf_target,
+                &mut args.common,
+            ],
+            Command::DecodeTx(args) => vec![&mut args.network, &mut args.common],
+            Command::VerifyTx(args) => vec![&mut args.network, &mut args.common],
+            Command::EncodeQr(args) => vec![&mut args.common],
+            Command::DecodeQr(args) => vec![&mut args.common],
+            Command::Timestamp(args) => vec![
+                &mut args.keyfile,
+                &mut args.network,
+                &mut args.api_endpoint,
+                &mut args.fee,
+                &mut args.conf_target,
+                &mut args.common,
+            ],
+            Command::VerifyTimestamp(args) => vec![&mut args.common],
+            Command::ReadOpReturn(args) => vec![&mut args.network, &mut args.api_endpoint, &mut args.common],
+            Command::ScanOpReturn(args) => {
+                vec![&mut args.keyfile, &mut args.network, &mut args.api_endpoint, &mut args.common]
+            },
+            Command::ShowConfig(args) => vec![
+                &mut args.keyfile,
+                &mut args.network,
+                &mut args.api_endpoint,
+                &mut args.fee,
+                &mut args.conf_target,
+                &mut args.common,
+            ],
+        }
+    }
+
+    pub fn get_output_format(&self) -> OutputFormat {
+        match self {
+            Command::Version(args) => args.output.output,
+            Command::GetUtxos(args) => args.output.output,
+            Command::GetUtxosForAddress(args) => args.output.output,
+            Command::GetBalance(args) => args.output.output,
+            Command::GetHistory(args) => args.output.output,
+            Command::MakeOnlineTx(args) => args.output.output,
+            Command::MakeOfflineTx(args) => args.output.output,
+            Command::MakeOnlineOpReturnTx(args) => args.tx.output.output,
+            Command::MakeOfflineOpReturnTx(args) => args.tx.output.output,
+            Command::Consolidate(args) => args.output.output,
+            Command::DecodeTx(args) => args.output.output,
+            Command::VerifyTx(args) => args.output.output,
+            Command::ReadOpReturn(args) => args.output.output,
+            Command::ScanOpReturn(args) => args.output.output,
+            Command::ShowConfig(args) => args.output.output,
+            // NOTE: These commands' output has a fixed format, so they've no `--output` flag & report errors as text.
+            Command::EncodeQr(_) |
+            Command::DecodeQr(_) |
+            Command::Timestamp(_) |
+            Command::VerifyTimestamp(_) |
+            Command::Completions(_) |
+            Command::ManPage(_) => OutputFormat::Text,
+        }
+    }
+
+    pub fn get_log_level(&self) -> &str {
+        self.get_common_args().map(|common| common.get_log_level()).unwrap_or(DEFAULT_LOG_LEVEL)
+    }
+
+    fn get_stdin_inputs(&self) -> Vec<&'static str> {
+        let stdin_inputs = match self {
+            Command::MakeOnlineTx(args) => vec![("--payments", is_stdin_path(&args.payments.payments))],
+            Command::MakeOnlineOpReturnTx(args) => vec![("--payments", is_stdin_path(&args.tx.payments.payments))],
+            Command::MakeOfflineTx(args) => vec![
+                ("--utxoFile", is_stdin_path(&args.utxos.utxo_file)),
+                ("--payments", is_stdin_path(&args.payments.payments)),
+            ],
+            Command::MakeOfflineOpReturnTx(args) => vec![
+                ("--utxoFile", is_stdin_path(&args.tx.utxos.utxo_file)),
+                ("--payments", is_stdin_path(&args.tx.payments.payments)),
+            ],
+            Command::Timestamp(args) => vec![("--utxoFile", is_stdin_path(&args.utxos.utxo_file))],
+            Command::DecodeTx(args) => vec![
+                ("--utxoFile", is_stdin_path(&args.utxo_file)),
+                ("--txFile", args.tx_input.reads_from_stdin(&args.tx_hex)),
+            ],
+            Command::VerifyTx(args) => vec![
+                ("--utxoFile", args.utxo_file == "-"),
+                ("--txFile", args.tx_input.reads_from_stdin(&args.tx_hex)),
+            ],
+            Command::VerifyTimestamp(args) => vec![
+                ("--proof", args.proof == "-"),
+                ("--txFile", args.tx_input.reads_from_stdin(&args.tx_hex)),
+            ],
+            Command::ReadOpReturn(args) => vec![("--txFile", args.tx_input.reads_from_stdin(&args.tx))],
+            Command::EncodeQr(args) => vec![("--inFile", args.in_file.is_none() || is_stdin_path(&args.in_file))],
+            _ => vec![],
+        };
+        stdin_inputs
+            .into_iter()
+            .filter(|(_, uses_stdin)| *uses_stdin)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+pub fn check_at_most_one_input_uses_stdin(command: Command) -> Result<Command> {
+    let stdin_inputs = command.get_stdin_inputs();
+    match stdin_inputs.len() {
+        0 | 1 => Ok(command),
+        _ => Err(AppError::Custom(
+            format!("✘ Only one input can be read from stdin, got: {}", stdin_inputs.join(", "))
+        )),
+    }
+}
+
+/// Parses the command line, printing the help or version & exiting if either was asked for.
+pub fn parse_command() -> Result<Command> {
+    Cli::try_parse()
+        .map(|cli| cli.command)
+        .map_err(|e| match e.use_stderr() {
+            true => AppError::Custom(e.to_string().trim_end().to_string()),
+            false => e.exit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    fn get_command(argv: &[&str]) -> Command {
+        Cli::try_parse_from(argv).unwrap().command
+    }
+
+    #[test]
+    fn should_build_cli_without_conflicting_args() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn should_parse_recipient_pairs() {
+        let command = get_command(&[
+            "bitcoff",
+            "makeOnlineTx",
+            "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM",
+            "546",
+            "moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE",
+            "1000sat",
+        ]);
+        let (to, amounts) = match command {
+            Command::MakeOnlineTx(args) => args.payments.get_to_and_amounts().unwrap(),
+            _ => panic!("Wrong command parsed!"),
+        };
+        assert_eq!(to, vec!["mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", "moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE"]);
+        assert_eq!(amounts, vec!["546", "1000sat"]);
+    }
+
+    #[test]
+    fn should_err_on_recipient_without_amount() {
+        match get_command(&["bitcoff", "makeOnlineTx", "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM"]) {
+            Command::MakeOnlineTx(args) => assert!(args.to_state().is_err()),
+            _ => panic!("Wrong command parsed!"),
+        }
+    }
+
+    #[test]
+    fn should_validate_args_at_parse_time() {
+        assert!(Cli::try_parse_from(["bitcoff", "getBalance", "--network=Regtest"]).is_err());
+        assert!(Cli::try_parse_from(["bitcoff", "getBalance", "--output=yaml"]).is_err());
+        assert!(Cli::try_parse_from(["bitcoff", "showConfig", "--fee=cheap"]).is_err());
+        assert!(Cli::try_parse_from(["bitcoff", "makeOfflineTx", "--payments=payments.csv"]).is_err());
+        assert!(Cli::try_parse_from(["bitcoff", "scanOpReturn", "--nonce=1"]).is_err());
+    }
+
+    #[test]
+    fn should_parse_command_aliases() {
+        match get_command(&["bitcoff", "get-balance", "--network=TESTNET", "--output=JSON"]) {
+            Command::GetBalance(args) => {
+                assert_eq!(args.network.network, Some("Testnet".to_string()));
+                assert_eq!(args.output.output, OutputFormat::Json);
+            },
+            _ => panic!("Wrong command parsed!"),
+        }
+    }
+
+    #[test]
+    fn should_reject_verifying_several_timestamped_files() {
+        let command = get_command(&["bitcoff", "verifyTimestamp", "Cargo.toml", "Cargo.lock", "--proof=proof.json"]);
+        match command {
+            Command::VerifyTimestamp(args) => assert!(args.to_state().is_err()),
+            _ => panic!("Wrong command parsed!"),
+        }
+        assert!(Cli::try_parse_from(["bitcoff", "verifyTimestamp", "a", "b", "c", "--proof=proof.json"]).is_err());
+    }
+
+    #[test]
+    fn should_default_consolidation_to_a_low_priority_auto_fee() {
+        match get_command(&["bitcoff", "consolidate"]) {
+            Command::Consolidate(args) => {
+                let state = args.to_state().unwrap();
+                assert_eq!(state.sats_per_byte, None);
+                assert_eq!(state.conf_target, CONSOLIDATION_CONF_TARGET);
+            },
+            _ => panic!("Wrong command parsed!"),
+        }
+        match get_command(&["bitcoff", "consolidate", "--fee=2", "--confTarget=3"]) {
+            Command::Consolidate(args) => {
+                let state = args.to_state().unwrap();
+                assert_eq!(state.sats_per_byte, Some(2.0));
+                assert_eq!(state.conf_target, 3);
+            },
+            _ => panic!("Wrong command parsed!"),
+        }
+    }
+
+    #[test]
+    fn should_timestamp_online_unless_utxos_are_supplied() {
+        match get_command(&["bitcoff", "timestamp", "Cargo.toml"]) {
+            Command::Timestamp(args) => assert!(args.utxos.get_offline_utxo_args().is_none()),
+            _ => panic!("Wrong command parsed!"),
+        }
+        match get_command(&["bitcoff", "timestamp", "Cargo.toml", "--utxoFile=utxos.json"]) {
+            Command::Timestamp(args) => {
+                let utxos = args.utxos.get_offline_utxo_args().unwrap();
+                assert_eq!(utxos.utxo_file, Some("utxos.json".to_string()));
+            },
+            _ => panic!("Wrong command parsed!"),
+        }
+        assert!(Cli::try_parse_from(["bitcoff", "timestamp", "Cargo.toml", "--utxoFile=a", "--utxos=[]"]).is_err());
+    }
+
+    #[test]
+    fn should_allow_a_single_stdin_input() {
+        let command = get_command(&["bitcoff", "verifyTx", "--utxoFile=utxos.json"]);
+        assert!(check_at_most_one_input_uses_stdin(command).is_ok());
+    }
+
+    #[test]
+    fn should_reject_several_stdin_inputs() {
+        let command = get_command(&["bitcoff", "verifyTx", "--utxoFile=-"]);
+        assert_eq!(command.get_stdin_inputs(), vec!["--utxoFile", "--txFile"]);
+        assert!(check_at_most_one_input_uses_stdin(command).is_err());
+    }
+
+    #[test]
+    fn should_take_trailing_positional_as_op_return_data() {
+        let command = get_command(&[
+            "bitcoff",
+            "makeOnlineOpReturnTx",
+            "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM",
+            "546",
+            "decaff",
+        ]);
+        let (data, payments) = match command {
+            Command::MakeOnlineOpReturnTx(args) => args.op_return.get_data_and_payments(&args.tx.payments).unwrap(),
+            _ => panic!("Wrong command parsed!"),
+        };
+        assert_eq!(data, vec!["decaff"]);
+        assert_eq!(payments.recipients, vec!["mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", "546"]);
+    }
+
+    #[test]
+    fn should_err_if_no_op_return_data_supplied() {
+        let command = get_command(&["bitcoff", "makeOnlineOpReturnTx", "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", "546"]);
+        match command {
+            Command::MakeOnlineOpReturnTx(args) => {
+                assert!(args.op_return.get_data_and_payments(&args.tx.payments).is_err())
+            },
+            _ => panic!("Wrong command parsed!"),
+        }
+    }
+
+    #[test]
+    fn should_parse_repeated_op_return_data() {
+        let command = get_command(&[
+            "bitcoff",
+            "makeOfflineOpReturnTx",
+            "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM",
+            "546",
+            "--data=decaff",
+            "--data=text:gm",
+            "--utxoFile=utxos.json",
+        ]);
+        match command {
+            Command::MakeOfflineOpReturnTx(args) => {
+                assert_eq!(args.tx.payments.recipients, vec!["mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", "546"]);
+                assert_eq!(args.op_return.data, vec!["decaff", "text:gm"]);
+            },
+            _ => panic!("Wrong command parsed!"),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use clap::CommandFactory;
+use clap_complete::{
+    Shell,
+    generate,
+};
+use crate::{
+    types::Result,
+    commands::Cli,
+    utils::bytes_to_utf8_str,
+};
+
+pub fn get_completions(shell: Shell) -> Result<String> {
+    info!("✔ Generating {} completion script...", shell);
+    let mut buffer = vec![];
+    generate(shell, &mut Cli::command(), "bitcoff", &mut buffer);
+    bytes_to_utf8_str(&buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_get_completions_for_each_shell() {
+        vec![Shell::Bash, Shell::Zsh, Shell::Fish]
+            .into_iter()
+            .for_each(|shell| assert!(get_completions(shell).unwrap().contains("makeOfflineOpReturnTx")));
+    }
+}
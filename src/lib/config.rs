@@ -0,0 +1,274 @@
+use std::{
+    fs,
+    env,
+    path::PathBuf,
+    collections::BTreeMap,
+};
+use serde_json::json;
+use crate::{
+    types::Result,
+    errors::AppError,
+    output_format::OutputFormat,
+    constants::CONFIG_FILE_PATH,
+    commands::{
+        Command,
+        NETWORK_NAMES,
+        ShowConfigArgs,
+        get_canonical_network_name,
+    },
+};
+
+/// A fee rate in a config file, which may be a number, eg: `fee = 12.5`, or a string, eg: `fee = "auto"`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum FeeValue {
+    Rate(f64),
+    Text(String),
+}
+
+impl FeeValue {
+    pub fn to_cli_arg(&self) -> String {
+        match self {
+            FeeValue::Rate(rate) => rate.to_string(),
+            FeeValue::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// A named set of defaults for the CLI flags of the same names, any of which may be omitted.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Profile {
+    #[serde(skip)]
+    pub name: Option<String>,
+    pub network: Option<String>,
+    pub keyfile: Option<String>,
+    pub fee: Option<FeeValue>,
+    pub conf_target: Option<u64>,
+    pub change: Option<String>,
+    pub log_level: Option<String>,
+    pub api_endpoint: Option<String>,
+    pub max_fee: Option<u64>,
+    pub max_fee_pct: Option<f64>,
+}
+
+/// A group of flags, some of which may be set by a config profile instead of on the command line.
+pub trait ApplyProfile {
+    /// Fills in any of the flags not passed on the command line from the `profile`.
+    fn apply_profile(&mut self, profile: &Profile);
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Config {
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// The config file's default path, under `$XDG_CONFIG_HOME`, else under `$HOME/.config`.
+pub fn get_default_config_path() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|config_dir| config_dir.join(CONFIG_FILE_PATH))
+}
+
+pub fn get_config_path(maybe_path: &Option<String>) -> Option<PathBuf> {
+    match maybe_path {
+        Some(path) => Some(PathBuf::from(path)),
+        None => get_default_config_path(),
+    }
+}
+
+pub fn parse_config(toml_string: &str) -> Result<Config> {
+    toml::from_str(toml_string).map_err(|e| AppError::Custom(format!("✘ Error parsing config file: {}", e)))
+}
+
+/// Loads the config file at `maybe_path`, which must exist, else the one at the default path, if there is one.
+pub fn load_config(maybe_path: &Option<String>) -> Result<Config> {
+    match get_config_path(maybe_path) {
+        Some(ref path) if path.exists() => fs::read_to_string(path)
+            .map_err(|e| AppError::Custom(format!("✘ Error reading config file '{}': {}", path.display(), e)))
+            .and_then(|toml_string| parse_config(&toml_string)),
+        Some(ref path) if maybe_path.is_some() => Err(AppError::Custom(
+            format!("✘ Config file '{}' does not exist!", path.display())
+        )),
+        _ => Ok(Config::default()),
+    }
+}
+
+/// Checks the profile's network is one the `--network` flag accepts, & spells it as the flag would.
+fn check_profile_network(profile: Profile) -> Result<Profile> {
+    match profile.network.as_deref().map(|network| (network, get_canonical_network_name(network))) {
+        None => Ok(profile),
+        Some((_, Some(network))) => Ok(Profile { network: Some(network.to_string()), ..profile }),
+        Some((network, None)) => Err(AppError::Custom(format!(
+            "✘ Profile '{}' has an unsupported network '{}', expected one of: {}!",
+            profile.name.as_deref().unwrap_or_default(),
+            network,
+            NETWORK_NAMES.join(", "),
+        ))),
+    }
+}
+
+pub fn get_profile(config: &Config, maybe_name: &Option<String>) -> Result<Profile> {
+    match maybe_name {
+        None => Ok(Profile::default()),
+        Some(name) => config
+            .profiles
+            .get(name)
+            .map(|profile| Profile { name: Some(name.clone()), ..profile.clone() })
+            .ok_or_else(|| AppError::Custom(format!("✘ No profile named '{}' in config file!", name)))
+            .and_then(check_profile_network),
+    }
+}
+
+/// Fills in any of the command's flags not passed on the command line from the `--profile`, else the config's
+/// default profile.
+pub fn apply_config_to_command(mut command: Command) -> Result<Command> {
+    let (maybe_config_path, maybe_profile_name) = match command.get_common_args() {
+        Some(common) => (common.config.clone(), common.profile.clone()),
+        None => return Ok(command),
+    };
+    let config = load_config(&maybe_config_path)?;
+    let profile = get_profile(&config, &maybe_profile_name.or_else(|| config.default_profile.clone()))?;
+    command.get_profile_args_mut().into_iter().for_each(|args| args.apply_profile(&profile));
+    Ok(command)
+}
+
+fn get_config_path_description(maybe_path: &Option<String>) -> String {
+    match get_config_path(maybe_path) {
+        Some(ref path) if path.exists() => path.display().to_string(),
+        Some(path) => format!("{} (not found)", path.display()),
+        None => "none".to_string(),
+    }
+}
+
+/// The settings a command would run with once the config profile & CLI flags are combined.
+pub fn get_effective_config_output(args: &ShowConfigArgs) -> Result<String> {
+    let config_path = get_config_path_description(&args.common.config);
+    let api_endpoint = args.api_endpoint.get_api_endpoint(args.network.get_network());
+    let to_string_or_none = |maybe_value: Option<String>| maybe_value.unwrap_or_else(|| "none".to_string());
+    match args.output.output {
+        OutputFormat::Json => Ok(
+            json!({
+                "config": config_path,
+                "profile": args.common.profile,
+                "network": args.network.get_network_name(),
+                "keyfile": args.keyfile.get_keyfile(),
+                "fee": args.fee.get_fee(),
+                "confTarget": args.conf_target.get_conf_target(),
+                "change": args.fee.get_change(),
+                "logLevel": args.common.get_log_level(),
+                "apiEndpoint": api_endpoint,
+                "maxFee": args.fee.max_fee,
+                "maxFeePct": args.fee.max_fee_pct,
+            }).to_string()
+        ),
+        OutputFormat::Text => Ok(
+            [
+                "❍ Config ❍\n".to_string(),
+                format!("    Config file:   {}", config_path),
+                format!("    Profile:       {}", to_string_or_none(args.common.profile.clone())),
+                format!("    Network:       {}", args.network.get_network_name()),
+                format!("    Keyfile:       {}", args.keyfile.get_keyfile()),
+                format!("    Fee:           {}", args.fee.get_fee()),
+                format!("    Conf target:   {}", args.conf_target.get_conf_target()),
+                format!("    Change:        {}", args.fee.get_change()),
+                format!("    Log level:     {}", args.common.get_log_level()),
+                format!("    API endpoint:  {}", api_endpoint),
+                format!("    Max fee:       {}", to_string_or_none(args.fee.max_fee.map(|fee| fee.to_string()))),
+                format!("    Max fee %:     {}", to_string_or_none(args.fee.max_fee_pct.map(|pct| pct.to_string()))),
+            ].join("\n")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use crate::commands::Cli;
+
+    const SAMPLE_CONFIG: &str = "
+defaultProfile = \"testnet-dev\"
+
+[profiles.mainnet-treasury]
+network = \"Bitcoin\"
+keyfile = \"./treasury-key.gpg\"
+fee = \"auto\"
+confTarget = 3
+maxFee = 50000
+maxFeePct = 1.5
+
+[profiles.testnet-dev]
+network = \"Testnet\"
+fee = 2
+logLevel = \"debug\"
+apiEndpoint = \"http://localhost:3002/\"
+";
+
+    #[test]
+    fn should_parse_config_with_profiles() {
+        let config = parse_config(SAMPLE_CONFIG).unwrap();
+        assert_eq!(config.default_profile, Some("testnet-dev".to_string()));
+        assert_eq!(config.profiles.len(), 2);
+        let treasury = &config.profiles["mainnet-treasury"];
+        assert_eq!(treasury.fee, Some(FeeValue::Text("auto".to_string())));
+        assert_eq!(treasury.conf_target, Some(3));
+        assert_eq!(treasury.max_fee_pct, Some(1.5));
+        assert_eq!(config.profiles["testnet-dev"].fee.as_ref().map(FeeValue::to_cli_arg), Some("2".to_string()));
+    }
+
+    #[test]
+    fn should_err_parsing_config_with_unknown_setting() {
+        assert!(parse_config("[profiles.dev]\nnetwrok = \"Testnet\"").is_err());
+    }
+
+    #[test]
+    fn should_err_getting_unknown_profile() {
+        let config = parse_config(SAMPLE_CONFIG).unwrap();
+        assert!(get_profile(&config, &Some("mainnet-hot".to_string())).is_err());
+        assert_eq!(get_profile(&config, &None).unwrap(), Profile::default());
+    }
+
+    #[test]
+    fn should_canonicalise_profile_network() {
+        let config = parse_config("[profiles.dev]\nnetwork = \"testnet\"").unwrap();
+        let profile = get_profile(&config, &Some("dev".to_string())).unwrap();
+        assert_eq!(profile.network, Some("Testnet".to_string()));
+    }
+
+    #[test]
+    fn should_err_getting_profile_with_unsupported_network() {
+        ["testnett", "regtest"].iter().for_each(|network| {
+            let config = parse_config(&format!("[profiles.dev]\nnetwork = \"{}\"", network)).unwrap();
+            assert!(get_profile(&config, &Some("dev".to_string())).is_err());
+        });
+    }
+
+    #[test]
+    fn should_let_cli_flags_override_profile() {
+        let config = parse_config(SAMPLE_CONFIG).unwrap();
+        let profile = get_profile(&config, &Some("mainnet-treasury".to_string())).unwrap();
+        let argv = ["bitcoff", "makeOnlineTx", "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", "546", "--fee=10"];
+        let mut command = Cli::try_parse_from(argv).unwrap().command;
+        command.get_profile_args_mut().into_iter().for_each(|args| args.apply_profile(&profile));
+        let args = match command {
+            Command::MakeOnlineTx(args) => args,
+            _ => panic!("Wrong command parsed!"),
+        };
+        assert_eq!(args.fee.fee, Some("10".to_string()));
+        assert_eq!(args.network.network, Some("Bitcoin".to_string()));
+        assert_eq!(args.keyfile.keyfile, Some("./treasury-key.gpg".to_string()));
+        assert_eq!(args.fee.max_fee, Some(50000));
+        assert_eq!(args.common.profile, Some("mainnet-treasury".to_string()));
+    }
+
+    #[test]
+    fn should_err_loading_missing_explicit_config_file() {
+        assert!(load_config(&Some("/does/not/exist/config.toml".to_string())).is_err());
+    }
+}
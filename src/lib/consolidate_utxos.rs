@@ -0,0 +1,40 @@
+use crate::{
+    types::Result,
+    commands::ConsolidateArgs,
+    save_output::maybe_save_output,
+    batch_payments::process_payment_batches,
+    create_tx::create_tx_and_add_to_state,
+    btc_tx_summary::{
+        get_tx_summary_and_add_to_state,
+        maybe_confirm_tx_summary_in_state,
+    },
+    consolidation::{
+        maybe_warn_about_fee_rate_in_state,
+        get_consolidation_output_from_state,
+        split_state_into_consolidation_batches,
+    },
+    get_utxos_info::get_utxos_info_and_add_to_state,
+    fee_estimates::maybe_get_fee_rate_estimate_and_add_to_state,
+    get_btc_private_key::get_btc_private_key_and_add_to_state,
+    extract_utxos_from_utxo_info::extract_utxos_and_add_to_state,
+    get_btc_txs_from_utxos_info::get_txs_from_utxo_infos_and_put_in_state,
+};
+
+pub fn consolidate_utxos(args: &ConsolidateArgs) -> Result<String> {
+    info!("✔ Consolidating UTXOs...");
+    args.to_state()
+        .and_then(maybe_get_fee_rate_estimate_and_add_to_state)
+        .and_then(|state| maybe_warn_about_fee_rate_in_state(state, &args.future_fee))
+        .and_then(get_btc_private_key_and_add_to_state)
+        .and_then(get_utxos_info_and_add_to_state)
+        .and_then(get_txs_from_utxo_infos_and_put_in_state)
+        .and_then(extract_utxos_and_add_to_state)
+        .and_then(|state| split_state_into_consolidation_batches(state, args.max_inputs))
+        .and_then(|states| process_payment_batches(states, |state|
+            get_tx_summary_and_add_to_state(state)
+                .and_then(maybe_confirm_tx_summary_in_state)
+                .and_then(create_tx_and_add_to_state)
+                .and_then(|state| get_consolidation_output_from_state(state, &args.future_fee))
+        ))
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use bitcoin::blockdata::transaction::TxOut as BtcTxOut;
+use crate::{
+    state::State,
+    errors::AppError,
+    btc_amount::parse_fee_rate,
+    output_format::OutputFormat,
+    batch_payments::tx_fits_weight_limit,
+    get_tx_output::get_tx_output_from_state,
+    utils::{
+        format_sats,
+        create_new_tx_output,
+        calculate_btc_tx_size,
+        get_change_address_from_state,
+    },
+    types::{
+        Result,
+        BtcUtxosAndValues,
+        BtcAddressAndAmount,
+        BtcAddressesAndAmounts,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsolidationReport {
+    pub fee: u64,
+    pub num_inputs: usize,
+    pub sats_per_byte: f64,
+    pub future_sats_per_byte: f64,
+    pub projected_savings: u64,
+}
+
+impl ConsolidationReport {
+    pub fn new(num_inputs: usize, fee: u64, sats_per_byte: f64, future_sats_per_byte: f64) -> Self {
+        // NOTE: Spending the one consolidated output later costs one input instead of `num_inputs` of them.
+        let future_bytes_saved = calculate_btc_tx_size(num_inputs, 0) - calculate_btc_tx_size(1, 0);
+        ConsolidationReport {
+            fee,
+            num_inputs,
+            sats_per_byte,
+            future_sats_per_byte,
+            projected_savings: (future_bytes_saved as f64 * future_sats_per_byte).floor() as u64,
+        }
+    }
+
+    pub fn get_fee_per_input(&self) -> f64 {
+        self.fee as f64 / self.num_inputs as f64
+    }
+
+    pub fn get_net_savings(&self) -> i64 {
+        self.projected_savings as i64 - self.fee as i64
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "fee": self.fee,
+            "num_inputs": self.num_inputs,
+            "net_savings": self.get_net_savings(),
+            "sats_per_byte": self.sats_per_byte,
+            "fee_per_input": self.get_fee_per_input(),
+            "projected_savings": self.projected_savings,
+            "future_sats_per_byte": self.future_sats_per_byte,
+        })
+    }
+}
+
+impl fmt::Display for ConsolidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "❍ Consolidation Report ❍\n")?;
+        writeln!(f, "    Inputs:        {} UTXO(s) ❍ {} sats-per-byte", self.num_inputs, self.sats_per_byte)?;
+        writeln!(f, "    Fee:           {}", format_sats(self.fee))?;
+        writeln!(f, "    Fee per input: {:.2} sats", self.get_fee_per_input())?;
+        writeln!(
+            f,
+            "    Savings:       {} ❍ when later spent at {} sats-per-byte",
+            format_sats(self.projected_savings),
+            self.future_sats_per_byte,
+        )?;
+        write!(f, "    Net savings:   {} sats", self.get_net_savings())
+    }
+}
+
+pub fn get_max_inputs_per_consolidation_tx(output: &BtcTxOut) -> usize {
+    let mut max_inputs = 1;
+    while tx_fits_weight_limit(max_inputs + 1, std::slice::from_ref(output)) {
+        max_inputs += 1;
+    }
+    max_inputs
+}
+
+pub fn get_consolidation_candidates(utxos: &BtcUtxosAndValues, maybe_max_inputs: Option<usize>) -> Result<BtcUtxosAndValues> {
+    info!("✔ Getting consolidation candidates...");
+    let mut candidates = utxos.to_vec();
+    // NOTE: Smallest first, since those are the costliest to spend relative to their value.
+    candidates.sort_by_key(|utxo| utxo.value);
+    if let Some(max_inputs) = maybe_max_inputs {
+        candidates.truncate(max_inputs);
+    };
+    match candidates.len() {
+        0 | 1 => Err(AppError::Custom(
+            format!("✘ Need at least 2 UTXOs to consolidate but only {} are available!", candidates.len())
+        )),
+        _ => Ok(BtcUtxosAndValues::from_vec(candidates)),
+    }
+}
+
+pub fn split_consolidation_candidates_into_batches(
+    candidates: &BtcUtxosAndValues,
+    max_inputs_per_tx: usize,
+) -> Vec<BtcUtxosAndValues> {
+    // NOTE: A lone leftover UTXO is left unconsolidated since there'd be nothing to merge it with.
+    candidates
+        .to_vec()
+        .chunks(max_inputs_per_tx)
+        .filter(|chunk| chunk.len() >= 2)
+        .map(|chunk| BtcUtxosAndValues::from_vec(chunk.to_vec()))
+        .collect()
+}
+
+pub fn split_state_into_consolidation_batches(state: State, maybe_max_inputs: Option<usize>) -> Result<Vec<State>> {
+    info!("✔ Splitting state into consolidation batches...");
+    let destination = BtcAddressAndAmount::new(&get_change_address_from_state(&state)?, 0)?;
+    let candidates = get_consolidation_candidates(state.get_btc_utxos_and_values()?, maybe_max_inputs)?;
+    let max_inputs_per_tx = get_max_inputs_per_consolidation_tx(&create_new_tx_output(&destination)?);
+    let batches = split_consolidation_candidates_into_batches(&candidates, max_inputs_per_tx);
+    info!("✔ Consolidating {} UTXO(s) in {} tx(s)...", candidates.len(), batches.len());
+    Ok(
+        batches
+            .into_iter()
+            .map(|utxos| State {
+                sweep: true,
+                ..state.to_batch_state(BtcAddressesAndAmounts(vec![destination.clone()]), utxos)
+            })
+            .collect()
+    )
+}
+
+/// Warns if the fee rate is above the `future_fee` rate, since consolidating would then cost more than it saves.
+pub fn maybe_warn_about_fee_rate_in_state(state: State, future_fee: &str) -> Result<State> {
+    let sats_per_byte = state.get_sats_per_byte()?;
+    let future_sats_per_byte = parse_fee_rate(future_fee)?;
+    if sats_per_byte > future_sats_per_byte {
+        warn!(
+            "✘ Fee rate of {} sats-per-byte is above the `--futureFee` of {} ∴ consolidating now won't save fees!",
+            sats_per_byte,
+            future_sats_per_byte,
+        );
+    };
+    Ok(state)
+}
+
+pub fn get_consolidation_output_from_state(state: State, future_fee: &str) -> Result<String> {
+    info!("✔ Getting consolidation output from state...");
+    let summary = state.get_btc_tx_summary()?;
+    let tx = state.get_btc_tx()?;
+    let fee = summary.utxo_total - tx.output.iter().map(|output| output.value).sum::<u64>();
+    let report = ConsolidationReport::new(
+        summary.num_inputs,
+        fee,
+        state.get_sats_per_byte()?,
+        parse_fee_rate(future_fee)?,
+    );
+    info!("✔ {}", report);
+    match state.output_format {
+        OutputFormat::Text => {
+            eprintln!("{}\n", report);
+            get_tx_output_from_state(state)
+        },
+        OutputFormat::Json => {
+            let mut output: JsonValue = serde_json::from_str(&get_tx_output_from_state(state)?)?;
+            output["consolidation"] = report.to_json();
+            Ok(output.to_string())
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::MAX_STANDARD_TX_WEIGHT,
+        test_utils::get_sample_utxo,
+    };
+
+    fn get_sample_utxos(values: &[u64]) -> BtcUtxosAndValues {
+        BtcUtxosAndValues::from_vec(
+            values
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let mut utxo = get_sample_utxo();
+                    utxo.value = *value;
+                    utxo.utxo.previous_output.vout = i as u32;
+                    utxo
+                })
+                .collect()
+        )
+    }
+
+    #[test]
+    fn should_get_max_inputs_per_consolidation_tx() {
+        let destination = BtcAddressAndAmount::new("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM", 0).unwrap();
+        let output = create_new_tx_output(&destination).unwrap();
+        let result = get_max_inputs_per_consolidation_tx(&output);
+        assert_eq!(result, 670);
+        assert!(calculate_btc_tx_size(result + 1, 1) * 4 > MAX_STANDARD_TX_WEIGHT);
+    }
+
+    #[test]
+    fn should_get_smallest_consolidation_candidates_first() {
+        let result = get_consolidation_candidates(&get_sample_utxos(&[5_000, 1_000, 3_000, 2_000]), Some(3)).unwrap();
+        assert_eq!(result.to_vec().iter().map(|utxo| utxo.value).collect::<Vec<u64>>(), vec![1_000, 2_000, 3_000]);
+    }
+
+    #[test]
+    fn should_err_if_too_few_consolidation_candidates() {
+        assert!(get_consolidation_candidates(&get_sample_utxos(&[5_000]), None).is_err());
+        assert!(get_consolidation_candidates(&get_sample_utxos(&[5_000, 1_000]), Some(1)).is_err());
+    }
+
+    #[test]
+    fn should_split_consolidation_candidates_into_batches() {
+        let candidates = get_sample_utxos(&[1_000; 7]);
+        let result = split_consolidation_candidates_into_batches(&candidates, 3);
+        assert_eq!(result.iter().map(|batch| batch.len()).collect::<Vec<usize>>(), vec![3, 3]);
+    }
+
+    #[test]
+    fn should_get_consolidation_report() {
+        let result = ConsolidationReport::new(10, 7_450, 5.0, 50.0);
+        assert_eq!(result.get_fee_per_input(), 745.0);
+        assert_eq!(result.projected_savings, 9 * 149 * 50);
+        assert_eq!(result.get_net_savings(), 67_050 - 7_450);
+    }
+}
@@ -1,3 +1,20 @@
 pub const ONE_BTC: f64 = 100_000_000.0;
 pub const BLOCK_EXPLORER_URL: &str = "https://blockstream.info/";
+pub const DEFAULT_FEE: &str = "23";
+pub const DEFAULT_CONF_TARGET: u64 = 6;
+pub const CONSOLIDATION_CONF_TARGET: u64 = 144; // NOTE: About a day, since consolidating is rarely urgent.
+pub const DEFAULT_NETWORK: &str = "Bitcoin";
+pub const DEFAULT_LOG_LEVEL: &str = "none";
+pub const DEFAULT_CHANGE_ADDRESS: &str = "signer";
+pub const DEFAULT_KEYFILE_PATH: &str = "./encrypted-btc-private-key.gpg";
+pub const CONFIG_FILE_PATH: &str = "bitcoff/config.toml";
 pub const DEFAULT_BTC_SEQUENCE: u32 = 4294967295; // NOTE: 0xFFFFFFFF
+pub const MAX_BIP125_RBF_SEQUENCE: u32 = 4294967293; // NOTE: 0xFFFFFFFD
+pub const MAX_LOCK_TIME_SEQUENCE: u32 = 4294967294; // NOTE: 0xFFFFFFFE
+pub const DUST_RELAY_FEE_RATE: u64 = 3; // NOTE: In sats-per-vbyte.
+pub const MAX_OP_RETURN_RELAY: usize = 83;
+pub const MAX_LARGE_OP_RETURN_RELAY: usize = 100_000;
+pub const MAX_STANDARD_TX_WEIGHT: u64 = 400_000;
+pub const MAX_BTC_SUPPLY_IN_SATS: u64 = 2_100_000_000_000_000;
+pub const MIN_RELAY_FEE_RATE: f64 = 1.0; // NOTE: In sats-per-vbyte.
+pub const MAX_SANE_FEE_RATE: f64 = 1_000.0; // NOTE: In sats-per-vbyte.
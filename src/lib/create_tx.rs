@@ -1,19 +1,37 @@
-use crate::lib::{
+use crate::{
     state::State,
     types::Result,
-    utils::get_change_address_from_cli_args_in_state,
-    btc_transaction::create_signed_raw_btc_tx_for_n_input_n_outputs,
+    utils::get_change_address_from_state,
+    tx_builder::{
+        TxBuilder,
+        ChangePolicy,
+    },
 };
 
+pub fn get_tx_builder_from_state(state: &State) -> Result<TxBuilder> {
+    let tx_builder = TxBuilder::new()
+        .add_recipients(state.addresses_and_amounts.clone())
+        .inputs(state.get_btc_utxos_and_values()?.clone())
+        .fee_rate(state.get_sats_per_byte()?)
+        .fee_caps(state.max_fee, state.max_fee_pct)
+        .subtract_fee_from(&state.subtract_fee_from)
+        .op_return_policy(state.op_return_policy)
+        .change_policy(match state.sweep {
+            true => ChangePolicy::Sweep,
+            false => ChangePolicy::ToAddress(get_change_address_from_state(state)?),
+        });
+    Ok(
+        state
+            .op_return_outputs
+            .iter()
+            .cloned()
+            .fold(tx_builder, |tx_builder, output| tx_builder.add_output(output))
+    )
+}
+
 pub fn create_tx_and_add_to_state(state: State) -> Result<State> {
     info!("✔ Creating tx and adding to state...");
-    create_signed_raw_btc_tx_for_n_input_n_outputs(
-        state.cli_args.flag_fee,
-        state.addresses_and_amounts.clone(),
-        &get_change_address_from_cli_args_in_state(&state)?,
-        *state.get_btc_private_key()?,
-        state.get_btc_utxos_and_values()?,
-        None,
-    )
+    get_tx_builder_from_state(&state)
+        .and_then(|tx_builder| tx_builder.sign(state.get_btc_private_key()?))
         .and_then(|tx| state.add_btc_tx(tx))
 }
@@ -0,0 +1,205 @@
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use bitcoin::{
+    hashes::{
+        Hash,
+        sha256d,
+    },
+    consensus::encode::serialize as btc_serialize,
+    network::constants::Network as BtcNetwork,
+    blockdata::transaction::{
+        TxIn as BtcUtxo,
+        TxOut as BtcTxOut,
+        Transaction as BtcTransaction,
+    },
+};
+use crate::{
+    state::State,
+    errors::AppError,
+    constants::MAX_BIP125_RBF_SEQUENCE,
+    utils::get_op_return_payload,
+    btc_destination::{
+        get_script_type,
+        get_address_from_script,
+    },
+    types::{
+        Result,
+        BtcUtxosAndValues,
+    },
+};
+
+pub fn get_btc_tx_size(tx: &BtcTransaction) -> u64 {
+    btc_serialize(tx).len() as u64
+}
+
+pub fn get_btc_tx_vsize(tx: &BtcTransaction) -> u64 {
+    tx.get_weight().div_ceil(4) as u64
+}
+
+pub fn get_btc_tx_wtxid(tx: &BtcTransaction) -> String {
+    sha256d::Hash::hash(&btc_serialize(tx)).to_string()
+}
+
+pub fn btc_tx_signals_rbf(tx: &BtcTransaction) -> bool {
+    tx.input.iter().any(|input| input.sequence <= MAX_BIP125_RBF_SEQUENCE)
+}
+
+fn get_input_value(input: &BtcUtxo, maybe_utxos: Option<&BtcUtxosAndValues>) -> Option<u64> {
+    maybe_utxos
+        .and_then(|utxos| utxos.get_utxo_and_value_for_outpoint(&input.previous_output))
+        .map(|utxo_and_value| utxo_and_value.value)
+}
+
+pub fn get_btc_tx_fee(tx: &BtcTransaction, utxos: &BtcUtxosAndValues) -> Result<u64> {
+    let input_values = tx
+        .input
+        .iter()
+        .map(|input| get_input_value(input, Some(utxos)))
+        .collect::<Option<Vec<u64>>>()
+        .ok_or_else(|| AppError::Custom("✘ Not all of the tx's inputs are in the UTXO set supplied!".to_string()))?;
+    let output_total: u64 = tx.output.iter().map(|output| output.value).sum();
+    input_values
+        .iter()
+        .sum::<u64>()
+        .checked_sub(output_total)
+        .ok_or_else(|| AppError::Custom("✘ Tx outputs total more than its inputs!".to_string()))
+}
+
+fn decode_btc_tx_input(input: &BtcUtxo, maybe_utxos: Option<&BtcUtxosAndValues>) -> JsonValue {
+    json!({
+        "vout": input.previous_output.vout,
+        "sequence": input.sequence,
+        "txid": input.previous_output.txid.to_string(),
+        "value": get_input_value(input, maybe_utxos),
+        "script_sig": hex::encode(input.script_sig.as_bytes()),
+        "witness": input.witness.iter().map(hex::encode).collect::<Vec<String>>(),
+    })
+}
+
+fn decode_btc_tx_output(index: usize, output: &BtcTxOut, network: BtcNetwork) -> JsonValue {
+    json!({
+        "index": index,
+        "value": output.value,
+        "script_pubkey": hex::encode(output.script_pubkey.as_bytes()),
+        "type": get_script_type(&output.script_pubkey),
+        "op_return": get_op_return_payload(&output.script_pubkey).map(hex::encode),
+        "address": get_address_from_script(&output.script_pubkey, network),
+    })
+}
+
+pub fn decode_btc_tx(
+    tx: &BtcTransaction,
+    network: BtcNetwork,
+    maybe_utxos: Option<&BtcUtxosAndValues>,
+) -> Result<JsonValue> {
+    info!("✔ Decoding BTC tx...");
+    let vsize = get_btc_tx_vsize(tx);
+    let maybe_fee = match maybe_utxos {
+        Some(utxos) => Some(get_btc_tx_fee(tx, utxos)?),
+        None => None,
+    };
+    Ok(
+        json!({
+            "vsize": vsize,
+            "version": tx.version,
+            "fee": maybe_fee,
+            "weight": tx.get_weight(),
+            "txid": tx.txid().to_string(),
+            "lock_time": tx.lock_time,
+            "rbf": btc_tx_signals_rbf(tx),
+            "size": get_btc_tx_size(tx),
+            "wtxid": get_btc_tx_wtxid(tx),
+            "sats_per_vbyte": maybe_fee.map(|fee| fee as f64 / vsize as f64),
+            "inputs": tx
+                .input
+                .iter()
+                .map(|input| decode_btc_tx_input(input, maybe_utxos))
+                .collect::<Vec<JsonValue>>(),
+            "outputs": tx
+                .output
+                .iter()
+                .enumerate()
+                .map(|(i, output)| decode_btc_tx_output(i, output, network))
+                .collect::<Vec<JsonValue>>(),
+        })
+    )
+}
+
+pub fn get_decoded_tx_json_string_from_state(state: State) -> Result<String> {
+    info!("✔ Getting decoded tx JSON string from state...");
+    decode_btc_tx(state.get_btc_tx()?, state.network, state.btc_utxos_and_values.as_ref())
+        .map(|json| json.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::get_op_return_output,
+        get_btc_txs_from_utxos_info::convert_hex_tx_to_btc_tx,
+        test_utils::{
+            get_sample_tx,
+            get_sample_utxo,
+            SAMPLE_TESTNET_TX_ID,
+            SAMPLE_TARGET_BTC_ADDRESS,
+            get_sample_signed_tx_hex,
+        },
+    };
+
+    #[test]
+    fn should_decode_btc_tx() {
+        let expected_size = 225;
+        let expected_num_outputs = 2;
+        let result = decode_btc_tx(&get_sample_tx(), BtcNetwork::Testnet, None).unwrap();
+        assert_eq!(result["txid"], SAMPLE_TESTNET_TX_ID);
+        assert_eq!(result["wtxid"], SAMPLE_TESTNET_TX_ID);
+        assert_eq!(result["size"], expected_size);
+        assert_eq!(result["vsize"], expected_size);
+        assert_eq!(result["weight"], expected_size * 4);
+        assert_eq!(result["rbf"], false);
+        assert_eq!(result["fee"], JsonValue::Null);
+        assert_eq!(result["outputs"].as_array().unwrap().len(), expected_num_outputs);
+        assert_eq!(result["outputs"][0]["value"], 1666);
+        assert_eq!(result["outputs"][0]["address"], SAMPLE_TARGET_BTC_ADDRESS);
+        assert_eq!(result["outputs"][0]["type"], "p2pkh");
+    }
+
+    #[test]
+    fn should_get_btc_tx_fee() {
+        let expected_fee = 19300;
+        let tx = convert_hex_tx_to_btc_tx(get_sample_signed_tx_hex()).unwrap();
+        let utxos = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        let result = get_btc_tx_fee(&tx, &utxos).unwrap();
+        assert_eq!(result, expected_fee);
+    }
+
+    #[test]
+    fn should_err_getting_fee_if_utxo_missing() {
+        let tx = convert_hex_tx_to_btc_tx(get_sample_signed_tx_hex()).unwrap();
+        let utxos = BtcUtxosAndValues::from_vec(vec![]);
+        assert!(get_btc_tx_fee(&tx, &utxos).is_err());
+    }
+
+    #[test]
+    fn should_get_op_return_payload() {
+        let payload = hex::decode("decaff").unwrap();
+        let output = get_op_return_output(&payload).unwrap();
+        assert_eq!(get_op_return_payload(&output.script_pubkey), Some(payload));
+    }
+
+    #[test]
+    fn should_not_get_op_return_payload_from_non_op_return_output() {
+        let tx = get_sample_tx();
+        assert_eq!(get_op_return_payload(&tx.output[0].script_pubkey), None);
+    }
+
+    #[test]
+    fn should_detect_rbf_signalling() {
+        let mut tx = get_sample_tx();
+        assert!(!btc_tx_signals_rbf(&tx));
+        tx.input[0].sequence = MAX_BIP125_RBF_SEQUENCE;
+        assert!(btc_tx_signals_rbf(&tx));
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    types::Result,
+    commands::DecodeQrArgs,
+    utils::bytes_to_utf8_str,
+    save_output::maybe_save_output,
+    qr_code::read_qr_codes_from_image,
+    bbqr::{
+        BbqrFileType,
+        decode_bbqr,
+    },
+};
+
+fn get_output_from_bbqr_bytes(file_type: BbqrFileType, bytes: &[u8]) -> Result<String> {
+    match file_type {
+        BbqrFileType::Psbt | BbqrFileType::Transaction => Ok(hex::encode(bytes)),
+        BbqrFileType::Json | BbqrFileType::Unicode => bytes_to_utf8_str(bytes),
+    }
+}
+
+pub fn decode_qr(args: &DecodeQrArgs) -> Result<String> {
+    info!("✔ Decoding QR code(s) from {} image(s)...", args.images.len());
+    args
+        .images
+        .iter()
+        .map(|path| read_qr_codes_from_image(path))
+        .collect::<Result<Vec<Vec<String>>>>()
+        .and_then(|parts| decode_bbqr(&parts.concat()))
+        .and_then(|(file_type, bytes)| get_output_from_bbqr_bytes(file_type, &bytes))
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_sample_tx_hex;
+
+    #[test]
+    fn should_get_hex_output_from_transaction_bbqr_bytes() {
+        let bytes = hex::decode(get_sample_tx_hex()).unwrap();
+        let result = get_output_from_bbqr_bytes(BbqrFileType::Transaction, &bytes).unwrap();
+        assert_eq!(result, get_sample_tx_hex());
+    }
+
+    #[test]
+    fn should_get_text_output_from_json_bbqr_bytes() {
+        let result = get_output_from_bbqr_bytes(BbqrFileType::Json, b"{\"version\":3}").unwrap();
+        assert_eq!(result, "{\"version\":3}");
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{
+    types::Result,
+    commands::DecodeTxArgs,
+    save_output::maybe_save_output,
+    decode_btc_tx::get_decoded_tx_json_string_from_state,
+    get_btc_tx::get_btc_tx_and_add_to_state,
+    utxo_codec::maybe_get_utxos_from_utxo_json_string_and_add_to_state,
+    get_utxo_json_string::maybe_get_utxo_json_string_and_add_to_state,
+};
+
+pub fn decode_transaction(args: &DecodeTxArgs) -> Result<String> {
+    info!("✔ Decoding transaction...");
+    args.to_state()
+        .and_then(|state| get_btc_tx_and_add_to_state(state, &args.tx_hex, &args.tx_input.tx_file))
+        .and_then(|state| maybe_get_utxo_json_string_and_add_to_state(state, &args.utxo_file))
+        .and_then(maybe_get_utxos_from_utxo_json_string_and_add_to_state)
+        .and_then(get_decoded_tx_json_string_from_state)
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
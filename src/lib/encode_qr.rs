@@ -0,0 +1,137 @@
+use std::{
+    path::Path,
+    io::Write,
+    thread::sleep,
+    time::Duration,
+};
+use serde_json::Value as JsonValue;
+use bitcoin::{
+    consensus::encode::deserialize as btc_deserialize,
+    blockdata::transaction::Transaction as BtcTransaction,
+};
+use crate::{
+    errors::AppError,
+    commands::EncodeQrArgs,
+    utils::read_from_path_or_stdin,
+    save_output::maybe_save_output,
+    types::{
+        Bytes,
+        Result,
+    },
+    bbqr::{
+        BbqrFileType,
+        encode_bbqr,
+    },
+    qr_code::{
+        save_qr_code_as_png,
+        render_qr_code_for_terminal,
+    },
+};
+
+pub const PSBT_MAGIC_BYTES: [u8; 5] = [0x70, 0x73, 0x62, 0x74, 0xff];
+pub const QR_ANIMATION_FRAME_MILLIS: u64 = 500;
+
+fn get_bbqr_file_type_and_bytes(input: &str) -> Result<(BbqrFileType, Bytes)> {
+    let trimmed = input.trim();
+    if trimmed.is_empty() {
+        return Err(AppError::Custom("✘ Nothing to encode as a QR code!".to_string()))
+    };
+    if (trimmed.starts_with('{') || trimmed.starts_with('[')) && serde_json::from_str::<JsonValue>(trimmed).is_ok() {
+        return Ok((BbqrFileType::Json, trimmed.as_bytes().to_vec()))
+    };
+    if let Ok(bytes) = hex::decode(trimmed.trim_start_matches("0x")) {
+        if bytes.starts_with(&PSBT_MAGIC_BYTES) {
+            return Ok((BbqrFileType::Psbt, bytes))
+        };
+        if btc_deserialize::<BtcTransaction>(&bytes).is_ok() {
+            return Ok((BbqrFileType::Transaction, bytes))
+        };
+    };
+    Ok((BbqrFileType::Unicode, trimmed.as_bytes().to_vec()))
+}
+
+fn save_qr_codes_as_pngs(parts: &[String], dir: &str) -> Result<String> {
+    std::fs::create_dir_all(dir)?;
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let path = Path::new(dir).join(format!("bitcoff-qr-{:02}-of-{:02}.png", i + 1, parts.len()));
+            save_qr_code_as_png(part, &path).map(|_| path.display().to_string())
+        })
+        .collect::<Result<Vec<String>>>()
+        .map(|paths| paths.join("\n"))
+}
+
+fn render_qr_codes_for_terminal(parts: &[String]) -> Result<Vec<String>> {
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| Ok(format!("❍ Part {} of {} ❍\n{}", i + 1, parts.len(), render_qr_code_for_terminal(part)?)))
+        .collect()
+}
+
+// NOTE: Loops until interrupted so a scanner can pick up the parts in whatever order it sees them.
+fn animate_qr_codes_in_terminal(frames: &[String]) -> Result<String> {
+    let mut stdout = std::io::stdout();
+    loop {
+        for frame in frames {
+            write!(stdout, "\x1B[2J\x1B[H{}", frame)?;
+            stdout.flush()?;
+            sleep(Duration::from_millis(QR_ANIMATION_FRAME_MILLIS));
+        }
+    }
+}
+
+pub fn encode_qr(args: &EncodeQrArgs) -> Result<String> {
+    info!("✔ Encoding QR code(s)...");
+    let (file_type, bytes) = get_bbqr_file_type_and_bytes(&read_from_path_or_stdin(&args.in_file)?)?;
+    info!("✔ Encoding input as BBQr file type: {:?}", file_type);
+    let parts = encode_bbqr(&bytes, file_type, args.part_size)?;
+    match (&args.qr_dir, args.animate) {
+        (Some(dir), _) => save_qr_codes_as_pngs(&parts, dir),
+        (None, true) => animate_qr_codes_in_terminal(&render_qr_codes_for_terminal(&parts)?),
+        (None, false) => render_qr_codes_for_terminal(&parts).map(|frames| frames.join("\n")),
+    }
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        get_sample_tx_hex,
+        SAMPLE_UTXO_JSON_STRING,
+    };
+
+    #[test]
+    fn should_detect_transaction_for_bbqr() {
+        let (file_type, bytes) = get_bbqr_file_type_and_bytes(get_sample_tx_hex()).unwrap();
+        assert_eq!(file_type, BbqrFileType::Transaction);
+        assert_eq!(hex::encode(bytes), get_sample_tx_hex());
+    }
+
+    #[test]
+    fn should_detect_json_for_bbqr() {
+        let (file_type, _) = get_bbqr_file_type_and_bytes(SAMPLE_UTXO_JSON_STRING).unwrap();
+        assert_eq!(file_type, BbqrFileType::Json);
+    }
+
+    #[test]
+    fn should_detect_psbt_for_bbqr() {
+        let (file_type, _) = get_bbqr_file_type_and_bytes("70736274ff01000a").unwrap();
+        assert_eq!(file_type, BbqrFileType::Psbt);
+    }
+
+    #[test]
+    fn should_fall_back_to_unicode_for_bbqr() {
+        let (file_type, bytes) = get_bbqr_file_type_and_bytes("hello air gap\n").unwrap();
+        assert_eq!(file_type, BbqrFileType::Unicode);
+        assert_eq!(bytes, b"hello air gap".to_vec());
+    }
+
+    #[test]
+    fn should_err_if_nothing_to_encode_for_bbqr() {
+        assert!(get_bbqr_file_type_and_bytes("  \n").is_err());
+    }
+}
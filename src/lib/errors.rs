@@ -1,4 +1,8 @@
 use std::fmt;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
 
 #[derive(Debug)]
 pub enum AppError {
@@ -6,12 +10,66 @@ pub enum AppError {
     IOError(std::io::Error),
     HexError(hex::FromHexError),
     SecpError(secp256k1::Error),
-    NoneError(std::option::NoneError),
+    NoneError(String),
     SerdeJsonError(serde_json::Error),
     FromUtf8Error(std::str::Utf8Error),
     SystemTimeError(std::time::SystemTimeError),
     BitcoinError(bitcoin::consensus::encode::Error),
     BitcoinAddressError(bitcoin::util::address::Error),
+    /// A tx failed verification, with the report of which checks failed.
+    TxVerificationError(JsonValue),
+}
+
+impl AppError {
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            AppError::Custom(_) => "Custom",
+            AppError::IOError(_) => "IOError",
+            AppError::HexError(_) => "HexError",
+            AppError::SecpError(_) => "SecpError",
+            AppError::NoneError(_) => "NoneError",
+            AppError::BitcoinError(_) => "BitcoinError",
+            AppError::FromUtf8Error(_) => "FromUtf8Error",
+            AppError::SerdeJsonError(_) => "SerdeJsonError",
+            AppError::SystemTimeError(_) => "SystemTimeError",
+            AppError::BitcoinAddressError(_) => "BitcoinAddressError",
+            AppError::TxVerificationError(_) => "TxVerificationError",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            AppError::Custom(_) => 1,
+            AppError::IOError(_) => 2,
+            AppError::HexError(_) => 3,
+            AppError::SecpError(_) => 4,
+            AppError::NoneError(_) => 5,
+            AppError::BitcoinError(_) => 6,
+            AppError::FromUtf8Error(_) => 7,
+            AppError::SerdeJsonError(_) => 8,
+            AppError::SystemTimeError(_) => 9,
+            AppError::BitcoinAddressError(_) => 10,
+            AppError::TxVerificationError(_) => 11,
+        }
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        match *self {
+            AppError::TxVerificationError(ref report) => json!({
+                "error": {
+                    "kind": self.kind(),
+                    "report": report,
+                    "message": "✘ Tx failed verification!",
+                }
+            }),
+            _ => json!({
+                "error": {
+                    "kind": self.kind(),
+                    "message": self.to_string(),
+                }
+            }),
+        }
+    }
 }
 
 impl fmt::Display for AppError {
@@ -22,11 +80,12 @@ impl fmt::Display for AppError {
             AppError::HexError(ref e) => format!("✘ Hex error! {}", e),
             AppError::BitcoinError(ref e) => format!("✘ Bitcoin error: {}", e),
             AppError::SecpError(ref e) => format!("✘ Secp256k1 error: {:?}", e),
-            AppError::NoneError(ref e) => format!("✘ Nothing to unwrap: {:?}", e),
+            AppError::NoneError(ref what) => format!("✘ Nothing to unwrap: {}!", what),
             AppError::FromUtf8Error(ref e) => format!("✘ From utf8 error: {:?}", e),
             AppError::SerdeJsonError(ref e) => format!("✘ Serde-Json error: {}", e),
             AppError::SystemTimeError(ref e) => format!("✘ System time error: {}", e),
             AppError::BitcoinAddressError(ref e) => format!("✘ Bitcoin address error: {}", e),
+            AppError::TxVerificationError(ref report) => format!("✘ Tx failed verification: {}", report),
         };
         f.write_fmt(format_args!("{}", msg))
     }
@@ -80,8 +139,30 @@ impl From<std::time::SystemTimeError> for AppError {
     }
 }
 
-impl From<std::option::NoneError> for AppError {
-    fn from(e: std::option::NoneError) -> AppError {
-        AppError::NoneError(e)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_app_error_to_json() {
+        let message = "✘ Some error!";
+        let result = AppError::Custom(message.to_string()).to_json();
+        assert_eq!(result["error"]["kind"], "Custom");
+        assert_eq!(result["error"]["message"], message);
+    }
+
+    #[test]
+    fn should_convert_tx_verification_error_to_json_with_report() {
+        let report = json!({ "valid": false, "errors": ["✘ Some error!"] });
+        let result = AppError::TxVerificationError(report.clone()).to_json();
+        assert_eq!(result["error"]["kind"], "TxVerificationError");
+        assert_eq!(result["error"]["report"], report);
+    }
+
+    #[test]
+    fn should_get_distinct_exit_codes_per_error_kind() {
+        let custom_error = AppError::Custom("✘ Some error!".to_string());
+        let hex_error = AppError::HexError(hex::FromHexError::OddLength);
+        assert_ne!(custom_error.exit_code(), hex_error.exit_code());
     }
 }
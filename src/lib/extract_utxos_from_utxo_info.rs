@@ -3,8 +3,13 @@ use bitcoin::blockdata::transaction::{
     OutPoint as BtcOutPoint,
     Transaction as BtcTransaction,
 };
-use crate::lib::{
+use crate::{
     state::State,
+    backend::BtcBackend,
+    utxo_filters::UtxoFilters,
+    coin_control::maybe_apply_coin_control,
+    get_utxos_info::get_filtered_utxos_info,
+    get_btc_txs_from_utxos_info::get_btc_txs_for_utxos_info,
     constants::{
         ONE_BTC,
         DEFAULT_BTC_SEQUENCE,
@@ -48,9 +53,21 @@ fn create_unsigned_utxos_from_tx_outputs(tx: &[BtcTransaction], utxos_info: &[Ut
         )
 }
 
+/// Gets the UTXOs paying to `address` that pass the `filters`, ready to be spent by a transaction.
+pub fn get_btc_utxos_and_values_for_address(
+    backend: &dyn BtcBackend,
+    address: &str,
+    filters: &UtxoFilters,
+) -> Result<BtcUtxosAndValues> {
+    let utxos_info = get_filtered_utxos_info(backend, address, filters)?;
+    get_btc_txs_for_utxos_info(&utxos_info, backend)
+        .and_then(|txs| create_unsigned_utxos_from_tx_outputs(&txs, &utxos_info))
+}
+
 pub fn extract_utxos_and_add_to_state(state: State) -> Result<State> {
     info!("✔ Maybe extracting UTXOs and adding to state...");
     create_unsigned_utxos_from_tx_outputs(state.get_btc_txs()?, state.get_utxos_info()?)
+        .and_then(|utxos| maybe_apply_coin_control(utxos, &state))
         .and_then(|utxos| {
             info!(
                 "✔ Total value of the {} UTXO(s): {} BTC",
@@ -65,7 +82,7 @@ pub fn extract_utxos_and_add_to_state(state: State) -> Result<State> {
 mod tests {
     use super::*;
     use bitcoin::consensus::encode::serialize as btc_serialize;
-    use crate::lib::test_utils::{
+    use crate::test_utils::{
         get_sample_tx,
         SAMPLE_UTXO_INDEX,
         SAMPLE_TESTNET_TX_ID,
@@ -0,0 +1,207 @@
+use std::collections::HashMap;
+use crate::{
+    state::State,
+    errors::AppError,
+    backend::BtcBackend,
+    types::Result,
+    btc_amount::parse_fee_rate,
+    constants::{
+        MAX_SANE_FEE_RATE,
+        MIN_RELAY_FEE_RATE,
+    },
+};
+
+pub const AUTO_FEE_RATE: &str = "auto";
+
+pub type FeeEstimates = HashMap<u64, f64>;
+
+pub fn is_auto_fee_rate(fee_rate: &str) -> bool {
+    fee_rate.trim().to_lowercase() == AUTO_FEE_RATE
+}
+
+/// Parses a `--fee` arg, which is `auto` to estimate a rate later, else a rate between the minimum relay fee rate &
+/// the sanity limit.
+pub fn maybe_parse_fee_rate(fee_rate: &str) -> Result<Option<f64>> {
+    match is_auto_fee_rate(fee_rate) {
+        true => Ok(None),
+        false => parse_fee_rate(fee_rate).and_then(check_fee_rate_is_sane).and_then(check_fee_rate_relays).map(Some),
+    }
+}
+
+pub fn parse_fee_estimates_json_string(fee_estimates_json_string: &str) -> Result<FeeEstimates> {
+    info!("✔ Parsing fee estimates JSON string...");
+    serde_json::from_str::<HashMap<String, f64>>(fee_estimates_json_string)?
+        .into_iter()
+        .map(|(conf_target, sats_per_byte)|
+            match conf_target.parse::<u64>() {
+                Ok(conf_target) => Ok((conf_target, sats_per_byte)),
+                Err(_) => Err(AppError::Custom(format!("✘ Invalid confirmation target in fee estimates: '{}'", conf_target))),
+            }
+        )
+        .collect()
+}
+
+pub fn choose_fee_rate_from_estimates(fee_estimates: &FeeEstimates, conf_target: u64) -> Result<f64> {
+    info!("✔ Choosing fee rate for confirmation within {} block(s)...", conf_target);
+    // NOTE: Use the estimate for the slowest target that still confirms in time, else the fastest one on offer.
+    let maybe_estimate = fee_estimates
+        .iter()
+        .filter(|(target, _)| **target <= conf_target)
+        .max_by_key(|(target, _)| **target)
+        .or_else(|| fee_estimates.iter().min_by_key(|(target, _)| **target));
+    match maybe_estimate {
+        None => Err(AppError::Custom("✘ No fee estimates available!".to_string())),
+        Some((target, sats_per_byte)) => {
+            info!("✔ Estimated fee rate for {} block(s): {} sats-per-byte", target, sats_per_byte);
+            apply_fee_rate_limits(*sats_per_byte)
+        }
+    }
+}
+
+fn check_fee_rate_is_sane(sats_per_byte: f64) -> Result<f64> {
+    match !sats_per_byte.is_finite() || sats_per_byte > MAX_SANE_FEE_RATE {
+        true => Err(AppError::Custom(
+            format!("✘ Fee rate of {} sats-per-byte exceeds the sanity limit of {}!", sats_per_byte, MAX_SANE_FEE_RATE)
+        )),
+        false => Ok(sats_per_byte),
+    }
+}
+
+// NOTE: An explicit rate is the user's choice ∴ it's rejected rather than quietly raised like an estimate is.
+fn check_fee_rate_relays(sats_per_byte: f64) -> Result<f64> {
+    match sats_per_byte < MIN_RELAY_FEE_RATE {
+        true => Err(AppError::Custom(format!(
+            "✘ Fee rate of {} sats-per-byte is below the minimum relay fee rate of {} ∴ the tx wouldn't relay!",
+            sats_per_byte,
+            MIN_RELAY_FEE_RATE,
+        ))),
+        false => Ok(sats_per_byte),
+    }
+}
+
+pub fn apply_fee_rate_limits(sats_per_byte: f64) -> Result<f64> {
+    let sats_per_byte = check_fee_rate_is_sane(sats_per_byte)?;
+    match sats_per_byte < MIN_RELAY_FEE_RATE {
+        true => {
+            info!("✔ Raising fee rate to the minimum relay fee rate of {} sats-per-byte", MIN_RELAY_FEE_RATE);
+            Ok(MIN_RELAY_FEE_RATE)
+        }
+        false => Ok(sats_per_byte),
+    }
+}
+
+pub fn maybe_get_fee_rate_estimate_and_add_to_state(state: State) -> Result<State> {
+    match state.sats_per_byte {
+        Some(_) => {
+            info!("✔ Fee rate supplied via CLI args ∴ not estimating it!");
+            Ok(state)
+        }
+        None => {
+            info!("✔ Getting fee rate estimate and adding to state...");
+            state
+                .backend
+                .get_fee_estimates()
+                .and_then(|estimates| choose_fee_rate_from_estimates(&estimates, state.conf_target))
+                .and_then(|sats_per_byte| state.add_sats_per_byte(sats_per_byte))
+        }
+    }
+}
+
+pub fn check_fee_rate_is_available_offline(state: State) -> Result<State> {
+    match state.sats_per_byte {
+        Some(_) => Ok(state),
+        None => Err(AppError::Custom(
+            "✘ Cannot estimate a fee rate offline! Please supply one via `--fee=<rate>`.".to_string()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backend::EsploraBackend,
+        test_utils::SAMPLE_TESTNET_ENDPOINT,
+    };
+
+    fn get_sample_fee_estimates() -> FeeEstimates {
+        parse_fee_estimates_json_string("{\"1\":40.5,\"2\":31.2,\"6\":12.75,\"25\":5.1,\"144\":0.8}").unwrap()
+    }
+
+    #[test]
+    fn should_detect_auto_fee_rate() {
+        assert!(is_auto_fee_rate("auto"));
+        assert!(is_auto_fee_rate(" AUTO "));
+        assert!(!is_auto_fee_rate("23"));
+    }
+
+    #[test]
+    fn should_maybe_parse_fee_rate() {
+        assert_eq!(maybe_parse_fee_rate("auto").unwrap(), None);
+        assert_eq!(maybe_parse_fee_rate("12.5").unwrap(), Some(12.5));
+        assert!(maybe_parse_fee_rate("fast").is_err());
+    }
+
+    #[test]
+    fn should_err_if_explicit_fee_rate_exceeds_sanity_ceiling() {
+        assert_eq!(maybe_parse_fee_rate(&MAX_SANE_FEE_RATE.to_string()).unwrap(), Some(MAX_SANE_FEE_RATE));
+        assert!(maybe_parse_fee_rate(&(MAX_SANE_FEE_RATE + 1.0).to_string()).is_err());
+    }
+
+    #[test]
+    fn should_err_if_explicit_fee_rate_is_below_min_relay_fee_rate() {
+        assert!(maybe_parse_fee_rate("0").is_err());
+        assert!(maybe_parse_fee_rate("0.5").is_err());
+        assert_eq!(maybe_parse_fee_rate(&MIN_RELAY_FEE_RATE.to_string()).unwrap(), Some(MIN_RELAY_FEE_RATE));
+    }
+
+    #[test]
+    fn should_parse_fee_estimates_json_string() {
+        let result = get_sample_fee_estimates();
+        assert_eq!(result.len(), 5);
+        assert_eq!(result[&6], 12.75);
+    }
+
+    #[test]
+    fn should_err_parsing_invalid_fee_estimates_json_string() {
+        assert!(parse_fee_estimates_json_string("{\"soon\":1.0}").is_err());
+        assert!(parse_fee_estimates_json_string("[]").is_err());
+    }
+
+    #[test]
+    fn should_choose_fee_rate_for_exact_conf_target() {
+        assert_eq!(choose_fee_rate_from_estimates(&get_sample_fee_estimates(), 6).unwrap(), 12.75);
+    }
+
+    #[test]
+    fn should_choose_fee_rate_for_nearest_faster_conf_target() {
+        assert_eq!(choose_fee_rate_from_estimates(&get_sample_fee_estimates(), 10).unwrap(), 12.75);
+    }
+
+    #[test]
+    fn should_choose_fastest_fee_rate_if_conf_target_below_all_estimates() {
+        assert_eq!(choose_fee_rate_from_estimates(&get_sample_fee_estimates(), 0).unwrap(), 40.5);
+    }
+
+    #[test]
+    fn should_apply_min_relay_fee_rate_floor() {
+        assert_eq!(choose_fee_rate_from_estimates(&get_sample_fee_estimates(), 1008).unwrap(), MIN_RELAY_FEE_RATE);
+    }
+
+    #[test]
+    fn should_err_if_fee_rate_exceeds_sanity_ceiling() {
+        assert!(apply_fee_rate_limits(MAX_SANE_FEE_RATE + 1.0).is_err());
+        assert!(apply_fee_rate_limits(f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn should_err_if_no_fee_estimates() {
+        assert!(choose_fee_rate_from_estimates(&FeeEstimates::new(), 6).is_err());
+    }
+
+    #[test]
+    fn should_get_fee_estimates() {
+        let result = EsploraBackend::new(SAMPLE_TESTNET_ENDPOINT).get_fee_estimates();
+        assert!(!result.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{
+    types::Result,
+    commands::GetBalanceArgs,
+    save_output::maybe_save_output,
+    address_info::get_balance_output_from_state,
+    get_btc_private_key::maybe_get_btc_private_key_for_address_and_add_to_state,
+};
+
+pub fn get_balance(args: &GetBalanceArgs) -> Result<String> {
+    info!("✔ Getting balance...");
+    args.to_state()
+        .and_then(maybe_get_btc_private_key_for_address_and_add_to_state)
+        .and_then(get_balance_output_from_state)
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
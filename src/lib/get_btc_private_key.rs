@@ -1,5 +1,5 @@
 use std::process::Command;
-use crate::lib::{
+use crate::{
     state::State,
     types::Result,
     errors::AppError,
@@ -12,7 +12,7 @@ use crate::lib::{
 
 fn check_keyfile_exists(keyfile_path: &str) -> Result<()> {
     info!("✔ Checking BTC private keyfile exists...");
-    match file_exists(&keyfile_path) {
+    match file_exists(keyfile_path) {
         false =>
             Err(AppError::Custom(
                 format!(
@@ -52,9 +52,18 @@ fn get_btc_private_key_from_wif(btc_pk_wif: String) -> Result<BtcPrivateKey> {
 
 pub fn get_btc_private_key_and_add_to_state(state: State) -> Result<State> {
     info!("✔ Maybe getting BTC private key & adding to state...");
-    let keyfile_path = &state.cli_args.flag_keyfile;
-    check_keyfile_exists(keyfile_path)
-        .and_then(|_| maybe_get_btc_private_key_wif_string(keyfile_path))
+    check_keyfile_exists(&state.keyfile)
+        .and_then(|_| maybe_get_btc_private_key_wif_string(&state.keyfile))
         .and_then(get_btc_private_key_from_wif)
         .and_then(|btc_private_key| state.add_btc_private_key(btc_private_key))
 }
+
+pub fn maybe_get_btc_private_key_for_address_and_add_to_state(state: State) -> Result<State> {
+    match state.btc_address {
+        None => get_btc_private_key_and_add_to_state(state),
+        Some(_) => {
+            info!("✔ BTC address supplied via CLI args ∴ not getting BTC private key!");
+            Ok(state)
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{
+    state::State,
+    errors::AppError,
+    types::Result,
+    utils::read_from_path_or_stdin,
+    get_btc_txs_from_utxos_info::convert_hex_tx_to_btc_tx,
+};
+
+fn clean_tx_hex(tx_hex: &str) -> Result<String> {
+    let trimmed = tx_hex.trim();
+    let cleaned = match trimmed.starts_with("0x") {
+        true => &trimmed[2..],
+        false => trimmed,
+    };
+    match cleaned.is_empty() {
+        true => Err(AppError::Custom("✘ No transaction hex supplied!".to_string())),
+        false => Ok(cleaned.to_string()),
+    }
+}
+
+/// The tx hex passed as an arg, else read from the `maybe_tx_file`, else from stdin.
+pub fn get_tx_hex(maybe_tx_hex: &Option<String>, maybe_tx_file: &Option<String>) -> Result<String> {
+    info!("✔ Getting tx hex from CLI args...");
+    match maybe_tx_hex {
+        Some(tx_hex) => {
+            info!("✔ Tx hex passed in as argument...");
+            Ok(tx_hex.clone())
+        },
+        None => read_from_path_or_stdin(maybe_tx_file),
+    }
+        .and_then(|tx_hex| clean_tx_hex(&tx_hex))
+}
+
+pub fn get_btc_tx_and_add_to_state(
+    state: State,
+    maybe_tx_hex: &Option<String>,
+    maybe_tx_file: &Option<String>,
+) -> Result<State> {
+    info!("✔ Getting BTC tx from CLI args and adding to state...");
+    get_tx_hex(maybe_tx_hex, maybe_tx_file)
+        .and_then(|tx_hex| convert_hex_tx_to_btc_tx(&tx_hex))
+        .and_then(|btc_tx| state.add_btc_tx(btc_tx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::get_sample_tx_hex;
+
+    #[test]
+    fn should_clean_tx_hex() {
+        let dirty_hex = format!("  0x{}\n", get_sample_tx_hex());
+        let result = clean_tx_hex(&dirty_hex).unwrap();
+        assert_eq!(result, get_sample_tx_hex());
+    }
+
+    #[test]
+    fn should_err_when_cleaning_empty_tx_hex() {
+        assert!(clean_tx_hex(" \n").is_err());
+    }
+}
@@ -2,9 +2,9 @@ use bitcoin::{
     consensus::encode::deserialize as btc_deserialize,
     blockdata::transaction::Transaction as BtcTransaction,
 };
-use crate::lib::{
+use crate::{
     state::State,
-    utils::make_api_call,
+    backend::BtcBackend,
     types::{
         Result,
         UtxoInfo,
@@ -21,47 +21,42 @@ fn convert_hex_txs_to_btc_txs(hex_txs: Vec<String>) -> Result<BtcTransactions> {
     info!("✔ Converting hex txs to BTC txs...");
     hex_txs
         .iter()
-        .map(|hex| convert_hex_tx_to_btc_tx(&hex))
+        .map(|hex| convert_hex_tx_to_btc_tx(hex))
         .collect()
 }
 
-fn get_hex_tx_from_tx_id(tx_id: &str, api_endpoint: &str) -> Result<String> {
-    info!("✔ Getting BTC tx in hex format for tx id: {}", tx_id);
-    make_api_call(&format!("{}tx/{}/hex", api_endpoint, tx_id)[..], &"✘ Error getting BTC tx in hex: {:?}")
+fn get_hex_txs_from_utxos_info(utxos_info: &[UtxoInfo], backend: &dyn BtcBackend) -> Result<Vec<String>> {
+    info!("✔ Getting BTC txs in hex format...");
+    utxos_info.iter().map(|utxo_info| backend.get_tx_hex(&utxo_info.txid)).collect()
 }
 
-fn get_hex_txs_from_utxos_info(utxos_info: &[UtxoInfo], api_endpoint: &str) -> Result<Vec<String>> {
-    info!("✔ Getting BTC txs in hex format...");
-    utxos_info.iter().map(|utxo_info| get_hex_tx_from_tx_id(&utxo_info.txid, api_endpoint)).collect()
+pub fn get_btc_txs_for_utxos_info(utxos_info: &[UtxoInfo], backend: &dyn BtcBackend) -> Result<BtcTransactions> {
+    get_hex_txs_from_utxos_info(utxos_info, backend).and_then(convert_hex_txs_to_btc_txs)
 }
 
 pub fn get_txs_from_utxo_infos_and_put_in_state(
     state: State
 ) -> Result<State> {
     info!("✔ Maybe getting BTC txs...");
-    get_hex_txs_from_utxos_info(
-        state.get_utxos_info()?,
-        &state.api_endpoint,
-    )
-        .and_then(convert_hex_txs_to_btc_txs)
+    get_btc_txs_for_utxos_info(state.get_utxos_info()?, &state.backend)
         .and_then(|btc_tx| state.add_btc_txs(btc_tx))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::lib::test_utils::{
-        get_sample_tx_hex,
-        SAMPLE_TESTNET_TX_ID,
-        SAMPLE_TESTNET_ENDPOINT,
+    use crate::{
+        backend::EsploraBackend,
+        test_utils::{
+            get_sample_tx_hex,
+            SAMPLE_TESTNET_TX_ID,
+            SAMPLE_TESTNET_ENDPOINT,
+        },
     };
 
     #[test]
     fn should_get_tx_in_hex_format() {
-        match get_hex_tx_from_tx_id(
-            &SAMPLE_TESTNET_TX_ID.to_string(),
-            &SAMPLE_TESTNET_ENDPOINT.to_string(),
-        ) {
+        match EsploraBackend::new(SAMPLE_TESTNET_ENDPOINT).get_tx_hex(SAMPLE_TESTNET_TX_ID) {
             Err(e) => panic!("Error getting tx id: {}", e),
             Ok(result) => assert!(result == get_sample_tx_hex()),
         }
@@ -70,7 +65,7 @@ mod tests {
     #[test]
     fn should_convert_hex_to_btc_tx() {
         if let Err(e) = convert_hex_tx_to_btc_tx(
-            &get_sample_tx_hex().to_string()
+            get_sample_tx_hex()
         ) {
             panic!("Error converting hex to btc tx: {}", e);
         }
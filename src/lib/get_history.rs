@@ -0,0 +1,15 @@
+use crate::{
+    types::Result,
+    commands::GetHistoryArgs,
+    save_output::maybe_save_output,
+    address_info::get_history_output_from_state,
+    get_btc_private_key::maybe_get_btc_private_key_for_address_and_add_to_state,
+};
+
+pub fn get_history(args: &GetHistoryArgs) -> Result<String> {
+    info!("✔ Getting history...");
+    args.to_state()
+        .and_then(maybe_get_btc_private_key_for_address_and_add_to_state)
+        .and_then(|state| get_history_output_from_state(state, args.limit, &args.after))
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
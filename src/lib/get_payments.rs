@@ -0,0 +1,285 @@
+use serde_json::Value as JsonValue;
+use bitcoin::network::constants::Network as BtcNetwork;
+use crate::{
+    errors::AppError,
+    verify_btc_tx::get_dust_threshold,
+    utils::{
+        create_new_tx_output,
+        read_from_path_or_stdin,
+    },
+    btc_amount::{
+        is_sweep_amount,
+        parse_btc_amount,
+        parse_btc_amounts,
+    },
+    types::{
+        Result,
+        BtcAddressAndAmount,
+        BtcAddressesAndAmounts,
+    },
+};
+
+type RowResult = (usize, Result<BtcAddressAndAmount>);
+
+#[derive(Clone, Debug, Deserialize)]
+struct PaymentJson {
+    address: String,
+    amount: JsonValue,
+    label: Option<String>,
+}
+
+/// Parses a payment's amount, which unlike a <TO AMOUNT> pair's can't be `max` since a batch can't sweep.
+fn parse_payment_amount(amount: &str) -> Result<u64> {
+    match is_sweep_amount(amount) {
+        true => Err(AppError::Custom("✘ Cannot use a `max` amount in a payments file!".to_string())),
+        false => parse_btc_amount(amount),
+    }
+}
+
+/// Errs if the payment is for zero sats, or for so few its output would be dust. `OP_RETURN` outputs can't be dust,
+/// & usually carry no value.
+fn check_payment_amount(payment: BtcAddressAndAmount) -> Result<BtcAddressAndAmount> {
+    let output = create_new_tx_output(&payment)?;
+    if output.script_pubkey.is_op_return() {
+        return Ok(payment)
+    };
+    if payment.amount == 0 {
+        return Err(AppError::Custom("✘ Cannot pay an amount of zero!".to_string()))
+    };
+    match output.value < get_dust_threshold(&output) {
+        true => Err(AppError::Custom(format!(
+            "✘ Amount of {} sats is below the dust threshold of {} sats!",
+            output.value,
+            get_dust_threshold(&output),
+        ))),
+        false => Ok(payment),
+    }
+}
+
+fn parse_json_amount(amount: &JsonValue) -> Result<u64> {
+    match amount {
+        JsonValue::String(amount) => parse_payment_amount(amount),
+        JsonValue::Number(amount) => amount
+            .as_u64()
+            .ok_or_else(|| AppError::Custom(format!("✘ Not a valid amount: '{}'", amount))),
+        _ => Err(AppError::Custom(format!("✘ Not a valid amount: '{}'", amount))),
+    }
+}
+
+fn parse_json_payment(json: &JsonValue) -> Result<BtcAddressAndAmount> {
+    let payment: PaymentJson = serde_json::from_value(json.clone())?;
+    BtcAddressAndAmount::new(&payment.address, parse_json_amount(&payment.amount)?)
+        .and_then(check_payment_amount)
+        .map(|recipient| recipient.with_label(payment.label))
+}
+
+fn parse_payments_json(contents: &str) -> Result<Vec<RowResult>> {
+    info!("✔ Parsing payments JSON...");
+    let rows: Vec<JsonValue> = serde_json::from_str(contents)?;
+    Ok(rows.iter().enumerate().map(|(i, json)| (i + 1, parse_json_payment(json))).collect())
+}
+
+fn parse_csv_payment(line: &str) -> Result<BtcAddressAndAmount> {
+    let fields = line.splitn(3, ',').map(|field| field.trim()).collect::<Vec<&str>>();
+    match fields.len() {
+        0 | 1 => Err(AppError::Custom("✘ Expected at least an address & an amount!".to_string())),
+        _ => BtcAddressAndAmount::new(fields[0], parse_payment_amount(fields[1])?)
+            .and_then(check_payment_amount)
+            .map(|recipient| recipient.with_label(
+                fields.get(2).filter(|label| !label.is_empty()).map(|label| label.to_string())
+            )),
+    }
+}
+
+fn is_csv_header(line: &str) -> bool {
+    line.to_lowercase().starts_with("address")
+}
+
+fn parse_payments_csv(contents: &str) -> Vec<RowResult> {
+    info!("✔ Parsing payments CSV...");
+    contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(i, line)| !(line.is_empty() || line.starts_with('#') || (*i == 1 && is_csv_header(line))))
+        .map(|(i, line)| (i, parse_csv_payment(line)))
+        .collect()
+}
+
+fn collect_row_results(row_results: Vec<RowResult>) -> Result<Vec<BtcAddressAndAmount>> {
+    let errors = row_results
+        .iter()
+        .filter_map(|(row, result)| result.as_ref().err().map(|e| format!("✘ Row {}: {}", row, e)))
+        .collect::<Vec<String>>();
+    match errors.is_empty() {
+        false => Err(AppError::Custom(errors.join("\n"))),
+        true => Ok(row_results.into_iter().filter_map(|(_, result)| result.ok()).collect()),
+    }
+}
+
+fn merge_duplicate_addresses(payments: Vec<BtcAddressAndAmount>) -> Result<BtcAddressesAndAmounts> {
+    let mut merged_payments: Vec<BtcAddressAndAmount> = vec![];
+    for payment in payments {
+        match merged_payments.iter_mut().find(|merged| merged.address == payment.address) {
+            None => merged_payments.push(payment),
+            Some(merged) => {
+                info!("✔ Merging duplicate payment to address: {}", payment.address);
+                merged.amount = merged
+                    .amount
+                    .checked_add(payment.amount)
+                    .ok_or_else(|| AppError::Custom(format!("✘ Payments to {} overflow!", payment.address)))?;
+                if merged.label.is_none() {
+                    merged.label = payment.label;
+                };
+            },
+        }
+    }
+    Ok(BtcAddressesAndAmounts(merged_payments))
+}
+
+pub fn parse_payments(contents: &str) -> Result<BtcAddressesAndAmounts> {
+    let row_results = match contents.trim_start().starts_with('[') {
+        true => parse_payments_json(contents)?,
+        false => parse_payments_csv(contents),
+    };
+    collect_row_results(row_results).and_then(merge_duplicate_addresses)
+}
+
+/// The recipients passed as <TO AMOUNT> pairs, whose amounts are ignored when sweeping, else those in the
+/// `maybe_payments_path` file.
+pub fn get_addresses_and_amounts(
+    to: &[String],
+    amounts: &[String],
+    maybe_payments_path: &Option<String>,
+    sweep: bool,
+) -> Result<BtcAddressesAndAmounts> {
+    match maybe_payments_path {
+        None if sweep => BtcAddressesAndAmounts::new(to, &parse_btc_amounts(amounts)?),
+        None => BtcAddressesAndAmounts::new(to, &parse_btc_amounts(amounts)?)?
+            .0
+            .into_iter()
+            .map(check_payment_amount)
+            .collect::<Result<Vec<BtcAddressAndAmount>>>()
+            .map(BtcAddressesAndAmounts),
+        Some(_) => {
+            info!("✔ Reading payments...");
+            let payments = parse_payments(&read_from_path_or_stdin(maybe_payments_path)?)?;
+            info!("✔ {} payment(s) totalling {} sats", payments.len(), payments.sum());
+            Ok(payments)
+        },
+    }
+}
+
+/// Errs naming every payment whose destination is for the wrong network, or is non-standard & not allowed.
+pub fn check_payment_destinations(
+    payments: &BtcAddressesAndAmounts,
+    network: BtcNetwork,
+    allow_non_standard: bool,
+) -> Result<()> {
+    let errors = payments
+        .0
+        .iter()
+        .filter_map(|payment| payment.address.check(payment.amount, network, allow_non_standard).err())
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>();
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(AppError::Custom(errors.join("\n"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_payments_csv() {
+        let csv = "address,amount,label\n\
+            mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM,666,Alice\n\
+            # A comment\n\
+            \n\
+            mwqqpopcLxEFUCPGGPV7zpfm3xrSAjRcyq,0.00001337btc\n";
+        let result = parse_payments(csv).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.0[0].amount, 666);
+        assert_eq!(result.0[0].label, Some("Alice".to_string()));
+        assert_eq!(result.0[1].amount, 1337);
+        assert_eq!(result.0[1].label, None);
+    }
+
+    #[test]
+    fn should_parse_payments_json() {
+        let json = "[
+            { \"address\": \"mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM\", \"amount\": 666, \"label\": \"Alice\" },
+            { \"address\": \"mwqqpopcLxEFUCPGGPV7zpfm3xrSAjRcyq\", \"amount\": \"1.337mbtc\" }
+        ]";
+        let result = parse_payments(json).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.0[0].label, Some("Alice".to_string()));
+        assert_eq!(result.0[1].amount, 133_700);
+    }
+
+    #[test]
+    fn should_merge_duplicate_addresses() {
+        let csv = "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM,666\n\
+            mwqqpopcLxEFUCPGGPV7zpfm3xrSAjRcyq,1337\n\
+            mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM,1000,Alice\n";
+        let result = parse_payments(csv).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.0[0].amount, 1666);
+        assert_eq!(result.0[0].label, Some("Alice".to_string()));
+    }
+
+    #[test]
+    fn should_report_every_invalid_row() {
+        let csv = "not-an-address,666\n\
+            mwqqpopcLxEFUCPGGPV7zpfm3xrSAjRcyq,1337\n\
+            mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM,lots\n";
+        match parse_payments(csv) {
+            Ok(_) => panic!("Should not parse invalid payments!"),
+            Err(AppError::Custom(e)) => {
+                assert!(e.contains("Row 1"));
+                assert!(!e.contains("Row 2"));
+                assert!(e.contains("Row 3"));
+            },
+            Err(e) => panic!("Wrong error when parsing invalid payments: {}", e),
+        }
+    }
+
+    #[test]
+    fn should_reject_max_zero_and_dust_rows() {
+        let csv = "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM,max\n\
+            mwqqpopcLxEFUCPGGPV7zpfm3xrSAjRcyq,0\n\
+            mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM,545\n\
+            mwqqpopcLxEFUCPGGPV7zpfm3xrSAjRcyq,546\n";
+        match parse_payments(csv) {
+            Ok(_) => panic!("Should not parse max, zero or dust payments!"),
+            Err(AppError::Custom(e)) => {
+                assert!(e.contains("Row 1"));
+                assert!(e.contains("Row 2"));
+                assert!(e.contains("Row 3"));
+                assert!(!e.contains("Row 4"));
+            },
+            Err(e) => panic!("Wrong error when parsing invalid payments: {}", e),
+        }
+        assert!(parse_payments("[{ \"address\": \"mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM\", \"amount\": 0 }]").is_err());
+    }
+
+    #[test]
+    fn should_reject_zero_and_dust_pairs_unless_sweeping() {
+        let to = vec!["mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM".to_string()];
+        let get_amounts = |amount: &str| vec![amount.to_string()];
+        assert!(get_addresses_and_amounts(&to, &get_amounts("0"), &None, false).is_err());
+        assert!(get_addresses_and_amounts(&to, &get_amounts("545"), &None, false).is_err());
+        assert_eq!(get_addresses_and_amounts(&to, &get_amounts("546"), &None, false).unwrap().sum(), 546);
+        assert!(get_addresses_and_amounts(&to, &get_amounts("max"), &None, true).is_ok());
+        assert!(get_addresses_and_amounts(&to, &get_amounts("0"), &None, true).is_ok());
+    }
+
+    #[test]
+    fn should_allow_zero_value_op_return_payments() {
+        let to = vec!["script:6a0568656c6c6f".to_string()];
+        assert!(get_addresses_and_amounts(&to, &["0".to_string()], &None, false).is_ok());
+        assert!(parse_payments("script:6a0568656c6c6f,0").is_ok());
+    }
+}
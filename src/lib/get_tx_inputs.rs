@@ -0,0 +1,32 @@
+use crate::{
+    state::State,
+    types::Result,
+    commands::OfflineUtxoArgs,
+    get_utxos_info::get_utxos_info_and_add_to_state,
+    get_btc_private_key::get_btc_private_key_and_add_to_state,
+    utxo_codec::get_utxos_from_utxo_json_string_and_add_to_state,
+    get_utxo_json_string::get_utxo_json_string_and_add_to_state,
+    extract_utxos_from_utxo_info::extract_utxos_and_add_to_state,
+    get_btc_txs_from_utxos_info::get_txs_from_utxo_infos_and_put_in_state,
+    fee_estimates::{
+        check_fee_rate_is_available_offline,
+        maybe_get_fee_rate_estimate_and_add_to_state,
+    },
+};
+
+/// Gets the fee rate, estimating it if need be, the private key, & the UTXOs it can spend from the block explorer.
+pub fn get_online_tx_inputs_and_add_to_state(state: State) -> Result<State> {
+    maybe_get_fee_rate_estimate_and_add_to_state(state)
+        .and_then(get_btc_private_key_and_add_to_state)
+        .and_then(get_utxos_info_and_add_to_state)
+        .and_then(get_txs_from_utxo_infos_and_put_in_state)
+        .and_then(extract_utxos_and_add_to_state)
+}
+
+/// Checks a fee rate was supplied, then gets the private key & the UTXOs it can spend from the CLI args.
+pub fn get_offline_tx_inputs_and_add_to_state(state: State, utxos: &OfflineUtxoArgs) -> Result<State> {
+    check_fee_rate_is_available_offline(state)
+        .and_then(|state| get_utxo_json_string_and_add_to_state(state, &utxos.utxos, &utxos.utxo_file))
+        .and_then(get_btc_private_key_and_add_to_state)
+        .and_then(get_utxos_from_utxo_json_string_and_add_to_state)
+}
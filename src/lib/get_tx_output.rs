@@ -0,0 +1,30 @@
+use serde_json::json;
+use crate::{
+    state::State,
+    types::Result,
+    output_format::OutputFormat,
+    utils::serialize_btc_tx_to_hex,
+    decode_btc_tx::decode_btc_tx,
+};
+
+pub fn get_tx_output_from_state(state: State) -> Result<String> {
+    info!("✔ Getting tx output from state...");
+    let tx = state.get_btc_tx()?;
+    match state.output_format {
+        OutputFormat::Text => Ok(serialize_btc_tx_to_hex(tx)),
+        OutputFormat::Json => {
+            let decoded_tx = decode_btc_tx(tx, state.network, Some(state.get_btc_utxos_and_values()?))?;
+            Ok(
+                json!({
+                    "hex": serialize_btc_tx_to_hex(tx),
+                    "fee": decoded_tx["fee"],
+                    "txid": decoded_tx["txid"],
+                    "vsize": decoded_tx["vsize"],
+                    "inputs": decoded_tx["inputs"],
+                    "outputs": decoded_tx["outputs"],
+                    "change": state.get_btc_tx_summary()?.change,
+                }).to_string()
+            )
+        },
+    }
+}
@@ -1,32 +1,46 @@
-use crate::lib::{
+use crate::{
     state::State,
     types::Result,
     errors::AppError,
+    utils::read_from_path_or_stdin,
 };
 
-pub fn get_utxo_json_string_from_cli_args_and_add_to_state(
-    state: State
+pub fn get_utxo_json_string_and_add_to_state(
+    state: State,
+    maybe_utxos: &Option<String>,
+    maybe_utxo_file: &Option<String>,
 ) -> Result<State> {
     info!("✔ Getting UTXO json string from CLI args...");
-    match state.cli_args.arg_utxos.clone() {
+    match maybe_utxos {
         Some(utxo_json_string) => {
             info!("✔ UTXO json string passed in as argument...");
-            state.add_utxo_json_string(utxo_json_string)
+            state.add_utxo_json_string(utxo_json_string.clone())
         }
         None => {
-            info!("✔ UTXO json string passed in as argument...");
-            use std::fs::read_to_string;
-            match state.cli_args.flag_utxoFile.clone() {
+            info!("✔ UTXO json string passed in via file...");
+            match maybe_utxo_file {
                 None => Err(AppError::Custom(
                     "✘ No UTXO JSON file path passed in!".to_string()
                 )),
-                Some(path) => {
-                    info!("✔ Reading UTXO JSON from path: {}", path);
-                    let json_string = read_to_string(path)?;
-                    info!("✔ JSON string: {}", json_string);
+                Some(_) => {
+                    let json_string = read_from_path_or_stdin(maybe_utxo_file)?;
+                    info!("✔ Read {} bytes of UTXO JSON", json_string.len());
                     state.add_utxo_json_string(json_string)
                 }
             }
         }
     }
 }
+
+pub fn maybe_get_utxo_json_string_and_add_to_state(
+    state: State,
+    maybe_utxo_file: &Option<String>,
+) -> Result<State> {
+    match maybe_utxo_file.is_some() {
+        true => get_utxo_json_string_and_add_to_state(state, &None, maybe_utxo_file),
+        false => {
+            info!("✔ No UTXOs passed in via CLI args!");
+            Ok(state)
+        }
+    }
+}
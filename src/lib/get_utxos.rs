@@ -1,7 +1,6 @@
-use crate::lib::{
-    state::State,
+use crate::{
     types::Result,
-    get_cli_args::CliArgs,
+    commands::GetUtxosArgs,
     save_output::maybe_save_output,
     get_utxos_info::get_utxos_info_and_add_to_state,
     utxo_codec::get_utxo_json_string_from_utxos_in_state,
@@ -10,13 +9,13 @@ use crate::lib::{
     get_btc_txs_from_utxos_info::get_txs_from_utxo_infos_and_put_in_state,
 };
 
-pub fn get_utxos(cli_args: CliArgs) -> Result<String> {
+pub fn get_utxos(args: &GetUtxosArgs) -> Result<String> {
     info!("✔ Getting UTXOs...");
-    State::init_from_cli_args(cli_args.clone())
+    args.to_state()
         .and_then(get_btc_private_key_and_add_to_state)
         .and_then(get_utxos_info_and_add_to_state)
         .and_then(get_txs_from_utxo_infos_and_put_in_state)
         .and_then(extract_utxos_and_add_to_state)
         .and_then(get_utxo_json_string_from_utxos_in_state)
-        .and_then(|output| maybe_save_output(output, &cli_args.flag_outputPath))
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
 }
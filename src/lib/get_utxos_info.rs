@@ -1,70 +1,78 @@
-use crate::lib::{
+use crate::{
     state::State,
     types::{
         Result,
         UtxosInfo,
     },
     errors::AppError,
-    utils::make_api_call,
+    backend::BtcBackend,
+    address_info::get_address_from_state,
+    utxo_filters::{
+        UtxoFilters,
+        filter_utxos_info,
+    },
 };
 
-fn get_utxo_info_json_string(address: &str, api_endpoint: &str) -> Result<String> {
-    info!("✔ Getting UTXO info for address: {}", address);
-    make_api_call(&format!("{}address/{}/utxo", api_endpoint, address)[..], "✘ Error getting UTXO list")
-}
-
-fn parse_utxo_list_json_string(utxo_list_json_string: &str) -> Result<UtxosInfo> {
+pub fn parse_utxo_list_json_string(utxo_list_json_string: &str) -> Result<UtxosInfo> {
     info!("✔ Parsing UTXO list JSON string...");
-    match serde_json::from_str(&utxo_list_json_string) {
+    match serde_json::from_str(utxo_list_json_string) {
         Ok(json) => Ok(json),
         Err(e) => Err(AppError::Custom(e.to_string()))
     }
 }
 
-pub fn get_utxos_info_and_add_to_state(state: State) -> Result<State> {
-    info!("✔ Getting UTXOs info and adding to state...");
-    get_utxo_info_json_string(&state.get_btc_address()?, &state.api_endpoint)
-        .and_then(|json| parse_utxo_list_json_string(&json))
-        .and_then(|utxos_info| {
+/// Gets the UTXOs paying to `address` from the `backend` & applies the `filters` to them.
+pub fn get_filtered_utxos_info(backend: &dyn BtcBackend, address: &str, filters: &UtxoFilters) -> Result<UtxosInfo> {
+    backend
+        .get_utxos_info(address)
+        .and_then(|utxos_info| filter_utxos_info(utxos_info, filters, backend))
+        .inspect(|utxos_info| {
             info!("✔ {} UTXO(s) in list", utxos_info.len());
-            state.add_utxos_info(utxos_info)
         })
 }
 
-pub fn get_utxos_info_for_address_in_cli_args_and_add_to_state(state: State) -> Result<State> {
-    info!("✔ Getting UTXOs info for address in CLI args and adding to state...");
-    get_utxo_info_json_string(&state.cli_args.arg_btcAddress, &state.api_endpoint)
-        .and_then(|json| parse_utxo_list_json_string(&json))
-        .and_then(|utxos_info| {
-            info!("✔ {} UTXO(s) in list", utxos_info.len());
-            state.add_utxos_info(utxos_info)
-        })
+pub fn get_utxos_info_and_add_to_state(state: State) -> Result<State> {
+    info!("✔ Getting UTXOs info and adding to state...");
+    get_filtered_utxos_info(&state.backend, &state.get_btc_address()?, &state.utxo_filters)
+        .and_then(|utxos_info| state.add_utxos_info(utxos_info))
+}
+
+pub fn get_utxos_info_for_address_and_add_to_state(state: State) -> Result<State> {
+    info!("✔ Getting UTXOs info for address and adding to state...");
+    get_filtered_utxos_info(&state.backend, &get_address_from_state(&state)?, &state.utxo_filters)
+        .and_then(|utxos_info| state.add_utxos_info(utxos_info))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::lib::test_utils::{
-        SAMPLE_TESTNET_ENDPOINT,
-        SAMPLE_TARGET_BTC_ADDRESS,
+    use crate::{
+        backend::EsploraBackend,
+        test_utils::{
+            SAMPLE_TESTNET_ENDPOINT,
+            SAMPLE_TARGET_BTC_ADDRESS,
+        },
     };
 
     #[test]
-    fn should_get_utxo_list_json_string() {
-        let result = get_utxo_info_json_string(
-            &SAMPLE_TARGET_BTC_ADDRESS.to_string(),
-            &SAMPLE_TESTNET_ENDPOINT.to_string(),
-        );
+    fn should_get_utxo_list() {
+        let result = EsploraBackend::new(SAMPLE_TESTNET_ENDPOINT).get_utxos_info(SAMPLE_TARGET_BTC_ADDRESS);
         assert!(result.is_ok());
     }
 
     #[test]
     fn should_parse_utxo_list_json_string() {
-        let utxo_list_json_string = get_utxo_info_json_string(
-            &SAMPLE_TARGET_BTC_ADDRESS.to_string(),
-            &SAMPLE_TESTNET_ENDPOINT.to_string(),
-        ).unwrap();
-        let result = parse_utxo_list_json_string(&utxo_list_json_string);
-        assert!(result.is_ok());
+        let utxo_list_json_string = "[{\"txid\":\"85f8faf4a3da404a833d0a21b1cea215da74a4b2c1ce8187cbf6379f42c02924\",\"vout\":0,\"value\":1666}]";
+        let result = parse_utxo_list_json_string(utxo_list_json_string).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].value, 1666);
+    }
+
+    #[test]
+    fn should_get_filtered_utxo_list() {
+        let filters = UtxoFilters { min_value: Some(1), ..UtxoFilters::default() };
+        let backend = EsploraBackend::new(SAMPLE_TESTNET_ENDPOINT);
+        let result = get_filtered_utxos_info(&backend, SAMPLE_TARGET_BTC_ADDRESS, &filters).unwrap();
+        assert!(result.iter().all(|utxo_info| utxo_info.value >= 1));
     }
 }
@@ -1,5 +1,14 @@
-use crate::lib::types::Result;
+use serde_json::json;
+use crate::{
+    types::Result,
+    commands::VersionArgs,
+    output_format::OutputFormat,
+};
 
-pub fn get_version_info() -> Result<String> {
-    Ok(env!("CARGO_PKG_VERSION").to_string())
+pub fn get_version_info(args: &VersionArgs) -> Result<String> {
+    let version = env!("CARGO_PKG_VERSION").to_string();
+    match args.output.output {
+        OutputFormat::Text => Ok(version),
+        OutputFormat::Json => Ok(json!({ "version": version }).to_string()),
+    }
 }
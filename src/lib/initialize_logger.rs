@@ -1,18 +1,19 @@
 use simplelog::*;
 use log::LevelFilter;
-use crate::lib::{
+use crate::{
     types::Result,
     errors::AppError,
-    get_cli_args::CliArgs,
+    commands::Command,
 };
 
-pub fn maybe_initialize_logger_and_return_cli_args(
-    cli_args: CliArgs
-) -> Result<CliArgs> {
-    match &cli_args.flag_logLevel[..] {
-        "none" => Ok(cli_args),
+pub fn maybe_initialize_logger_and_return_command(
+    command: Command
+) -> Result<Command> {
+    let log_level = command.get_log_level().to_string();
+    match &log_level[..] {
+        "none" => Ok(command),
         _ => match TermLogger::init(
-            match &cli_args.flag_logLevel[..] {
+            match &log_level[..] {
                 "info" => Ok(LevelFilter::Info),
                 "warn" => Ok(LevelFilter::Warn),
                 "debug" => Ok(LevelFilter::Debug),
@@ -21,16 +22,16 @@ pub fn maybe_initialize_logger_and_return_cli_args(
                 _ => Err(AppError::Custom(
                     format!(
                         "✘ Not a valid log level: '{}'",
-                        cli_args.flag_logLevel
+                        log_level
                     )
                 ))
             }?,
             Config::default(),
-            TerminalMode::Mixed,
+            TerminalMode::Stderr,
         ) {
             Ok(_) => {
                 info!("✔ Logger initialized successfully!");
-                Ok(cli_args)
+                Ok(command)
             },
             Err(e) => Err(AppError::Custom(e.to_string()))
         }
@@ -1,25 +1,30 @@
-use crate::lib::{
-    state::State,
+use crate::{
     types::Result,
-    get_cli_args::CliArgs,
-    utils::serialize_tx_in_state,
+    commands::MakeOnlineOpReturnTxArgs,
+    get_tx_output::get_tx_output_from_state,
     save_output::maybe_save_output,
-    get_utxos_info::get_utxos_info_and_add_to_state,
-    create_op_return_tx::create_op_return_tx_and_add_to_state,
-    get_btc_private_key::get_btc_private_key_and_add_to_state,
-    extract_utxos_from_utxo_info::extract_utxos_and_add_to_state,
-    get_btc_txs_from_utxos_info::get_txs_from_utxo_infos_and_put_in_state,
+    batch_payments::{
+        process_payment_batches,
+        split_state_into_payment_batches,
+    },
+    btc_tx_summary::{
+        get_tx_summary_and_add_to_state,
+        maybe_confirm_tx_summary_in_state,
+    },
+    create_tx::create_tx_and_add_to_state,
+    get_tx_inputs::get_online_tx_inputs_and_add_to_state,
 };
 
-pub fn make_online_op_return_transaction(cli_args: CliArgs) -> Result<String> {
+pub fn make_online_op_return_transaction(args: &MakeOnlineOpReturnTxArgs) -> Result<String> {
     info!("✔ Making online `OP_RETURN` transaction...");
-    State::init_from_cli_args(cli_args.clone())
-        .and_then(get_btc_private_key_and_add_to_state)
-        .and_then(get_utxos_info_and_add_to_state)
-        .and_then(get_txs_from_utxo_infos_and_put_in_state)
-        .and_then(extract_utxos_and_add_to_state)
-        .and_then(create_op_return_tx_and_add_to_state)
-        .and_then(serialize_tx_in_state)
-        .and_then(|output| maybe_save_output(output, &cli_args.flag_outputPath))
-
+    args.to_state()
+        .and_then(get_online_tx_inputs_and_add_to_state)
+        .and_then(split_state_into_payment_batches)
+        .and_then(|states| process_payment_batches(states, |state|
+            get_tx_summary_and_add_to_state(state)
+                .and_then(maybe_confirm_tx_summary_in_state)
+                .and_then(create_tx_and_add_to_state)
+                .and_then(get_tx_output_from_state)
+        ))
+        .and_then(|output| maybe_save_output(output, &args.tx.common.output_path))
 }
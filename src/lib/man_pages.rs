@@ -0,0 +1,51 @@
+use std::{
+    fs,
+    path::PathBuf,
+};
+use clap::CommandFactory;
+use clap_mangen::Man;
+use crate::{
+    types::Result,
+    errors::AppError,
+    commands::Cli,
+    utils::bytes_to_utf8_str,
+};
+
+fn get_man_page(command: clap::Command) -> Result<String> {
+    let mut buffer = vec![];
+    Man::new(command).render(&mut buffer)?;
+    bytes_to_utf8_str(&buffer)
+}
+
+/// Saves the top-level man page & one per command, eg: `bitcoff-makeOnlineTx.1`, in `dir`.
+fn save_man_pages(dir: &str) -> Result<Vec<PathBuf>> {
+    let mut command = Cli::command();
+    command.build();
+    fs::create_dir_all(dir)?;
+    std::iter::once(&command)
+        .chain(command.get_subcommands().filter(|subcommand| subcommand.get_name() != "help"))
+        .map(|command| Man::new(command.clone()).generate_to(dir).map_err(AppError::from))
+        .collect()
+}
+
+pub fn get_man_pages(maybe_dir: &Option<String>) -> Result<String> {
+    match maybe_dir {
+        None => get_man_page(Cli::command()),
+        Some(dir) => {
+            info!("✔ Saving man pages in '{}'...", dir);
+            save_man_pages(dir).map(|paths| format!("✔ {} man pages saved in '{}'", paths.len(), dir))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_get_man_page() {
+        let result = get_man_page(Cli::command()).unwrap();
+        assert!(result.contains("bitcoff"));
+        assert!(result.contains("makeOnlineTx"));
+    }
+}
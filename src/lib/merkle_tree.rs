@@ -0,0 +1,187 @@
+use bitcoin::hashes::{
+    Hash,
+    sha256,
+};
+use crate::{
+    errors::AppError,
+    types::Result,
+};
+
+/// Which side of the running hash a proof step's sibling sits on when the two are hashed together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiblingPosition {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProofStep {
+    pub hash: String,
+    pub position: SiblingPosition,
+}
+
+/// A binary tree of SHA256 hashes, where each leaf is hashed as SHA256(0x00 ‖ leaf) & each parent as
+/// SHA256(0x01 ‖ left ‖ right).
+///
+/// NOTE: The prefixes keep leaves & parents apart, as per RFC 6962, so neither can be passed off as the other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    leaves: Vec<sha256::Hash>,
+    levels: Vec<Vec<sha256::Hash>>,
+}
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub fn parse_sha256_hash(hash: &str) -> Result<sha256::Hash> {
+    hex::decode(hash)
+        .ok()
+        .and_then(|bytes| sha256::Hash::from_slice(&bytes).ok())
+        .ok_or_else(|| AppError::Custom(format!("✘ '{}' is not a valid SHA256 hash!", hash)))
+}
+
+fn hash_leaf(leaf: &sha256::Hash) -> sha256::Hash {
+    sha256::Hash::hash(&[&[LEAF_PREFIX], &leaf[..]].concat())
+}
+
+fn hash_pair(left: &sha256::Hash, right: &sha256::Hash) -> sha256::Hash {
+    sha256::Hash::hash(&[&[NODE_PREFIX], &left[..], &right[..]].concat())
+}
+
+fn get_next_level(level: &[sha256::Hash]) -> Vec<sha256::Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_pair(left, right),
+            // NOTE: An odd node out is promoted as is, rather than hashed with itself as in bitcoin's block Merkle trees,
+            // so that eg: [a, b, c] & [a, b, c, c] don't share a root.
+            _ => pair[0],
+        })
+        .collect()
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<sha256::Hash>) -> Result<Self> {
+        if leaves.is_empty() {
+            return Err(AppError::Custom("✘ Cannot build a Merkle tree with no leaves!".to_string()))
+        };
+        let mut levels = vec![leaves.iter().map(hash_leaf).collect::<Vec<sha256::Hash>>()];
+        while levels[levels.len() - 1].len() > 1 {
+            let next_level = get_next_level(&levels[levels.len() - 1]);
+            levels.push(next_level);
+        }
+        Ok(Self { leaves, levels })
+    }
+
+    pub fn root(&self) -> sha256::Hash {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// The leaves as passed in, before being hashed into the tree.
+    pub fn leaves(&self) -> &[sha256::Hash] {
+        &self.leaves
+    }
+
+    /// The sibling hashes needed to get from the leaf at `leaf_index` up to the root, lowest first.
+    pub fn get_proof(&self, leaf_index: usize) -> Result<Vec<MerkleProofStep>> {
+        if leaf_index >= self.leaves().len() {
+            return Err(AppError::Custom(format!("✘ No leaf at index {} in the Merkle tree!", leaf_index)))
+        };
+        let mut index = leaf_index;
+        let mut steps = vec![];
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                steps.push(MerkleProofStep {
+                    hash: hex::encode(&sibling[..]),
+                    position: match index % 2 {
+                        0 => SiblingPosition::Right,
+                        _ => SiblingPosition::Left,
+                    },
+                });
+            };
+            index /= 2;
+        }
+        Ok(steps)
+    }
+}
+
+pub fn get_merkle_root_from_proof(leaf: sha256::Hash, proof: &[MerkleProofStep]) -> Result<sha256::Hash> {
+    proof.iter().try_fold(hash_leaf(&leaf), |hash, step| {
+        let sibling = parse_sha256_hash(&step.hash)?;
+        Ok(match step.position {
+            SiblingPosition::Left => hash_pair(&sibling, &hash),
+            SiblingPosition::Right => hash_pair(&hash, &sibling),
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_sample_leaves(num_leaves: usize) -> Vec<sha256::Hash> {
+        (0..num_leaves).map(|i| sha256::Hash::hash(format!("file #{}", i).as_bytes())).collect()
+    }
+
+    fn concat_hashes(left: &sha256::Hash, right: &sha256::Hash) -> Vec<u8> {
+        [&left[..], &right[..]].concat()
+    }
+
+    #[test]
+    fn should_use_hashed_leaf_as_root_of_single_leaf_tree() {
+        let leaves = get_sample_leaves(1);
+        let result = MerkleTree::new(leaves.clone()).unwrap();
+        assert_eq!(result.root(), hash_leaf(&leaves[0]));
+        assert!(result.get_proof(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn should_get_root_of_two_leaf_tree() {
+        let leaves = get_sample_leaves(2);
+        let result = MerkleTree::new(leaves.clone()).unwrap();
+        assert_eq!(result.root(), hash_pair(&hash_leaf(&leaves[0]), &hash_leaf(&leaves[1])));
+    }
+
+    #[test]
+    fn should_not_let_a_parent_pass_as_a_leaf() {
+        let leaves = get_sample_leaves(3);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let spliced_leaves = vec![sha256::Hash::hash(&concat_hashes(&leaves[0], &leaves[1])), leaves[2]];
+        assert_ne!(MerkleTree::new(spliced_leaves).unwrap().root(), tree.root());
+        assert_ne!(MerkleTree::new(tree.levels[1].clone()).unwrap().root(), tree.root());
+    }
+
+    #[test]
+    fn should_not_prove_file_made_of_two_child_hashes() {
+        let tree = MerkleTree::new(get_sample_leaves(4)).unwrap();
+        let forged_file = concat_hashes(&tree.levels[0][0], &tree.levels[0][1]);
+        let proof = tree.get_proof(0).unwrap();
+        assert_eq!(forged_file.len(), 64);
+        assert_ne!(get_merkle_root_from_proof(sha256::Hash::hash(&forged_file), &proof[1..]).unwrap(), tree.root());
+    }
+
+    #[test]
+    fn should_prove_every_leaf_back_to_root() {
+        (1..10).for_each(|num_leaves| {
+            let tree = MerkleTree::new(get_sample_leaves(num_leaves)).unwrap();
+            tree.leaves().iter().enumerate().for_each(|(i, leaf)| {
+                let proof = tree.get_proof(i).unwrap();
+                assert_eq!(get_merkle_root_from_proof(*leaf, &proof).unwrap(), tree.root());
+            });
+        });
+    }
+
+    #[test]
+    fn should_not_prove_wrong_leaf() {
+        let tree = MerkleTree::new(get_sample_leaves(3)).unwrap();
+        let proof = tree.get_proof(0).unwrap();
+        assert_ne!(get_merkle_root_from_proof(tree.leaves()[1], &proof).unwrap(), tree.root());
+    }
+
+    #[test]
+    fn should_err_building_tree_with_no_leaves() {
+        assert!(MerkleTree::new(vec![]).is_err());
+        assert!(MerkleTree::new(get_sample_leaves(2)).unwrap().get_proof(2).is_err());
+    }
+}
@@ -1,21 +1,69 @@
+//! Build, sign & verify bitcoin transactions with a private key that never has to touch a networked machine.
+//!
+//! The chain data needed to build a transaction is sourced via a `BtcBackend`, of which `EsploraBackend` is the
+//! default implementation. The `bitcoff` binary is a thin wrapper around the `cli` module.
+#![allow(clippy::match_bool)]
+
+#[macro_use] extern crate log;
+#[macro_use] extern crate serde_derive;
+
+pub mod cli;
+pub(crate) mod bbqr;
 pub(crate) mod state;
 pub(crate) mod types;
 pub(crate) mod utils;
+pub(crate) mod config;
 pub(crate) mod errors;
-pub(crate) mod get_utxos;
-pub(crate) mod create_tx;
+pub(crate) mod backend;
+pub(crate) mod qr_code;
+pub(crate) mod commands;
 pub(crate) mod constants;
-pub(crate) mod utxo_codec;
+pub(crate) mod create_tx;
+pub(crate) mod decode_qr;
+pub(crate) mod encode_qr;
+pub(crate) mod get_utxos;
+pub(crate) mod man_pages;
+pub(crate) mod op_return;
+pub(crate) mod utxo_file;
+pub(crate) mod btc_amount;
 pub(crate) mod test_utils;
-pub(crate) mod usage_info;
+pub(crate) mod tx_builder;
+pub(crate) mod utxo_codec;
+pub(crate) mod completions;
+pub(crate) mod get_balance;
+pub(crate) mod get_btc_tx;
+pub(crate) mod get_history;
+pub(crate) mod merkle_tree;
 pub(crate) mod save_output;
-pub(crate) mod get_cli_args;
+pub(crate) mod show_config;
+pub(crate) mod address_info;
+pub(crate) mod coin_control;
+pub(crate) mod get_payments;
+pub(crate) mod utxo_filters;
+pub(crate) mod consolidation;
+pub(crate) mod decode_btc_tx;
+pub(crate) mod fee_estimates;
+pub(crate) mod get_tx_output;
+pub(crate) mod get_tx_inputs;
+pub(crate) mod output_format;
+pub(crate) mod verify_btc_tx;
+pub(crate) mod batch_payments;
+pub(crate) mod btc_tx_summary;
 pub(crate) mod get_utxos_info;
-pub(crate) mod btc_transaction;
+pub(crate) mod op_return_data;
+pub(crate) mod read_op_return;
+pub(crate) mod scan_op_return;
+pub(crate) mod btc_destination;
 pub(crate) mod btc_private_key;
+pub(crate) mod btc_transaction;
+pub(crate) mod timestamp_files;
+pub(crate) mod timestamp_proof;
 pub(crate) mod get_version_info;
+pub(crate) mod verify_timestamp;
+pub(crate) mod consolidate_utxos;
 pub(crate) mod initialize_logger;
-pub(crate) mod create_op_return_tx;
+pub(crate) mod decode_transaction;
+pub(crate) mod verify_transaction;
 pub(crate) mod get_btc_private_key;
 pub(crate) mod get_utxo_json_string;
 pub(crate) mod get_utxos_for_address;
@@ -25,3 +73,83 @@ pub(crate) mod get_btc_txs_from_utxos_info;
 pub(crate) mod extract_utxos_from_utxo_info;
 pub(crate) mod make_online_op_return_transaction;
 pub(crate) mod make_offline_op_return_transaction;
+
+pub use bitcoin;
+pub use crate::{
+    errors::AppError,
+    btc_private_key::BtcPrivateKey,
+    btc_tx_summary::BtcTxSummary,
+    utxo_filters::UtxoFilters,
+    fee_estimates::FeeEstimates,
+    verify_btc_tx::verify_btc_tx,
+    utils::serialize_btc_tx_to_hex,
+    btc_amount::{
+        parse_fee_rate,
+        parse_btc_amount,
+    },
+    backend::{
+        BtcBackend,
+        EsploraBackend,
+    },
+    btc_destination::{
+        BtcDestination,
+        get_script_type,
+    },
+    decode_btc_tx::{
+        get_btc_tx_fee,
+        get_btc_tx_vsize,
+    },
+    address_info::{
+        EsploraTx,
+        AddressInfo,
+        AddressStats,
+        HistoryEntry,
+        get_address_txs,
+    },
+    utxo_file::{
+        UtxoFile,
+        UtxoFileMetadata,
+        parse_utxo_file,
+        get_utxo_file_json_value,
+    },
+    types::{
+        Result,
+        UtxoInfo,
+        UtxosInfo,
+        UtxoStatus,
+        BtcUtxoAndValue,
+        BtcUtxosAndValues,
+        BtcAddressAndAmount,
+        BtcAddressesAndAmounts,
+    },
+    tx_builder::{
+        TxBuilder,
+        ChangePolicy,
+    },
+    op_return::{
+        OpReturnOutput,
+        OpReturnPolicy,
+    },
+    merkle_tree::{
+        MerkleTree,
+        MerkleProofStep,
+        SiblingPosition,
+    },
+    timestamp_proof::{
+        TimestampProof,
+        hash_file,
+    },
+    config::{
+        Config,
+        Profile,
+        FeeValue,
+        load_config,
+    },
+    op_return_data::{
+        OpReturnData,
+        scan_op_return_data,
+        get_op_return_data_from_tx,
+    },
+    get_btc_txs_from_utxos_info::convert_hex_tx_to_btc_tx,
+    extract_utxos_from_utxo_info::get_btc_utxos_and_values_for_address,
+};
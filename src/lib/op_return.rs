@@ -0,0 +1,209 @@
+use std::fs;
+use bitcoin::{
+    blockdata::transaction::TxOut as BtcTxOut,
+    hashes::{
+        Hash,
+        sha256,
+    },
+};
+use crate::{
+    errors::AppError,
+    utils::get_op_return_output,
+    constants::{
+        MAX_OP_RETURN_RELAY,
+        MAX_LARGE_OP_RETURN_RELAY,
+    },
+    types::{
+        Byte,
+        Bytes,
+        Result,
+    },
+};
+
+pub const OP_RETURN_MAGIC_LENGTH: usize = 4;
+
+/// An output carrying arbitrary data in an `OP_RETURN` script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpReturnOutput(pub Bytes);
+
+impl OpReturnOutput {
+    /// Commits to the SHA256 hash of `data` rather than the data itself, OpenTimestamps-style.
+    pub fn from_hash_of(data: &[Byte]) -> Self {
+        OpReturnOutput(sha256::Hash::hash(data).into_inner().to_vec())
+    }
+
+    /// Prepends a 4-byte protocol tag so the output can be told apart from other protocols' data.
+    pub fn with_magic(self, magic: [Byte; OP_RETURN_MAGIC_LENGTH]) -> Self {
+        OpReturnOutput([&magic[..], &self.0[..]].concat())
+    }
+}
+
+impl From<OpReturnOutput> for BtcTxOut {
+    fn from(output: OpReturnOutput) -> BtcTxOut {
+        get_op_return_output(&output.0).expect("✘ Pushing bytes to a script cannot fail!")
+    }
+}
+
+/// The relay policy a transaction's `OP_RETURN` outputs are checked against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OpReturnPolicy {
+    /// At most one `OP_RETURN` output, whose script is at most 83 bytes, ie: an 80-byte payload.
+    #[default]
+    Standard,
+    /// Any number of `OP_RETURN` outputs whose scripts total at most 100,000 bytes, as relayed by nodes running the
+    /// newer, larger `-datacarriersize` default. Many nodes & miners still won't relay these.
+    LargeDatacarrier,
+}
+
+impl OpReturnPolicy {
+    pub fn new(allow_large_data: bool) -> Self {
+        match allow_large_data {
+            true => OpReturnPolicy::LargeDatacarrier,
+            false => OpReturnPolicy::Standard,
+        }
+    }
+
+    pub fn get_errors(&self, outputs: &[BtcTxOut]) -> Vec<String> {
+        let op_return_outputs = outputs
+            .iter()
+            .enumerate()
+            .filter(|(_, output)| output.script_pubkey.is_op_return())
+            .collect::<Vec<(usize, &BtcTxOut)>>();
+        let mut errors = vec![];
+        match self {
+            OpReturnPolicy::Standard => {
+                if op_return_outputs.len() > 1 {
+                    errors.push(
+                        format!("✘ Tx has {} `OP_RETURN` outputs but only one is standard!", op_return_outputs.len())
+                    );
+                };
+                op_return_outputs
+                    .iter()
+                    .filter(|(_, output)| output.script_pubkey.len() > MAX_OP_RETURN_RELAY)
+                    .for_each(|(i, output)| errors.push(format!(
+                        "✘ `OP_RETURN` output #{} is {} bytes, exceeding the standard maximum of {}!",
+                        i,
+                        output.script_pubkey.len(),
+                        MAX_OP_RETURN_RELAY,
+                    )));
+            },
+            OpReturnPolicy::LargeDatacarrier => {
+                let total = op_return_outputs.iter().map(|(_, output)| output.script_pubkey.len()).sum::<usize>();
+                if total > MAX_LARGE_OP_RETURN_RELAY {
+                    errors.push(format!(
+                        "✘ `OP_RETURN` outputs total {} bytes, exceeding the large datacarrier maximum of {}!",
+                        total,
+                        MAX_LARGE_OP_RETURN_RELAY,
+                    ));
+                };
+            },
+        };
+        errors
+    }
+
+    pub fn check(&self, outputs: &[BtcTxOut]) -> Result<()> {
+        let errors = self.get_errors(outputs);
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(AppError::Custom(errors.join(" "))),
+        }
+    }
+}
+
+/// Parses an `OP_RETURN` payload, which is hex unless prefixed with `text:` for UTF-8 text, or `file:` for the raw
+/// bytes of the file at that path.
+pub fn parse_op_return_data(data: &str) -> Result<Bytes> {
+    let mut parts = data.splitn(2, ':');
+    match (parts.next(), parts.next()) {
+        (Some("text"), Some(text)) => Ok(text.as_bytes().to_vec()),
+        (Some("file"), Some(path)) => {
+            info!("✔ Reading `OP_RETURN` data from file: {}", path);
+            fs::read(path).map_err(|e| AppError::Custom(format!("✘ Error reading data file '{}': {}", path, e)))
+        },
+        _ => hex::decode(data).map_err(|_| AppError::Custom(
+            format!("✘ Data '{}' is not valid hex! Prefix it with `text:` or `file:` for other formats.", data)
+        )),
+    }
+}
+
+pub fn parse_op_return_magic(magic: &str) -> Result<[Byte; OP_RETURN_MAGIC_LENGTH]> {
+    let bytes = hex::decode(magic).map_err(|_| AppError::Custom(format!("✘ Prefix '{}' is not valid hex!", magic)))?;
+    match bytes.len() {
+        OP_RETURN_MAGIC_LENGTH => {
+            let mut tag = [0u8; OP_RETURN_MAGIC_LENGTH];
+            tag.copy_from_slice(&bytes);
+            Ok(tag)
+        },
+        length => Err(AppError::Custom(
+            format!("✘ Prefix must be {} bytes, got {}!", OP_RETURN_MAGIC_LENGTH, length)
+        )),
+    }
+}
+
+/// One output per `OP_RETURN` payload, each hashed first if `hash_data` & tagged with the 4-byte `maybe_prefix`.
+pub fn get_op_return_outputs(
+    data: &[String],
+    maybe_prefix: &Option<String>,
+    hash_data: bool,
+) -> Result<Vec<OpReturnOutput>> {
+    let maybe_magic = match maybe_prefix {
+        Some(prefix) => Some(parse_op_return_magic(prefix)?),
+        None => None,
+    };
+    data
+        .iter()
+        .map(|data| -> Result<OpReturnOutput> {
+            let bytes = parse_op_return_data(data)?;
+            let output = match hash_data {
+                true => OpReturnOutput::from_hash_of(&bytes),
+                false => OpReturnOutput(bytes),
+            };
+            Ok(match maybe_magic {
+                Some(magic) => output.with_magic(magic),
+                None => output,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::slice;
+
+    #[test]
+    fn should_parse_op_return_data_in_each_format() {
+        assert_eq!(parse_op_return_data("c0ffee").unwrap(), vec![0xc0, 0xff, 0xee]);
+        assert_eq!(parse_op_return_data("text:gm: bitcoff").unwrap(), b"gm: bitcoff".to_vec());
+        assert!(parse_op_return_data("not hex").is_err());
+        assert!(parse_op_return_data("file:/does/not/exist").is_err());
+    }
+
+    #[test]
+    fn should_hash_and_prefix_op_return_output() {
+        let expected_hash = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        let result = OpReturnOutput::from_hash_of(b"hello").with_magic(parse_op_return_magic("6f6d6e69").unwrap());
+        assert_eq!(result.0.len(), 36);
+        assert_eq!(hex::encode(&result.0[..4]), "6f6d6e69");
+        assert_eq!(hex::encode(&result.0[4..]), expected_hash);
+    }
+
+    #[test]
+    fn should_err_parsing_magic_of_wrong_length() {
+        assert!(parse_op_return_magic("6f6d6e").is_err());
+        assert!(parse_op_return_magic("6f6d6e6901").is_err());
+    }
+
+    #[test]
+    fn should_check_op_return_outputs_against_relay_policy() {
+        let max_standard_output: BtcTxOut = OpReturnOutput(vec![0u8; 80]).into();
+        let large_output: BtcTxOut = OpReturnOutput(vec![0u8; 81]).into();
+        assert_eq!(max_standard_output.script_pubkey.len(), MAX_OP_RETURN_RELAY);
+        assert!(OpReturnPolicy::Standard.check(slice::from_ref(&max_standard_output)).is_ok());
+        assert!(OpReturnPolicy::Standard.check(slice::from_ref(&large_output)).is_err());
+        assert!(OpReturnPolicy::Standard.check(&[max_standard_output.clone(), max_standard_output.clone()]).is_err());
+        assert!(OpReturnPolicy::LargeDatacarrier.check(&[max_standard_output, large_output]).is_ok());
+        let too_large_output: BtcTxOut = OpReturnOutput(vec![0u8; MAX_LARGE_OP_RETURN_RELAY]).into();
+        assert!(OpReturnPolicy::LargeDatacarrier.check(&[too_large_output]).is_err());
+    }
+}
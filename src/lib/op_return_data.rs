@@ -0,0 +1,204 @@
+use std::fmt;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use bitcoin::blockdata::{
+    script::Script as BtcScript,
+    transaction::Transaction as BtcTransaction,
+};
+use crate::{
+    state::State,
+    backend::BtcBackend,
+    output_format::OutputFormat,
+    utils::get_op_return_payload,
+    op_return::parse_op_return_magic,
+    get_btc_txs_from_utxos_info::convert_hex_tx_to_btc_tx,
+    get_btc_tx::get_btc_tx_and_add_to_state,
+    address_info::{
+        EsploraTx,
+        get_address_txs,
+        get_address_from_state,
+    },
+    types::{
+        Byte,
+        Bytes,
+        Result,
+    },
+};
+
+/// The payload of an `OP_RETURN` output, along with the outpoint it was found at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpReturnData {
+    pub vout: usize,
+    pub txid: String,
+    pub payload: Bytes,
+}
+
+impl OpReturnData {
+    pub fn get_text(&self) -> Option<&str> {
+        std::str::from_utf8(&self.payload).ok()
+    }
+
+    pub fn has_prefix(&self, prefix: &[Byte]) -> bool {
+        self.payload.starts_with(prefix)
+    }
+
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "vout": self.vout,
+            "txid": self.txid,
+            "text": self.get_text(),
+            "hex": hex::encode(&self.payload),
+        })
+    }
+}
+
+impl fmt::Display for OpReturnData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{} ❍ {}", self.txid, self.vout, hex::encode(&self.payload))?;
+        match self.get_text() {
+            Some(text) => write!(f, " ❍ {:?}", text),
+            None => Ok(()),
+        }
+    }
+}
+
+fn get_op_return_data_from_scripts<'a, I>(txid: &str, scripts: I) -> Vec<OpReturnData>
+    where I: Iterator<Item = &'a BtcScript>
+{
+    scripts
+        .enumerate()
+        .filter_map(|(vout, script)| get_op_return_payload(script).map(|payload| OpReturnData {
+            vout,
+            payload,
+            txid: txid.to_string(),
+        }))
+        .collect()
+}
+
+pub fn get_op_return_data_from_tx(tx: &BtcTransaction) -> Vec<OpReturnData> {
+    get_op_return_data_from_scripts(&tx.txid().to_string(), tx.output.iter().map(|output| &output.script_pubkey))
+}
+
+pub fn get_op_return_data_from_esplora_tx(tx: &EsploraTx) -> Vec<OpReturnData> {
+    let scripts = tx
+        .vout
+        .iter()
+        .map(|output| BtcScript::from(hex::decode(&output.scriptpubkey).unwrap_or_default()))
+        .collect::<Vec<BtcScript>>();
+    get_op_return_data_from_scripts(&tx.txid, scripts.iter())
+}
+
+/// Every `OP_RETURN` payload in the history of `address`, newest first, optionally only those starting with `prefix`.
+pub fn scan_op_return_data(
+    backend: &dyn BtcBackend,
+    address: &str,
+    maybe_prefix: Option<&[Byte]>,
+) -> Result<Vec<OpReturnData>> {
+    info!("✔ Scanning history of address {} for `OP_RETURN` data...", address);
+    Ok(
+        get_address_txs(backend, address, usize::MAX, None)?
+            .iter()
+            .flat_map(get_op_return_data_from_esplora_tx)
+            .filter(|data| maybe_prefix.map(|prefix| data.has_prefix(prefix)).unwrap_or(true))
+            .collect()
+    )
+}
+
+fn is_txid(tx: &str) -> bool {
+    tx.len() == 64 && hex::decode(tx).is_ok()
+}
+
+pub fn get_tx_to_read_and_add_to_state(
+    state: State,
+    maybe_tx: &Option<String>,
+    maybe_tx_file: &Option<String>,
+) -> Result<State> {
+    info!("✔ Getting tx to read `OP_RETURN` data from...");
+    match maybe_tx {
+        Some(ref tx) if is_txid(tx.trim()) => {
+            let tx_hex = state.backend.get_tx_hex(tx.trim())?;
+            convert_hex_tx_to_btc_tx(&tx_hex).and_then(|btc_tx| state.add_btc_tx(btc_tx))
+        },
+        Some(tx_hex) => convert_hex_tx_to_btc_tx(tx_hex.trim()).and_then(|btc_tx| state.add_btc_tx(btc_tx)),
+        None => get_btc_tx_and_add_to_state(state, &None, maybe_tx_file),
+    }
+}
+
+fn get_op_return_data_output(data: &[OpReturnData], output_format: OutputFormat, json: JsonValue) -> String {
+    match output_format {
+        OutputFormat::Json => json.to_string(),
+        OutputFormat::Text => match data.is_empty() {
+            true => "❍ No `OP_RETURN` data found".to_string(),
+            false => data.iter().map(OpReturnData::to_string).collect::<Vec<String>>().join("\n"),
+        },
+    }
+}
+
+pub fn get_read_op_return_output_from_state(state: State) -> Result<String> {
+    info!("✔ Getting `OP_RETURN` data output from state...");
+    let tx = state.get_btc_tx()?;
+    let data = get_op_return_data_from_tx(tx);
+    let json = json!({
+        "txid": tx.txid().to_string(),
+        "op_returns": data.iter().map(OpReturnData::to_json).collect::<Vec<JsonValue>>(),
+    });
+    Ok(get_op_return_data_output(&data, state.output_format, json))
+}
+
+pub fn get_scan_op_return_output_from_state(state: State, maybe_prefix: &Option<String>) -> Result<String> {
+    info!("✔ Getting `OP_RETURN` scan output from state...");
+    let address = get_address_from_state(&state)?;
+    let maybe_prefix = match maybe_prefix {
+        Some(prefix) => Some(parse_op_return_magic(prefix)?),
+        None => None,
+    };
+    let data = scan_op_return_data(&state.backend, &address, maybe_prefix.as_ref().map(|prefix| &prefix[..]))?;
+    let json = json!({
+        "address": address,
+        "op_returns": data.iter().map(OpReturnData::to_json).collect::<Vec<JsonValue>>(),
+    });
+    Ok(get_op_return_data_output(&data, state.output_format, json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        op_return::OpReturnOutput,
+        test_utils::get_sample_tx,
+    };
+
+    const SAMPLE_OP_RETURN_TX_JSON: &str = "{\"txid\":\"85f8faf4a3da404a833d0a21b1cea215da74a4b2c1ce8187cbf6379f42c02924\",\"fee\":226,\"status\":{\"confirmed\":true,\"block_height\":1000,\"block_time\":1570000000},\"vin\":[],\"vout\":[{\"scriptpubkey\":\"76a9149ae6e42c56f1ea319cfc704ad50db0683015029b88ac\",\"scriptpubkey_address\":\"mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM\",\"value\":5000},{\"scriptpubkey\":\"6a0b626366666d6f726e696e67\",\"value\":0}]}";
+
+    #[test]
+    fn should_get_op_return_data_from_tx() {
+        let mut tx = get_sample_tx();
+        tx.output.push(OpReturnOutput(b"gm".to_vec()).into());
+        tx.output.push(OpReturnOutput(vec![0xc0, 0xff, 0xee]).into());
+        let result = get_op_return_data_from_tx(&tx);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].vout, 2);
+        assert_eq!(result[0].get_text(), Some("gm"));
+        assert_eq!(result[1].get_text(), None);
+        assert_eq!(result[1].to_json()["hex"], "c0ffee");
+    }
+
+    #[test]
+    fn should_get_op_return_data_from_esplora_tx() {
+        let tx: EsploraTx = serde_json::from_str(SAMPLE_OP_RETURN_TX_JSON).unwrap();
+        let result = get_op_return_data_from_esplora_tx(&tx);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].vout, 1);
+        assert_eq!(result[0].get_text(), Some("bcffmorning"));
+        assert!(result[0].has_prefix(b"bcff"));
+        assert!(!result[0].has_prefix(b"omni"));
+    }
+
+    #[test]
+    fn should_recognise_txids() {
+        assert!(is_txid("85f8faf4a3da404a833d0a21b1cea215da74a4b2c1ce8187cbf6379f42c02924"));
+        assert!(!is_txid(&hex::encode(bitcoin::consensus::encode::serialize(&get_sample_tx()))));
+    }
+}
@@ -0,0 +1,76 @@
+use std::path::Path;
+use qrcode::{
+    QrCode,
+    EcLevel,
+    render::unicode::Dense1x2,
+};
+use crate::{
+    errors::AppError,
+    types::Result,
+};
+
+fn get_qr_code(data: &str) -> Result<QrCode> {
+    QrCode::with_error_correction_level(data.as_bytes(), EcLevel::L)
+        .map_err(|e| AppError::Custom(format!("✘ Error making QR code: {}", e)))
+}
+
+pub fn render_qr_code_for_terminal(data: &str) -> Result<String> {
+    Ok(
+        get_qr_code(data)?
+            .render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .build()
+    )
+}
+
+pub fn save_qr_code_as_png(data: &str, path: &Path) -> Result<()> {
+    info!("✔ Saving QR code to {}", path.display());
+    get_qr_code(data)?
+        .render::<image::Luma<u8>>()
+        .build()
+        .save(path)
+        .map_err(|e| AppError::Custom(format!("✘ Error saving QR code to {}: {}", path.display(), e)))
+}
+
+pub fn read_qr_codes_from_image(path: &str) -> Result<Vec<String>> {
+    info!("✔ Reading QR code(s) from image: {}", path);
+    let image = image::open(path)
+        .map_err(|e| AppError::Custom(format!("✘ Error opening image {}: {}", path, e)))?
+        .to_luma8();
+    let mut prepared_image = rqrr::PreparedImage::prepare(image);
+    let contents = prepared_image
+        .detect_grids()
+        .iter()
+        .map(|grid| grid
+            .decode()
+            .map(|(_, content)| content)
+            .map_err(|e| AppError::Custom(format!("✘ Error decoding QR code in {}: {:?}", path, e)))
+        )
+        .collect::<Result<Vec<String>>>()?;
+    match contents.is_empty() {
+        true => Err(AppError::Custom(format!("✘ No QR codes found in image: {}", path))),
+        false => Ok(contents),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_qr_code_for_terminal() {
+        let result = render_qr_code_for_terminal("B$2U0100MZXW6YTBOI").unwrap();
+        assert!(!result.is_empty());
+    }
+
+    #[test]
+    fn should_make_qr_code_png_round_trip() {
+        let data = "B$2U0100MZXW6YTBOI";
+        let path = std::env::temp_dir().join("bitcoff-qr-code-test.png");
+        save_qr_code_as_png(data, &path).unwrap();
+        let result = read_qr_codes_from_image(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(result, vec![data.to_string()]);
+    }
+}
@@ -0,0 +1,17 @@
+use crate::{
+    types::Result,
+    commands::ReadOpReturnArgs,
+    save_output::maybe_save_output,
+    op_return_data::{
+        get_tx_to_read_and_add_to_state,
+        get_read_op_return_output_from_state,
+    },
+};
+
+pub fn read_op_return(args: &ReadOpReturnArgs) -> Result<String> {
+    info!("✔ Reading `OP_RETURN` data...");
+    args.to_state()
+        .and_then(|state| get_tx_to_read_and_add_to_state(state, &args.tx, &args.tx_input.tx_file))
+        .and_then(get_read_op_return_output_from_state)
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
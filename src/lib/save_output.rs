@@ -1,4 +1,4 @@
-use crate::lib::types::Result;
+use crate::types::Result;
 
 pub fn maybe_save_output(
     output: String,
@@ -0,0 +1,15 @@
+use crate::{
+    types::Result,
+    commands::ScanOpReturnArgs,
+    save_output::maybe_save_output,
+    op_return_data::get_scan_op_return_output_from_state,
+    get_btc_private_key::maybe_get_btc_private_key_for_address_and_add_to_state,
+};
+
+pub fn scan_op_return(args: &ScanOpReturnArgs) -> Result<String> {
+    info!("✔ Scanning for `OP_RETURN` data...");
+    args.to_state()
+        .and_then(maybe_get_btc_private_key_for_address_and_add_to_state)
+        .and_then(|state| get_scan_op_return_output_from_state(state, &args.prefix))
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
@@ -0,0 +1,11 @@
+use crate::{
+    types::Result,
+    commands::ShowConfigArgs,
+    save_output::maybe_save_output,
+    config::get_effective_config_output,
+};
+
+pub fn show_config(args: &ShowConfigArgs) -> Result<String> {
+    info!("✔ Showing effective config...");
+    get_effective_config_output(args).and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
@@ -1,10 +1,26 @@
 use bitcoin::{
     network::constants::Network as BtcNetwork,
-    blockdata::transaction::Transaction as BtcTransaction,
+    blockdata::transaction::{
+        OutPoint as BtcOutPoint,
+        Transaction as BtcTransaction,
+    },
 };
-use crate::lib::{
+use crate::{
     errors::AppError,
+    backend::EsploraBackend,
+    utxo_filters::UtxoFilters,
     btc_private_key::BtcPrivateKey,
+    btc_tx_summary::BtcTxSummary,
+    output_format::OutputFormat,
+    op_return::{
+        OpReturnPolicy,
+        OpReturnOutput,
+    },
+    constants::{
+        DEFAULT_CONF_TARGET,
+        DEFAULT_KEYFILE_PATH,
+        DEFAULT_CHANGE_ADDRESS,
+    },
     types::{
         Result,
         UtxosInfo,
@@ -12,22 +28,35 @@ use crate::lib::{
         BtcUtxosAndValues,
         BtcAddressesAndAmounts,
     },
-    get_cli_args::{
-        CliArgs,
-        get_network_from_cli_arg,
-        get_api_endpoint_from_cli_args,
-    },
 };
 
+#[derive(Clone)]
 pub struct State {
-    pub cli_args: CliArgs,
+    pub yes: bool,
+    pub sweep: bool,
+    pub keyfile: String,
+    pub change: String,
+    pub conf_target: u64,
     pub network: BtcNetwork,
-    pub api_endpoint: String,
+    pub btc_address: Option<String>,
+    pub max_fee: Option<u64>,
+    pub max_fee_pct: Option<f64>,
+    pub sats_per_byte: Option<f64>,
+    pub utxo_filters: UtxoFilters,
+    pub selected_utxos: Vec<BtcOutPoint>,
+    pub allow_extra_inputs: bool,
+    pub allow_unverified_utxos: bool,
+    pub subtract_fee_from: Vec<usize>,
+    pub op_return_policy: OpReturnPolicy,
+    pub op_return_outputs: Vec<OpReturnOutput>,
+    pub backend: EsploraBackend,
+    pub output_format: OutputFormat,
     pub utxos_info: Option<UtxosInfo>,
     pub btc_tx: Option<BtcTransaction>,
     pub utxo_json_string: Option<String>,
     pub btc_txs: Option<BtcTransactions>,
     pub btc_private_key: Option<BtcPrivateKey>,
+    pub btc_tx_summary: Option<BtcTxSummary>,
     pub addresses_and_amounts: BtcAddressesAndAmounts,
     pub btc_utxos_and_values: Option<BtcUtxosAndValues>,
 }
@@ -41,23 +70,50 @@ pub fn get_not_in_state_err(substring: &str) -> String {
 }
 
 impl State {
-    pub fn init_from_cli_args(
-        cli_args: CliArgs
-    ) -> Result<State> {
-        Ok(
-            State {
-                btc_tx: None,
-                btc_txs: None,
-                utxos_info: None,
-                btc_private_key: None,
-                utxo_json_string: None,
-                btc_utxos_and_values: None,
-                network: get_network_from_cli_arg(&cli_args.flag_network),
-                api_endpoint: get_api_endpoint_from_cli_args(&cli_args.flag_network),
-                addresses_and_amounts: BtcAddressesAndAmounts::new(&cli_args.arg_to, &cli_args.arg_amount)?,
-                cli_args,
-            }
-        )
+    /// A `State` for the given network & block explorer, whose settings are all at their defaults until a command
+    /// sets those it takes.
+    pub fn init(network: BtcNetwork, backend: EsploraBackend, output_format: OutputFormat) -> State {
+        State {
+            network,
+            backend,
+            output_format,
+            yes: false,
+            sweep: false,
+            btc_address: None,
+            max_fee: None,
+            max_fee_pct: None,
+            sats_per_byte: None,
+            allow_extra_inputs: false,
+            allow_unverified_utxos: false,
+            keyfile: DEFAULT_KEYFILE_PATH.to_string(),
+            change: DEFAULT_CHANGE_ADDRESS.to_string(),
+            conf_target: DEFAULT_CONF_TARGET,
+            utxo_filters: UtxoFilters::default(),
+            selected_utxos: vec![],
+            subtract_fee_from: vec![],
+            op_return_policy: OpReturnPolicy::default(),
+            op_return_outputs: vec![],
+            btc_tx: None,
+            btc_txs: None,
+            utxos_info: None,
+            btc_private_key: None,
+            btc_tx_summary: None,
+            utxo_json_string: None,
+            btc_utxos_and_values: None,
+            addresses_and_amounts: BtcAddressesAndAmounts(vec![]),
+        }
+    }
+
+    pub fn to_batch_state(
+        &self,
+        addresses_and_amounts: BtcAddressesAndAmounts,
+        btc_utxos_and_values: BtcUtxosAndValues,
+    ) -> State {
+        State {
+            addresses_and_amounts,
+            btc_utxos_and_values: Some(btc_utxos_and_values),
+            ..self.clone()
+        }
     }
 
     pub fn add_btc_private_key(
@@ -144,9 +200,54 @@ impl State {
         }
     }
 
+    pub fn add_btc_tx_summary(
+        mut self,
+        btc_tx_summary: BtcTxSummary,
+    ) -> Result<State> {
+        match self.btc_tx_summary {
+            Some(_) => Err(AppError::Custom(
+                get_no_overwrite_state_err("btc_tx_summary"))
+            ),
+            None => {
+                self.btc_tx_summary = Some(btc_tx_summary);
+                Ok(self)
+            }
+        }
+    }
+
+    pub fn add_sats_per_byte(mut self, sats_per_byte: f64) -> Result<State> {
+        match self.sats_per_byte {
+            Some(_) => Err(AppError::Custom(
+                get_no_overwrite_state_err("sats_per_byte"))
+            ),
+            None => {
+                self.sats_per_byte = Some(sats_per_byte);
+                Ok(self)
+            }
+        }
+    }
+
+    pub fn get_sats_per_byte(&self) -> Result<f64> {
+        match self.sats_per_byte {
+            Some(sats_per_byte) => Ok(sats_per_byte),
+            None => Err(AppError::Custom(
+                get_not_in_state_err("sats_per_byte"))
+            )
+        }
+    }
+
+    pub fn get_btc_tx_summary(&self) -> Result<&BtcTxSummary> {
+        match &self.btc_tx_summary {
+            Some(btc_tx_summary) => Ok(btc_tx_summary),
+            None => Err(AppError::Custom(
+                get_not_in_state_err("btc_tx_summary"))
+            )
+        }
+    }
+
     pub fn get_btc_utxos_and_values(&self) -> Result<&BtcUtxosAndValues> {
         match &self.btc_utxos_and_values {
-            Some(btc_utxos_and_values) => Ok(&btc_utxos_and_values),
+            Some(btc_utxos_and_values) => Ok(btc_utxos_and_values),
             None => Err(AppError::Custom(
                 get_not_in_state_err("btc_utxos_and_values"))
             )
@@ -155,7 +256,7 @@ impl State {
 
     pub fn get_btc_txs(&self) -> Result<&BtcTransactions> {
         match &self.btc_txs {
-            Some(btc_txs) => Ok(&btc_txs),
+            Some(btc_txs) => Ok(btc_txs),
             None => Err(AppError::Custom(
                 get_not_in_state_err("btc_txs"))
             )
@@ -164,7 +265,7 @@ impl State {
 
     pub fn get_utxos_info(&self) -> Result<&UtxosInfo> {
         match &self.utxos_info {
-            Some(utxos_info) => Ok(&utxos_info),
+            Some(utxos_info) => Ok(utxos_info),
             None => Err(AppError::Custom(
                 get_not_in_state_err("utxos_info"))
             )
@@ -173,7 +274,7 @@ impl State {
 
     pub fn get_btc_private_key(&self) -> Result<&BtcPrivateKey> {
         match &self.btc_private_key {
-            Some(btc_private_key) => Ok(&btc_private_key),
+            Some(btc_private_key) => Ok(btc_private_key),
             None => Err(AppError::Custom(
                 get_not_in_state_err("btc_private_key"))
             )
@@ -187,7 +288,7 @@ impl State {
 
     pub fn get_btc_tx(&self) -> Result<&BtcTransaction> {
         match &self.btc_tx {
-            Some(btc_tx) => Ok(&btc_tx),
+            Some(btc_tx) => Ok(btc_tx),
             None => Err(AppError::Custom(
                 get_not_in_state_err("btc_tx"))
             )
@@ -196,7 +297,7 @@ impl State {
 
     pub fn get_utxo_json_string(&self) -> Result<&String> {
         match &self.utxo_json_string {
-            Some(utxo_json_string) => Ok(&utxo_json_string),
+            Some(utxo_json_string) => Ok(utxo_json_string),
             None => Err(AppError::Custom(
                 get_not_in_state_err("utxo_json_string"))
             )
@@ -1,6 +1,6 @@
 #![cfg(test)]
 use bitcoin::blockdata::transaction::Transaction as BtcTransaction;
-use crate::lib::{
+use crate::{
     types::BtcUtxoAndValue,
     btc_private_key::BtcPrivateKey,
     get_btc_txs_from_utxos_info::convert_hex_tx_to_btc_tx,
@@ -22,8 +22,12 @@ pub fn get_sample_tx_hex() -> &'static str {
     "01000000018986374e3404c889f3da5fd8b07311cad5b0e81e333a994638f65c9a9cdf4742010000006a47304402201db6cfd4be08ed4605b5eed60281438ea325af6ea6f0ff7e19f46431c29fcbcb0220157d5a1773f5eaff369735ea7608fd31b603fe279a45b5ee2f5d555c25711566012103d8d40098fa07622a89491597be95836a05de0fa5fcca1e474eb6a6213fc1f33fffffffff0282060000000000001976a91454102783c8640c5144d039cea53eb7dbb470081488acb4b81b01000000001976a9148302e646c0d9bf8b7292c6da11a721149e06749d88ac00000000"
 }
 
+pub fn get_sample_signed_tx_hex() -> &'static str {
+    "01000000016e3fa15afcd9b579b7ed082e0ee8cfba1f27a6cf007cb7ca95b06ab0fda2880c020000006b483045022100d5dec195ae624af5708ca7834b9e052b93bcf935c92c19dcf1be39ce1aa60d31022019db89b0938e14c82f1f66ad8ab7377fe5895c78293df5b3a50ffa221fa04700012103d2a5e3b162eb580fe2ce023cd5e0dddbb6286923acde77e3e5468314dc9373f7ffffffff0289130000000000001976a9149ae6e42c56f1ea319cfc704ad50db0683015029b88ac333a0d00000000001976a91454102783c8640c5144d039cea53eb7dbb470081488ac00000000"
+}
+
 pub fn get_sample_tx() -> BtcTransaction {
-    convert_hex_tx_to_btc_tx(&get_sample_tx_hex().to_string()).unwrap()
+    convert_hex_tx_to_btc_tx(get_sample_tx_hex()).unwrap()
 }
 
 pub fn get_sample_utxo() -> BtcUtxoAndValue {
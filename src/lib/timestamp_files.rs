@@ -0,0 +1,39 @@
+use crate::{
+    state::State,
+    types::Result,
+    commands::TimestampArgs,
+    save_output::maybe_save_output,
+    op_return::get_op_return_outputs,
+    create_tx::create_tx_and_add_to_state,
+    btc_tx_summary::{
+        get_tx_summary_and_add_to_state,
+        maybe_confirm_tx_summary_in_state,
+    },
+    timestamp_proof::{
+        get_merkle_tree_for_files,
+        get_timestamp_output_from_state,
+    },
+    get_tx_inputs::{
+        get_online_tx_inputs_and_add_to_state,
+        get_offline_tx_inputs_and_add_to_state,
+    },
+};
+
+pub fn timestamp_files(args: &TimestampArgs) -> Result<String> {
+    info!("✔ Timestamping {} file(s)...", args.files.len());
+    let tree = get_merkle_tree_for_files(&args.files)?;
+    info!("✔ Merkle root to timestamp: {}", hex::encode(&tree.root()[..]));
+    // NOTE: The root is published via the same `OP_RETURN` flow as any other `--data`.
+    let op_return_outputs = get_op_return_outputs(&[hex::encode(&tree.root()[..])], &args.prefix, false)?;
+    args.to_state()
+        .map(|state| State { op_return_outputs, ..state })
+        .and_then(|state| match args.utxos.get_offline_utxo_args() {
+            Some(utxos) => get_offline_tx_inputs_and_add_to_state(state, &utxos),
+            None => get_online_tx_inputs_and_add_to_state(state),
+        })
+        .and_then(get_tx_summary_and_add_to_state)
+        .and_then(maybe_confirm_tx_summary_in_state)
+        .and_then(create_tx_and_add_to_state)
+        .and_then(|state| get_timestamp_output_from_state(state, &args.files, &tree))
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
@@ -0,0 +1,201 @@
+use std::fs;
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use bitcoin::{
+    hashes::{
+        Hash,
+        sha256,
+    },
+    blockdata::transaction::Transaction as BtcTransaction,
+};
+use crate::{
+    state::State,
+    errors::AppError,
+    types::Result,
+    utils::{
+        get_op_return_payload,
+        serialize_btc_tx_to_hex,
+        read_from_path_or_stdin,
+    },
+    merkle_tree::{
+        MerkleTree,
+        MerkleProofStep,
+        get_merkle_root_from_proof,
+    },
+};
+
+/// Proves a file's hash was committed to by the `OP_RETURN` output of the transaction with id `txid`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampProof {
+    pub file: String,
+    pub hash: String,
+    pub txid: String,
+    pub merkle_root: String,
+    pub path: Vec<MerkleProofStep>,
+}
+
+impl TimestampProof {
+    pub fn verify(&self, file_hash: &sha256::Hash, tx: &BtcTransaction) -> Result<()> {
+        if hex::encode(&file_hash[..]) != self.hash {
+            return Err(AppError::Custom(format!("✘ File hash does not match the proof's hash of {}!", self.hash)))
+        };
+        let merkle_root = get_merkle_root_from_proof(*file_hash, &self.path)?;
+        if hex::encode(&merkle_root[..]) != self.merkle_root {
+            return Err(AppError::Custom("✘ Proof's path does not lead to its Merkle root!".to_string()))
+        };
+        if tx.txid().to_string() != self.txid {
+            return Err(AppError::Custom(format!("✘ Tx {} is not the proof's tx {}!", tx.txid(), self.txid)))
+        };
+        // NOTE: The root may follow a protocol prefix, as added via `--prefix`.
+        let tx_commits_to_root = tx
+            .output
+            .iter()
+            .filter_map(|output| get_op_return_payload(&output.script_pubkey))
+            .any(|payload| payload.ends_with(&merkle_root[..]));
+        match tx_commits_to_root {
+            true => Ok(()),
+            false => Err(AppError::Custom("✘ Tx has no `OP_RETURN` output committing to the Merkle root!".to_string())),
+        }
+    }
+}
+
+pub fn hash_file(path: &str) -> Result<sha256::Hash> {
+    info!("✔ Hashing file: {}", path);
+    fs::read(path)
+        .map(|bytes| sha256::Hash::hash(&bytes))
+        .map_err(|e| AppError::Custom(format!("✘ Error reading file '{}': {}", path, e)))
+}
+
+pub fn get_merkle_tree_for_files(paths: &[String]) -> Result<MerkleTree> {
+    paths
+        .iter()
+        .map(|path| hash_file(path))
+        .collect::<Result<Vec<sha256::Hash>>>()
+        .and_then(MerkleTree::new)
+}
+
+pub fn get_timestamp_proofs(paths: &[String], tree: &MerkleTree, txid: &str) -> Result<Vec<TimestampProof>> {
+    paths
+        .iter()
+        .zip(tree.leaves().iter())
+        .enumerate()
+        .map(|(i, (path, leaf))| tree.get_proof(i).map(|proof_path| TimestampProof {
+            path: proof_path,
+            file: path.clone(),
+            txid: txid.to_string(),
+            hash: hex::encode(&leaf[..]),
+            merkle_root: hex::encode(&tree.root()[..]),
+        }))
+        .collect()
+}
+
+/// Parses a single proof, or picks the one for `file_hash` out of the full output of the `timestamp` command.
+pub fn parse_timestamp_proof(json_string: &str, file_hash: &sha256::Hash) -> Result<TimestampProof> {
+    let json: JsonValue = serde_json::from_str(json_string)?;
+    match json.get("proofs") {
+        None => Ok(serde_json::from_value(json)?),
+        Some(proofs) => serde_json::from_value::<Vec<TimestampProof>>(proofs.clone())?
+            .into_iter()
+            .find(|proof| proof.hash == hex::encode(&file_hash[..]))
+            .ok_or_else(|| AppError::Custom("✘ No proof for this file's hash in the timestamp output!".to_string())),
+    }
+}
+
+pub fn get_timestamp_output_from_state(state: State, paths: &[String], tree: &MerkleTree) -> Result<String> {
+    info!("✔ Getting timestamp output from state...");
+    let tx = state.get_btc_tx()?;
+    let txid = tx.txid().to_string();
+    Ok(
+        json!({
+            "txid": txid,
+            "hex": serialize_btc_tx_to_hex(tx),
+            "merkle_root": hex::encode(&tree.root()[..]),
+            "proofs": get_timestamp_proofs(paths, tree, &txid)?,
+        }).to_string()
+    )
+}
+
+pub fn get_timestamp_verification_json_string_from_state(
+    state: State,
+    path: &str,
+    proof_path: &str,
+) -> Result<String> {
+    info!("✔ Verifying timestamp proof...");
+    let file_hash = hash_file(path)?;
+    let proof = parse_timestamp_proof(&read_from_path_or_stdin(&Some(proof_path.to_string()))?, &file_hash)?;
+    proof.verify(&file_hash, state.get_btc_tx()?)?;
+    info!("✔ Timestamp proof verified successfully!");
+    Ok(
+        json!({
+            "valid": true,
+            "file": path,
+            "txid": proof.txid,
+            "hash": proof.hash,
+            "merkle_root": proof.merkle_root,
+        }).to_string()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        op_return::OpReturnOutput,
+        test_utils::get_sample_tx,
+    };
+
+    fn get_sample_tree() -> MerkleTree {
+        MerkleTree::new(vec![sha256::Hash::hash(b"Alice"), sha256::Hash::hash(b"Bob"), sha256::Hash::hash(b"Carol")])
+            .unwrap()
+    }
+
+    fn get_sample_anchoring_tx(tree: &MerkleTree) -> BtcTransaction {
+        let mut tx = get_sample_tx();
+        tx.output.push(OpReturnOutput(tree.root()[..].to_vec()).with_magic(*b"bcff").into());
+        tx
+    }
+
+    fn get_sample_proofs(tree: &MerkleTree, tx: &BtcTransaction) -> Vec<TimestampProof> {
+        let paths = vec!["alice.txt".to_string(), "bob.txt".to_string(), "carol.txt".to_string()];
+        get_timestamp_proofs(&paths, tree, &tx.txid().to_string()).unwrap()
+    }
+
+    #[test]
+    fn should_verify_timestamp_proof_for_every_file() {
+        let tree = get_sample_tree();
+        let tx = get_sample_anchoring_tx(&tree);
+        get_sample_proofs(&tree, &tx)
+            .iter()
+            .zip(tree.leaves().iter())
+            .for_each(|(proof, leaf)| assert!(proof.verify(leaf, &tx).is_ok()));
+    }
+
+    #[test]
+    fn should_fail_to_verify_timestamp_proof_for_wrong_file() {
+        let tree = get_sample_tree();
+        let tx = get_sample_anchoring_tx(&tree);
+        let proof = &get_sample_proofs(&tree, &tx)[0];
+        assert!(proof.verify(&sha256::Hash::hash(b"Mallory"), &tx).is_err());
+        assert!(proof.verify(&tree.leaves()[1], &tx).is_err());
+    }
+
+    #[test]
+    fn should_fail_to_verify_timestamp_proof_against_wrong_tx() {
+        let tree = get_sample_tree();
+        let proof = &get_sample_proofs(&tree, &get_sample_anchoring_tx(&tree))[0];
+        assert!(proof.verify(&tree.leaves()[0], &get_sample_tx()).is_err());
+    }
+
+    #[test]
+    fn should_parse_proof_from_full_timestamp_output() {
+        let tree = get_sample_tree();
+        let tx = get_sample_anchoring_tx(&tree);
+        let proofs = get_sample_proofs(&tree, &tx);
+        let output = json!({ "txid": tx.txid().to_string(), "proofs": proofs }).to_string();
+        assert_eq!(parse_timestamp_proof(&output, &tree.leaves()[1]).unwrap(), proofs[1]);
+        let single_proof = serde_json::to_string(&proofs[2]).unwrap();
+        assert_eq!(parse_timestamp_proof(&single_proof, &tree.leaves()[2]).unwrap(), proofs[2]);
+    }
+}
@@ -0,0 +1,454 @@
+use bitcoin::blockdata::transaction::{
+    TxIn as BtcUtxo,
+    TxOut as BtcTxOut,
+    Transaction as BtcTransaction,
+};
+use crate::{
+    errors::AppError,
+    btc_private_key::BtcPrivateKey,
+    btc_tx_summary::BtcTxSummary,
+    decode_btc_tx::get_btc_tx_vsize,
+    utils::create_new_tx_output,
+    verify_btc_tx::{
+        get_dust_threshold,
+        check_btc_tx_is_valid,
+    },
+    op_return::OpReturnPolicy,
+    btc_transaction::{
+        VERSION,
+        sign_btc_tx,
+        MAX_SWEEP_ITERATIONS,
+    },
+    constants::{
+        DEFAULT_BTC_SEQUENCE,
+        MAX_BIP125_RBF_SEQUENCE,
+        MAX_LOCK_TIME_SEQUENCE,
+    },
+    types::{
+        Result,
+        BtcUtxoAndValue,
+        BtcUtxosAndValues,
+        BtcAddressAndAmount,
+        BtcAddressesAndAmounts,
+    },
+};
+
+/// What to do with any value left over once the recipients, extra outputs & fee are paid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangePolicy {
+    /// Pay any change to this address.
+    ToAddress(String),
+    /// Pay everything minus the fee to the only recipient, ignoring its amount, so there's no change.
+    Sweep,
+}
+
+/// Builds a transaction spending P2PKH UTXOs from a single key.
+///
+/// Recipients are paid first in the order they're added, then any extra outputs, then the change. Extra outputs are
+/// anything convertible into a `TxOut`, eg: an `OpReturnOutput`. Their values are funded from the inputs alongside
+/// the recipients' amounts & the fee is never taken from them.
+#[derive(Clone, Debug, Default)]
+pub struct TxBuilder {
+    rbf: bool,
+    lock_time: u32,
+    max_fee: Option<u64>,
+    max_fee_pct: Option<f64>,
+    sats_per_byte: Option<f64>,
+    utxos: Vec<BtcUtxoAndValue>,
+    extra_outputs: Vec<BtcTxOut>,
+    op_return_policy: OpReturnPolicy,
+    subtract_fee_from: Vec<usize>,
+    recipients: Vec<BtcAddressAndAmount>,
+    maybe_change_policy: Option<ChangePolicy>,
+}
+
+impl TxBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_recipient(mut self, recipient: BtcAddressAndAmount) -> Self {
+        self.recipients.push(recipient);
+        self
+    }
+
+    pub fn add_recipients(mut self, recipients: BtcAddressesAndAmounts) -> Self {
+        self.recipients.extend(recipients.0);
+        self
+    }
+
+    pub fn add_output<T: Into<BtcTxOut>>(mut self, output: T) -> Self {
+        self.extra_outputs.push(output.into());
+        self
+    }
+
+    /// Sets the UTXOs to spend, replacing any set previously. All of them are spent.
+    pub fn inputs(mut self, utxos: BtcUtxosAndValues) -> Self {
+        self.utxos = utxos.0;
+        self
+    }
+
+    pub fn fee_rate(mut self, sats_per_byte: f64) -> Self {
+        self.sats_per_byte = Some(sats_per_byte);
+        self
+    }
+
+    /// Caps the signed transaction's fee, in sats & as a percentage of the outgoing total. Unset caps aren't checked.
+    pub fn fee_caps(mut self, maybe_max_fee: Option<u64>, maybe_max_fee_pct: Option<f64>) -> Self {
+        self.max_fee = maybe_max_fee;
+        self.max_fee_pct = maybe_max_fee_pct;
+        self
+    }
+
+    /// Deducts the fee from the recipients at these indices, split in proportion to their amounts, instead of from the
+    /// change.
+    pub fn subtract_fee_from(mut self, indices: &[usize]) -> Self {
+        self.subtract_fee_from = indices.to_vec();
+        self
+    }
+
+    pub fn change_policy(mut self, change_policy: ChangePolicy) -> Self {
+        self.maybe_change_policy = Some(change_policy);
+        self
+    }
+
+    pub fn lock_time(mut self, lock_time: u32) -> Self {
+        self.lock_time = lock_time;
+        self
+    }
+
+    /// Signals that the transaction may be replaced by one paying a higher fee, as per BIP125.
+    pub fn rbf(mut self, rbf: bool) -> Self {
+        self.rbf = rbf;
+        self
+    }
+
+    /// The relay policy to check any `OP_RETURN` outputs against. Defaults to `OpReturnPolicy::Standard`.
+    pub fn op_return_policy(mut self, op_return_policy: OpReturnPolicy) -> Self {
+        self.op_return_policy = op_return_policy;
+        self
+    }
+
+    fn get_sats_per_byte(&self) -> Result<f64> {
+        self.sats_per_byte.ok_or_else(|| AppError::Custom("✘ No fee rate set in tx builder!".to_string()))
+    }
+
+    fn get_change_policy(&self) -> Result<&ChangePolicy> {
+        self.maybe_change_policy
+            .as_ref()
+            .ok_or_else(|| AppError::Custom("✘ No change policy set in tx builder!".to_string()))
+    }
+
+    fn is_sweep(&self) -> Result<bool> {
+        Ok(*self.get_change_policy()? == ChangePolicy::Sweep)
+    }
+
+    fn get_sequence(&self) -> u32 {
+        match (self.rbf, self.lock_time) {
+            (true, _) => MAX_BIP125_RBF_SEQUENCE,
+            // NOTE: A lock time is only enforced if at least one input opts out of the final sequence number.
+            (false, 0) => DEFAULT_BTC_SEQUENCE,
+            (false, _) => MAX_LOCK_TIME_SEQUENCE,
+        }
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.utxos.is_empty() {
+            return Err(AppError::Custom("✘ No UTXOs to spend in tx builder!".to_string()))
+        };
+        if self.recipients.is_empty() && self.extra_outputs.is_empty() {
+            return Err(AppError::Custom("✘ No recipients or outputs in tx builder!".to_string()))
+        };
+        if self.is_sweep()? && (self.recipients.len() != 1 || !self.subtract_fee_from.is_empty()) {
+            return Err(AppError::Custom(
+                "✘ A sweep needs exactly one recipient & cannot subtract the fee from it!".to_string()
+            ))
+        };
+        self.op_return_policy.check(&self.extra_outputs)
+    }
+
+    fn get_extra_outputs_total(&self) -> u64 {
+        self.extra_outputs.iter().map(|output| output.value).sum()
+    }
+
+    fn get_utxos_and_values(&self) -> BtcUtxosAndValues {
+        BtcUtxosAndValues::from_vec(self.utxos.clone())
+    }
+
+    fn get_inputs(&self) -> Vec<BtcUtxo> {
+        let sequence = self.get_sequence();
+        self.utxos.iter().map(|utxo_and_value| BtcUtxo { sequence, ..utxo_and_value.utxo.clone() }).collect()
+    }
+
+    fn get_tx(&self, outputs: Vec<BtcTxOut>) -> BtcTransaction {
+        BtcTransaction { output: outputs, version: VERSION, lock_time: self.lock_time, input: self.get_inputs() }
+    }
+
+    pub fn get_summary(&self) -> Result<BtcTxSummary> {
+        self.check()?;
+        match self.get_change_policy()? {
+            ChangePolicy::Sweep => BtcTxSummary::new_sweep(
+                self.get_sats_per_byte()?,
+                &self.recipients[0],
+                &self.get_utxos_and_values(),
+                &self.extra_outputs,
+            ),
+            ChangePolicy::ToAddress(change_address) => BtcTxSummary::new(
+                self.get_sats_per_byte()?,
+                &BtcAddressesAndAmounts(self.recipients.clone()),
+                change_address,
+                &self.get_utxos_and_values(),
+                &self.extra_outputs,
+                &self.subtract_fee_from,
+            ),
+        }
+    }
+
+    fn get_outputs_from_summary(&self, summary: &BtcTxSummary) -> Result<Vec<BtcTxOut>> {
+        let mut outputs = summary
+            .recipients
+            .to_vec()
+            .iter()
+            .map(create_new_tx_output)
+            .collect::<Result<Vec<BtcTxOut>>>()?;
+        outputs.extend(self.extra_outputs.clone());
+        if summary.change > 0 {
+            outputs.push(create_new_tx_output(&BtcAddressAndAmount::new(&summary.change_address, summary.change)?)?)
+        };
+        Ok(outputs)
+    }
+
+    fn get_unsigned_sweep_tx(&self, fee: u64) -> Result<BtcTransaction> {
+        let output = BtcTxOut {
+            script_pubkey: self.recipients[0].address.script_pubkey(),
+            value: self
+                .get_utxos_and_values()
+                .sum()
+                .checked_sub(fee + self.get_extra_outputs_total())
+                .ok_or_else(|| AppError::Custom("✘ Not enough UTXO value to pay the sweep tx's fee!".to_string()))?,
+        };
+        if output.value < get_dust_threshold(&output) {
+            return Err(AppError::Custom(format!("✘ Swept amount of {} sats would be dust!", output.value)))
+        };
+        let mut outputs = vec![output];
+        outputs.extend(self.extra_outputs.clone());
+        Ok(self.get_tx(outputs))
+    }
+
+    /// Builds the transaction without signing it. Each input's `script_sig` holds the `script_pubkey` it spends.
+    pub fn build_unsigned(&self) -> Result<BtcTransaction> {
+        let summary = self.get_summary()?;
+        info!("✔ {}", summary);
+        match self.is_sweep()? {
+            true => self.get_unsigned_sweep_tx(summary.fee),
+            false => self.get_outputs_from_summary(&summary).map(|outputs| self.get_tx(outputs)),
+        }
+    }
+
+    fn sign_sweep_tx_with_fee(&self, btc_private_key: &BtcPrivateKey, fee: u64) -> Result<(BtcTransaction, u64)> {
+        let tx = sign_btc_tx(self.get_unsigned_sweep_tx(fee)?, btc_private_key)?;
+        let exact_fee = (get_btc_tx_vsize(&tx) as f64 * self.get_sats_per_byte()?).ceil() as u64;
+        Ok((tx, exact_fee))
+    }
+
+    fn sign_sweep_tx(&self, btc_private_key: &BtcPrivateKey) -> Result<BtcTransaction> {
+        let mut fee = self.get_summary()?.fee;
+        let mut maybe_overpaying_tx: Option<(u64, BtcTransaction)> = None;
+        let mut min_exact_fee = fee;
+        for _ in 0..MAX_SWEEP_ITERATIONS {
+            let (tx, exact_fee) = self.sign_sweep_tx_with_fee(btc_private_key, fee)?;
+            if exact_fee == fee {
+                info!("✔ Sweeping {} sats with a fee of {} sats", tx.output[0].value, fee);
+                return Ok(tx)
+            };
+            if exact_fee < fee && maybe_overpaying_tx.as_ref().is_none_or(|(overpaid_fee, _)| fee < *overpaid_fee) {
+                maybe_overpaying_tx = Some((fee, tx));
+            };
+            min_exact_fee = min_exact_fee.min(exact_fee);
+            fee = exact_fee;
+        }
+        // NOTE: Signature lengths vary by a byte, so the fee can flip-flop between paying for the shorter & longer
+        // signature without settling. If so, the cheapest fee in between that still meets the rate for the real size
+        // is found by re-signing, which overpays by less than a byte's worth.
+        let (overpaid_fee, overpaying_tx) = maybe_overpaying_tx
+            .ok_or_else(|| AppError::Custom("✘ Could not calculate an exact fee for the sweep tx!".to_string()))?;
+        for fee in min_exact_fee + 1..overpaid_fee {
+            let (tx, exact_fee) = self.sign_sweep_tx_with_fee(btc_private_key, fee)?;
+            if exact_fee <= fee {
+                info!("✔ Sweeping {} sats with a fee of {} sats", tx.output[0].value, fee);
+                return Ok(tx)
+            };
+        }
+        info!("✔ Sweeping {} sats with a fee of {} sats", overpaying_tx.output[0].value, overpaid_fee);
+        Ok(overpaying_tx)
+    }
+
+    /// Builds & signs the transaction, then verifies it against the UTXOs it spends & checks its real fee against any
+    /// fee caps. A sweep's fee is calculated from the real size of the signed transaction.
+    pub fn sign(&self, btc_private_key: &BtcPrivateKey) -> Result<BtcTransaction> {
+        let summary = self.get_summary()?;
+        let tx = match self.is_sweep()? {
+            true => self.sign_sweep_tx(btc_private_key),
+            false => self.build_unsigned().and_then(|tx| sign_btc_tx(tx, btc_private_key)),
+        }?;
+        check_btc_tx_is_valid(&tx, &self.get_utxos_and_values(), self.op_return_policy)?;
+        summary.with_signed_tx(&tx).check_fee_caps(self.max_fee, self.max_fee_pct)?;
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::consensus::encode::serialize as btc_serialize;
+    use crate::{
+        op_return::OpReturnOutput,
+        decode_btc_tx::btc_tx_signals_rbf,
+        test_utils::{
+            get_sample_utxo,
+            get_sample_btc_private_key,
+            SAMPLE_TARGET_BTC_ADDRESS,
+        },
+    };
+
+    const SAMPLE_RECIPIENT: &str = "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM";
+
+    fn get_sample_utxo_script_pubkey() -> bitcoin::Script {
+        BtcAddressAndAmount::new(SAMPLE_TARGET_BTC_ADDRESS, 0).unwrap().address.script_pubkey()
+    }
+
+    fn get_sample_tx_builder() -> TxBuilder {
+        TxBuilder::new()
+            .add_recipient(BtcAddressAndAmount::new(SAMPLE_RECIPIENT, 5001).unwrap())
+            .inputs(BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]))
+            .change_policy(ChangePolicy::ToAddress(SAMPLE_TARGET_BTC_ADDRESS.to_string()))
+            .fee_rate(100.0)
+    }
+
+    fn get_sample_sweep_tx_builder(sats_per_byte: f64) -> TxBuilder {
+        TxBuilder::new()
+            .add_recipient(BtcAddressAndAmount::new(SAMPLE_RECIPIENT, 0).unwrap())
+            .inputs(BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]))
+            .change_policy(ChangePolicy::Sweep)
+            .fee_rate(sats_per_byte)
+    }
+
+    #[test]
+    fn should_create_tx_correctly() {
+        let expected_result = "01000000016e3fa15afcd9b579b7ed082e0ee8cfba1f27a6cf007cb7ca95b06ab0fda2880c020000006b483045022100a637a92ad011ba43f2a56a06cad8e5317c6e387097c5a0c0f4bd082908ccf47702202999e332af7a2884fb0ddf278523a0c16d162fb3065c22fd2fc6d4005b8fad85012103d2a5e3b162eb580fe2ce023cd5e0dddbb6286923acde77e3e5468314dc9373f7ffffffff0289130000000000001976a9149ae6e42c56f1ea319cfc704ad50db0683015029b88aceb2c0d00000000001976a91454102783c8640c5144d039cea53eb7dbb470081488ac00000000";
+        let result = get_sample_tx_builder().sign(&get_sample_btc_private_key()).unwrap();
+        let result_hex = hex::encode(btc_serialize(&result));
+        assert_eq!(result_hex, expected_result);
+    }
+
+    #[test]
+    fn should_build_unsigned_tx_spending_script_pubkeys() {
+        let utxo = get_sample_utxo();
+        let result = get_sample_tx_builder().build_unsigned().unwrap();
+        assert_eq!(result.input[0].script_sig, utxo.utxo.script_sig);
+        assert_eq!(result.output.len(), 2);
+        assert_eq!(result.output[0].value, 5001);
+    }
+
+    #[test]
+    fn should_add_extra_outputs_before_change() {
+        let result = get_sample_tx_builder().add_output(OpReturnOutput(vec![0xc0, 0xff, 0xee])).build_unsigned().unwrap();
+        assert_eq!(result.output.len(), 3);
+        assert!(result.output[1].script_pubkey.is_op_return());
+        assert_eq!(result.output[1].value, 0);
+    }
+
+    #[test]
+    fn should_fund_valued_extra_outputs_from_the_inputs() {
+        let utxos_and_values = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        let extra_output = BtcTxOut { value: 20_000, script_pubkey: get_sample_utxo_script_pubkey() };
+        let tx_builder = get_sample_tx_builder().add_output(extra_output.clone());
+        let summary = tx_builder.get_summary().unwrap();
+        let result = tx_builder.sign(&get_sample_btc_private_key()).unwrap();
+        let outputs_total = result.output.iter().map(|output| output.value).sum::<u64>();
+        assert_eq!(result.output[1], extra_output);
+        assert_eq!(summary.outgoing_total, 5001 + 20_000);
+        assert_eq!(utxos_and_values.sum(), outputs_total + summary.fee);
+    }
+
+    #[test]
+    fn should_fund_valued_extra_outputs_when_sweeping() {
+        let utxos_and_values = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        let extra_output = BtcTxOut { value: 20_000, script_pubkey: get_sample_utxo_script_pubkey() };
+        let result = get_sample_sweep_tx_builder(10.0)
+            .add_output(extra_output.clone())
+            .sign(&get_sample_btc_private_key())
+            .unwrap();
+        let outputs_total = result.output.iter().map(|output| output.value).sum::<u64>();
+        let fee = utxos_and_values.sum() - outputs_total;
+        assert_eq!(result.output[1], extra_output);
+        assert_eq!(fee, (get_btc_tx_vsize(&result) as f64 * 10.0).ceil() as u64);
+    }
+
+    #[test]
+    fn should_check_op_return_outputs_against_policy() {
+        let tx_builder = get_sample_tx_builder()
+            .add_output(OpReturnOutput(vec![0xc0, 0xff, 0xee]))
+            .add_output(OpReturnOutput(vec![0xde, 0xca, 0xff]));
+        assert!(tx_builder.build_unsigned().is_err());
+        let result = tx_builder.op_return_policy(OpReturnPolicy::LargeDatacarrier).build_unsigned().unwrap();
+        assert_eq!(result.output.len(), 4);
+    }
+
+    #[test]
+    fn should_set_lock_time_and_rbf() {
+        let result = get_sample_tx_builder().lock_time(1_610_000).build_unsigned().unwrap();
+        assert_eq!(result.lock_time, 1_610_000);
+        assert_eq!(result.input[0].sequence, MAX_LOCK_TIME_SEQUENCE);
+        assert!(!btc_tx_signals_rbf(&result));
+        let result = get_sample_tx_builder().rbf(true).build_unsigned().unwrap();
+        assert!(btc_tx_signals_rbf(&result));
+    }
+
+    #[test]
+    fn should_err_building_without_fee_rate() {
+        let result = TxBuilder::new()
+            .add_recipient(BtcAddressAndAmount::new(SAMPLE_RECIPIENT, 5001).unwrap())
+            .inputs(BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]))
+            .change_policy(ChangePolicy::ToAddress(SAMPLE_TARGET_BTC_ADDRESS.to_string()))
+            .build_unsigned();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_err_sweeping_to_several_recipients() {
+        let result = get_sample_sweep_tx_builder(10.0)
+            .add_recipient(BtcAddressAndAmount::new(SAMPLE_TARGET_BTC_ADDRESS, 0).unwrap())
+            .build_unsigned();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn should_create_sweep_tx_with_no_change_output() {
+        let sats_per_byte = 10.0;
+        let utxos_and_values = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        let result = get_sample_sweep_tx_builder(sats_per_byte).sign(&get_sample_btc_private_key()).unwrap();
+        let fee = utxos_and_values.sum() - result.output[0].value;
+        let vsize = get_btc_tx_vsize(&result);
+        assert_eq!(result.output.len(), 1);
+        assert!(fee >= (vsize as f64 * sats_per_byte).ceil() as u64);
+        assert!(fee < ((vsize + 1) as f64 * sats_per_byte).ceil() as u64);
+    }
+
+    #[test]
+    fn should_check_signed_tx_fee_against_fee_caps() {
+        let tx = get_sample_sweep_tx_builder(10.0).sign(&get_sample_btc_private_key()).unwrap();
+        let fee = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]).sum() - tx.output[0].value;
+        let sign_with_max_fee = |max_fee| {
+            get_sample_sweep_tx_builder(10.0).fee_caps(Some(max_fee), None).sign(&get_sample_btc_private_key())
+        };
+        assert!(sign_with_max_fee(fee).is_ok());
+        assert!(sign_with_max_fee(fee - 1).is_err());
+    }
+
+    #[test]
+    fn should_err_if_swept_amount_would_be_dust() {
+        let result = get_sample_sweep_tx_builder(4615.0).sign(&get_sample_btc_private_key());
+        assert!(result.is_err());
+    }
+}
@@ -3,17 +3,18 @@ use serde_json::{
     json,
     Value as JsonValue,
 };
-use crate::lib::{
+use crate::{
     errors::AppError,
+    btc_destination::BtcDestination,
     utils::{
         serialize_btc_utxo,
         deserialize_btc_utxo,
     },
 };
 use bitcoin::{
-    util::address::Address as BtcAddress,
     blockdata::transaction::{
         TxIn as BtcUtxo,
+        OutPoint as BtcOutPoint,
         Transaction as BtcTransaction,
     },
 };
@@ -29,12 +30,17 @@ pub struct BtcAddressesAndAmounts(pub Vec<BtcAddressAndAmount>);
 
 impl BtcAddressesAndAmounts {
     pub fn new(addresses: &[String], amounts: &[u64]) -> Result<Self> {
+        if addresses.len() != amounts.len() {
+            return Err(AppError::Custom(
+                format!("✘ Got {} address(es) but {} amount(s)!", addresses.len(), amounts.len())
+            ))
+        };
         Ok(
             BtcAddressesAndAmounts(
                 addresses
                     .iter()
-                    .enumerate()
-                    .map(|(i, address)| BtcAddressAndAmount::new(address, amounts[i]))
+                    .zip(amounts.iter())
+                    .map(|(address, amount)| BtcAddressAndAmount::new(address, *amount))
                     .collect::<Result<Vec<BtcAddressAndAmount>>>()?
             )
         )
@@ -51,17 +57,26 @@ impl BtcAddressesAndAmounts {
     pub fn len(&self) -> usize {
         self.0.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BtcAddressAndAmount {
     pub amount: u64,
-    pub address: BtcAddress,
+    pub address: BtcDestination,
+    pub label: Option<String>,
 }
 
 impl BtcAddressAndAmount {
     pub fn new(address: &str, amount: u64) -> Result<Self> {
-        Ok(BtcAddressAndAmount { amount, address: BtcAddress::from_str(&address)? })
+        Ok(BtcAddressAndAmount { amount, label: None, address: BtcDestination::from_str(address)? })
+    }
+
+    pub fn with_label(self, label: Option<String>) -> Self {
+        BtcAddressAndAmount { label, ..self }
     }
 }
 
@@ -70,6 +85,30 @@ pub struct UtxoInfo {
     pub vout: u32,
     pub value: u64,
     pub txid: String,
+    #[serde(default)]
+    pub status: UtxoStatus,
+}
+
+impl UtxoInfo {
+    pub fn get_confirmations(&self, tip_height: u64) -> u64 {
+        self.status.get_confirmations(tip_height)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct UtxoStatus {
+    pub confirmed: bool,
+    pub block_time: Option<u64>,
+    pub block_height: Option<u64>,
+}
+
+impl UtxoStatus {
+    pub fn get_confirmations(&self, tip_height: u64) -> u64 {
+        match (self.confirmed, self.block_height) {
+            (true, Some(block_height)) if tip_height >= block_height => tip_height - block_height + 1,
+            _ => 0,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -88,6 +127,10 @@ impl BtcUtxosAndValues {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn get_utxos(&self) -> Vec<BtcUtxo> {
         self.to_vec().iter().map(|x| x.utxo.clone()).collect()
     }
@@ -96,6 +139,10 @@ impl BtcUtxosAndValues {
         self.to_vec().iter().map(|x| x.value).sum()
     }
 
+    pub fn get_utxo_and_value_for_outpoint(&self, outpoint: &BtcOutPoint) -> Option<&BtcUtxoAndValue> {
+        self.0.iter().find(|x| x.utxo.previous_output == *outpoint)
+    }
+
     pub fn from_json(json: &str) -> Result<Self> {
         let strings: Vec<JsonValue> = serde_json::from_str(json)?;
         Ok(
@@ -107,10 +154,6 @@ impl BtcUtxosAndValues {
                 )
         )
     }
-
-    pub fn to_json(&self) -> Result<String> {
-        Ok(serde_json::to_string(&self.0.iter().map(BtcUtxoAndValue::to_json_value).collect::<Vec<JsonValue>>())?)
-    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -129,7 +172,7 @@ impl BtcUtxoAndValue {
         pub struct IntermediateJson {
             pub value: u64,
             pub serialized_utxo: String,
-        };
+        }
         let json: IntermediateJson = serde_json::from_str(json)?;
         Ok(Self::new(json.value, &deserialize_btc_utxo(&hex::decode(json.serialized_utxo)?)?))
     }
@@ -137,7 +180,7 @@ impl BtcUtxoAndValue {
     pub fn to_json_value(&self) -> JsonValue {
         json!({
             "value": self.value,
-            "serialized_utxo": hex::encode(&serialize_btc_utxo(&self.utxo)),
+            "serialized_utxo": hex::encode(serialize_btc_utxo(&self.utxo)),
         })
     }
 
@@ -160,11 +203,44 @@ impl BtcUtxoAndValue {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::lib::test_utils::{
+    use crate::test_utils::{
         get_sample_utxo,
         SAMPLE_UTXO_JSON_STRING,
     };
 
+    #[test]
+    fn should_get_btc_addresses_and_amounts() {
+        let addresses = vec![
+            "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM".to_string(),
+            "mwqqpopcLxEFUCPGGPV7zpfm3xrSAjRcyq".to_string(),
+        ];
+        let result = BtcAddressesAndAmounts::new(&addresses, &[666, 1337]).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.sum(), 2003);
+    }
+
+    #[test]
+    fn should_err_if_number_of_addresses_and_amounts_differ() {
+        let addresses = vec!["mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM".to_string()];
+        assert!(BtcAddressesAndAmounts::new(&addresses, &[]).is_err());
+        assert!(BtcAddressesAndAmounts::new(&addresses, &[666, 1337]).is_err());
+    }
+
+    #[test]
+    fn should_get_confirmations_from_utxo_info() {
+        let json = "{\"txid\":\"85f8faf4a3da404a833d0a21b1cea215da74a4b2c1ce8187cbf6379f42c02924\",\"vout\":0,\"value\":1666,\"status\":{\"confirmed\":true,\"block_height\":1000}}";
+        let utxo_info: UtxoInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(utxo_info.get_confirmations(1000), 1);
+        assert_eq!(utxo_info.get_confirmations(1005), 6);
+    }
+
+    #[test]
+    fn should_have_no_confirmations_if_utxo_info_unconfirmed() {
+        let json = "{\"txid\":\"85f8faf4a3da404a833d0a21b1cea215da74a4b2c1ce8187cbf6379f42c02924\",\"vout\":0,\"value\":1666,\"status\":{\"confirmed\":false}}";
+        let utxo_info: UtxoInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(utxo_info.get_confirmations(1000), 0);
+    }
+
     #[test]
     fn should_get_btc_utxo_and_value_from_json() {
         if let Err(e) = BtcUtxoAndValue::from_json(SAMPLE_UTXO_JSON_STRING) {
@@ -181,12 +257,9 @@ mod tests {
     }
 
     #[test]
-    fn should_make_btc_utxo_and_values_serde_json_round_trip_correctly() {
-        let utxo_1 = get_sample_utxo();
-        let utxo_2 = utxo_1.clone();
-        let utxos = BtcUtxosAndValues(vec![utxo_1, utxo_2]);
-        let json = utxos.to_json().unwrap();
+    fn should_get_btc_utxos_and_values_from_json() {
+        let json = format!("[{},{}]", SAMPLE_UTXO_JSON_STRING, SAMPLE_UTXO_JSON_STRING);
         let result = BtcUtxosAndValues::from_json(&json).unwrap();
-        assert_eq!(utxos, result);
+        assert_eq!(result, BtcUtxosAndValues(vec![get_sample_utxo(), get_sample_utxo()]));
     }
 }
@@ -1,11 +1,16 @@
+use std::io::Read;
 use secp256k1::SecretKey;
 use rand::{
     RngCore,
     thread_rng,
 };
-use crate::lib::{
+use crate::{
     state::State,
     errors::AppError,
+    constants::{
+        ONE_BTC,
+        DEFAULT_CHANGE_ADDRESS,
+    },
     types::{
         Byte,
         Bytes,
@@ -24,26 +29,23 @@ use bitcoin::{
             Transaction as BtcTransaction,
         },
         script::{
+            Instruction,
             Script as BtcScript,
             Builder as BtcScriptBuilder,
         },
     },
 };
 
-pub fn get_change_address_from_cli_args_in_state(state: &State) -> Result<String> {
-    info!("✔ Getting change-address from CLI args in state...");
-    match &state.cli_args.flag_change[..] {
-        "signer" => Ok(state.get_btc_address()?),
-        _ => Ok(state.cli_args.flag_change.clone())
+pub fn get_change_address_from_state(state: &State) -> Result<String> {
+    info!("✔ Getting change-address from state...");
+    match &state.change[..] {
+        DEFAULT_CHANGE_ADDRESS => Ok(state.get_btc_address()?),
+        change => Ok(change.to_string())
     }
 }
 
 pub fn serialize_btc_tx_to_hex(tx: &BtcTransaction) -> String {
-    hex::encode(&btc_serialize(tx))
-}
-
-pub fn serialize_tx_in_state(state: State) -> Result<String> {
-    state.get_btc_tx().map(serialize_btc_tx_to_hex)
+    hex::encode(btc_serialize(tx))
 }
 
 pub fn make_api_call(url: &str, error_message: &str) -> Result<String> {
@@ -68,8 +70,8 @@ pub fn create_new_tx_output(address_and_amount: &BtcAddressAndAmount) -> Result<
 
 pub fn get_script_sig<'a>(signature_slice: &'a[u8], utxo_spender_pub_key_slice: &'a[u8]) -> BtcScript {
     BtcScriptBuilder::new()
-        .push_slice(&signature_slice)
-        .push_slice(&utxo_spender_pub_key_slice)
+        .push_slice(signature_slice)
+        .push_slice(utxo_spender_pub_key_slice)
         .into_script()
 }
 
@@ -78,8 +80,25 @@ pub fn calculate_btc_tx_size(num_inputs: usize, num_outputs: usize) -> u64 {
     ((num_inputs * 148) + (num_outputs * 34) + 10 + num_inputs) as u64
 }
 
-pub fn calculate_btc_tx_fee(num_inputs: usize, num_outputs: usize, sats_per_byte: usize) -> u64 {
-    calculate_btc_tx_size(num_inputs, num_outputs) * sats_per_byte as u64
+pub fn get_tx_output_size(output: &BtcTxOut) -> u64 {
+    (8 + get_var_int_size(output.script_pubkey.len()) + output.script_pubkey.len()) as u64
+}
+
+// NOTE: As above, but sizes each output by its actual script rather than assuming they're all P2PKH.
+pub fn calculate_btc_tx_size_for_outputs(num_inputs: usize, outputs: &[BtcTxOut]) -> u64 {
+    calculate_btc_tx_size(num_inputs, 0) + outputs.iter().map(get_tx_output_size).sum::<u64>()
+}
+
+pub fn calculate_btc_tx_fee(num_inputs: usize, num_outputs: usize, sats_per_byte: f64) -> u64 {
+    (calculate_btc_tx_size(num_inputs, num_outputs) as f64 * sats_per_byte).ceil() as u64
+}
+
+pub fn calculate_btc_tx_fee_for_outputs(num_inputs: usize, outputs: &[BtcTxOut], sats_per_byte: f64) -> u64 {
+    (calculate_btc_tx_size_for_outputs(num_inputs, outputs) as f64 * sats_per_byte).ceil() as u64
+}
+
+pub fn format_sats(sats: u64) -> String {
+    format!("{:.8} BTC ({} sats)", sats as f64 / ONE_BTC, sats)
 }
 
 pub fn serialize_btc_utxo(btc_utxo: &BtcUtxo) -> Bytes {
@@ -98,6 +117,40 @@ fn get_op_return_script(op_return_bytes: &[Byte]) -> Result<BtcScript> {
     Ok(BtcScriptBuilder::new().push_opcode(opcodes::all::OP_RETURN).push_slice(op_return_bytes).into_script())
 }
 
+pub fn get_op_return_payload(script: &BtcScript) -> Option<Bytes> {
+    match script.is_op_return() {
+        false => None,
+        true => Some(
+            script
+                .iter(false)
+                .filter_map(|instruction| match instruction {
+                    Instruction::PushBytes(bytes) => Some(bytes.to_vec()),
+                    _ => None,
+                })
+                .flatten()
+                .collect()
+        ),
+    }
+}
+
+pub fn get_var_int_size(num: usize) -> usize {
+    match num {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x10000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+pub fn is_witness_program(script: &BtcScript) -> bool {
+    let bytes = script.as_bytes();
+    let version_is_valid = |byte: u8| {
+        byte == opcodes::all::OP_PUSHBYTES_0.into_u8() ||
+        (byte >= opcodes::all::OP_PUSHNUM_1.into_u8() && byte <= opcodes::all::OP_PUSHNUM_16.into_u8())
+    };
+    bytes.len() >= 4 && bytes.len() <= 42 && version_is_valid(bytes[0]) && bytes[1] as usize == bytes.len() - 2
+}
+
 pub fn bytes_to_utf8_str(bytes: &[Byte]) -> Result<String> {
     Ok(std::str::from_utf8(bytes)?.to_string())
 }
@@ -110,6 +163,21 @@ pub fn convert_bytes_to_string_with_no_new_lines(bytes: &[Byte]) -> Result<Strin
     bytes_to_utf8_str(bytes).map(strip_new_lines_from_str)
 }
 
+pub fn read_from_path_or_stdin(maybe_path: &Option<String>) -> Result<String> {
+    match maybe_path.as_deref() {
+        None | Some("-") => {
+            info!("✔ Reading input from stdin...");
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            Ok(input)
+        },
+        Some(path) => {
+            info!("✔ Reading input from path: {}", path);
+            Ok(std::fs::read_to_string(path)?)
+        },
+    }
+}
+
 pub fn file_exists(path: &str) -> bool {
     std::path::Path::new(path).is_file()
 }
@@ -1,16 +1,59 @@
-use crate::lib::{
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
+};
+use crate::{
     state::State,
-    types::{
-        Result,
-        BtcUtxosAndValues,
+    backend::BtcBackend,
+    output_format::OutputFormat,
+    address_info::get_address_from_state,
+    coin_control::maybe_apply_coin_control,
+    utxo_file::{
+        UtxoFileMetadata,
+        parse_utxo_file,
+        get_utxo_file_json_value,
     },
+    utxo_filters::filter_btc_utxos_and_values,
+    types::Result,
 };
 
 pub fn get_utxos_from_utxo_json_string_and_add_to_state(state: State) -> Result<State> {
-    BtcUtxosAndValues::from_json(state.get_utxo_json_string()?)
+    let utxo_file = parse_utxo_file(state.get_utxo_json_string()?)?;
+    utxo_file.check_is_verified(state.allow_unverified_utxos)?;
+    utxo_file.check_network(state.network)?;
+    utxo_file.check_address(&state.get_btc_address()?)?;
+    filter_btc_utxos_and_values(utxo_file.utxos, &state.utxo_filters)
+        .and_then(|utxos| maybe_apply_coin_control(utxos, &state))
         .and_then(|x| state.add_btc_utxos_and_values(x))
 }
 
+pub fn maybe_get_utxos_from_utxo_json_string_and_add_to_state(state: State) -> Result<State> {
+    match state.utxo_json_string {
+        Some(_) => {
+            let utxo_file = parse_utxo_file(state.get_utxo_json_string()?)?;
+            utxo_file.check_network(state.network)?;
+            state.add_btc_utxos_and_values(utxo_file.utxos)
+        },
+        None => Ok(state),
+    }
+}
+
+fn get_utxo_file_metadata_from_state(state: &State) -> Result<UtxoFileMetadata> {
+    Ok(
+        UtxoFileMetadata {
+            network: state.network,
+            address: get_address_from_state(state)?,
+            block_height: state.backend.get_tip_height()?,
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        }
+    )
+}
+
 pub fn get_utxo_json_string_from_utxos_in_state(state: State) -> Result<String> {
-    state.get_btc_utxos_and_values()?.to_json()
+    let utxos = state.get_btc_utxos_and_values()?;
+    let mut utxo_file = get_utxo_file_json_value(utxos, state.get_btc_txs()?, &get_utxo_file_metadata_from_state(&state)?)?;
+    if let OutputFormat::Json = state.output_format {
+        utxo_file["total"] = utxos.sum().into();
+    };
+    Ok(utxo_file.to_string())
 }
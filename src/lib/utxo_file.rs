@@ -0,0 +1,431 @@
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use bitcoin::{
+    hashes::{
+        Hash,
+        sha256,
+    },
+    network::constants::Network as BtcNetwork,
+    blockdata::{
+        script::Script as BtcScript,
+        transaction::Transaction as BtcTransaction,
+    },
+};
+use crate::{
+    errors::AppError,
+    get_btc_txs_from_utxos_info::convert_hex_tx_to_btc_tx,
+    utils::{
+        is_witness_program,
+        deserialize_btc_utxo,
+        serialize_btc_tx_to_hex,
+    },
+    types::{
+        Result,
+        BtcUtxoAndValue,
+        BtcUtxosAndValues,
+    },
+};
+
+pub const UTXO_FILE_VERSION: u64 = 3;
+pub const UTXO_FILE_VERSION_WITHOUT_ENVELOPE: u64 = 2;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+struct UtxoFileEntryJson {
+    pub value: u64,
+    pub serialized_utxo: String,
+    pub previous_tx: Option<String>,
+    pub script_pubkey: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+struct UtxoFileJson {
+    pub version: u64,
+    pub utxos: Vec<JsonValue>,
+    pub checksum: Option<String>,
+    pub network: Option<String>,
+    pub address: Option<String>,
+    pub created_at: Option<u64>,
+    pub block_height: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UtxoFileMetadata {
+    pub address: String,
+    pub created_at: u64,
+    pub block_height: u64,
+    pub network: BtcNetwork,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UtxoFile {
+    pub version: u64,
+    pub utxos: BtcUtxosAndValues,
+    pub maybe_metadata: Option<UtxoFileMetadata>,
+}
+
+impl UtxoFile {
+    /// Older versions of the file can't be checked against the signing key's network & address, nor v1 files'
+    /// UTXO values against their previous txs, so signing with one needs the user's explicit say-so.
+    pub fn check_is_verified(&self, allow_unverified_utxos: bool) -> Result<()> {
+        match self.maybe_metadata.is_none() && !allow_unverified_utxos {
+            true => Err(AppError::Custom(format!(
+                "✘ Version {} UTXO file can't be checked against the key's network & address, {}!",
+                self.version,
+                "use `--allowUnverifiedUtxos` to sign with it anyway or re-run `getUtxos` to make a new one",
+            ))),
+            false => Ok(()),
+        }
+    }
+
+    pub fn check_network(&self, network: BtcNetwork) -> Result<()> {
+        match &self.maybe_metadata {
+            Some(metadata) if metadata.network != network => Err(AppError::Custom(
+                format!("✘ UTXO file is for network '{}' but signing for network '{}'!", metadata.network, network)
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_address(&self, address: &str) -> Result<()> {
+        match &self.maybe_metadata {
+            Some(metadata) if metadata.address != address => Err(AppError::Custom(
+                format!("✘ UTXO file is for address '{}' but the key's address is '{}'!", metadata.address, address)
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The SHA256 hash of the whole envelope bar its `checksum`, to catch a file that's been corrupted or mistyped while
+/// being carried to the offline machine, metadata included.
+///
+/// NOTE: The hash is unkeyed, so anyone editing the file can recompute it ∴ it's no defence against tampering.
+///
+/// NOTE: `serde_json` sorts object keys, so the checksum is stable however the file's whitespace is edited.
+fn get_envelope_checksum(envelope: &JsonValue) -> String {
+    let mut envelope = envelope.clone();
+    if let Some(fields) = envelope.as_object_mut() {
+        fields.remove("checksum");
+    };
+    sha256::Hash::hash(envelope.to_string().as_bytes()).to_string()
+}
+
+fn parse_network(network: &str) -> Result<BtcNetwork> {
+    network.parse().map_err(|_| AppError::Custom(format!("✘ Not a valid network in UTXO file: '{}'", network)))
+}
+
+fn get_utxo_file_metadata(utxo_file: &UtxoFileJson, envelope: &JsonValue) -> Result<UtxoFileMetadata> {
+    let get_missing_field_err = |field: &str| AppError::Custom(format!("✘ UTXO file envelope is missing its `{}`!", field));
+    let checksum = utxo_file.checksum.as_ref().ok_or_else(|| get_missing_field_err("checksum"))?;
+    if *checksum != get_envelope_checksum(envelope) {
+        return Err(AppError::Custom("✘ UTXO file checksum mismatch ∴ the file is corrupt or was edited!".to_string()))
+    };
+    Ok(
+        UtxoFileMetadata {
+            network: parse_network(utxo_file.network.as_ref().ok_or_else(|| get_missing_field_err("network"))?)?,
+            address: utxo_file.address.clone().ok_or_else(|| get_missing_field_err("address"))?,
+            created_at: utxo_file.created_at.ok_or_else(|| get_missing_field_err("created_at"))?,
+            block_height: utxo_file.block_height.ok_or_else(|| get_missing_field_err("block_height"))?,
+        }
+    )
+}
+
+fn verify_utxo_against_previous_tx(utxo: &BtcUtxoAndValue, previous_tx: &BtcTransaction) -> Result<()> {
+    let outpoint = utxo.utxo.previous_output;
+    if previous_tx.txid() != outpoint.txid {
+        return Err(AppError::Custom(format!("✘ Previous tx {} does not match UTXO {}!", previous_tx.txid(), outpoint)))
+    };
+    let output = previous_tx
+        .output
+        .get(outpoint.vout as usize)
+        .ok_or_else(|| AppError::Custom(format!("✘ Previous tx has no output for UTXO {}!", outpoint)))?;
+    if output.value != utxo.value {
+        return Err(AppError::Custom(
+            format!(
+                "✘ UTXO {} claims a value of {} sats but its previous tx output is worth {} sats!",
+                outpoint,
+                utxo.value,
+                output.value,
+            )
+        ))
+    };
+    verify_utxo_against_script_pubkey(utxo, &output.script_pubkey)
+}
+
+// NOTE: The unsigned UTXO's `script_sig` holds the `script_pubkey` of the output it spends.
+fn verify_utxo_against_script_pubkey(utxo: &BtcUtxoAndValue, script_pubkey: &BtcScript) -> Result<()> {
+    match utxo.utxo.script_sig == *script_pubkey {
+        true => Ok(()),
+        false => Err(AppError::Custom(
+            format!("✘ UTXO {}'s script does not match the `script_pubkey` it spends!", utxo.utxo.previous_output)
+        )),
+    }
+}
+
+fn parse_utxo_file_entry(entry: &UtxoFileEntryJson) -> Result<BtcUtxoAndValue> {
+    let utxo = BtcUtxoAndValue::new(entry.value, &deserialize_btc_utxo(&hex::decode(&entry.serialized_utxo)?)?);
+    if let Some(previous_tx_hex) = &entry.previous_tx {
+        verify_utxo_against_previous_tx(&utxo, &convert_hex_tx_to_btc_tx(previous_tx_hex)?)?;
+    };
+    match &entry.script_pubkey {
+        Some(script_pubkey_hex) => {
+            let script_pubkey = BtcScript::from(hex::decode(script_pubkey_hex)?);
+            verify_utxo_against_script_pubkey(&utxo, &script_pubkey)?;
+            // NOTE: A legacy sighash doesn't commit to the value spent, so only segwit inputs may skip the previous tx.
+            if entry.previous_tx.is_none() && !is_witness_program(&script_pubkey) {
+                return Err(AppError::Custom(
+                    format!("✘ Legacy UTXO {} needs its full previous tx!", utxo.utxo.previous_output)
+                ))
+            };
+        },
+        None if entry.previous_tx.is_none() => return Err(AppError::Custom(
+            format!("✘ UTXO {} has neither a previous tx nor a `script_pubkey`!", utxo.utxo.previous_output)
+        )),
+        None => (),
+    };
+    Ok(utxo)
+}
+
+pub fn parse_utxo_file(json: &str) -> Result<UtxoFile> {
+    info!("✔ Parsing UTXO file...");
+    let json_value: JsonValue = serde_json::from_str(json)?;
+    if let JsonValue::Array(_) = json_value {
+        warn!("✘ Version 1 UTXO file ∴ skipping the checks of its UTXO values, network & address!");
+        return Ok(UtxoFile { version: 1, utxos: BtcUtxosAndValues::from_json(json)?, maybe_metadata: None })
+    };
+    let utxo_file: UtxoFileJson = serde_json::from_value(json_value.clone())?;
+    let maybe_metadata = match utxo_file.version {
+        UTXO_FILE_VERSION => Some(get_utxo_file_metadata(&utxo_file, &json_value)?),
+        UTXO_FILE_VERSION_WITHOUT_ENVELOPE => {
+            warn!("✘ Version {} UTXO file ∴ skipping the checks of its network & address!", utxo_file.version);
+            None
+        },
+        _ => return Err(AppError::Custom(format!("✘ Unsupported UTXO file version: {}", utxo_file.version))),
+    };
+    info!("✔ Version {} UTXO file ∴ verifying UTXO values...", utxo_file.version);
+    Ok(
+        UtxoFile {
+            maybe_metadata,
+            version: utxo_file.version,
+            utxos: utxo_file
+                .utxos
+                .into_iter()
+                .map(|entry| parse_utxo_file_entry(&serde_json::from_value(entry)?))
+                .collect::<Result<Vec<BtcUtxoAndValue>>>()
+                .map(BtcUtxosAndValues::from_vec)?,
+        }
+    )
+}
+
+fn get_utxo_file_entry_json_value(utxo: &BtcUtxoAndValue, previous_txs: &[BtcTransaction]) -> Result<JsonValue> {
+    let mut json_value = utxo.to_json_value();
+    match is_witness_program(&utxo.utxo.script_sig) {
+        true => json_value["script_pubkey"] = json!(hex::encode(utxo.utxo.script_sig.as_bytes())),
+        false => {
+            let previous_tx = previous_txs
+                .iter()
+                .find(|tx| tx.txid() == utxo.utxo.previous_output.txid)
+                .ok_or_else(|| AppError::Custom(format!("✘ No previous tx for UTXO {}!", utxo.utxo.previous_output)))?;
+            json_value["previous_tx"] = json!(serialize_btc_tx_to_hex(previous_tx));
+        },
+    };
+    Ok(json_value)
+}
+
+pub fn get_utxo_file_json_value(
+    utxos: &BtcUtxosAndValues,
+    previous_txs: &[BtcTransaction],
+    metadata: &UtxoFileMetadata,
+) -> Result<JsonValue> {
+    let utxos_json = utxos
+        .to_vec()
+        .iter()
+        .map(|utxo| get_utxo_file_entry_json_value(utxo, previous_txs))
+        .collect::<Result<Vec<JsonValue>>>()?;
+    let mut envelope = json!({
+        "version": UTXO_FILE_VERSION,
+        "address": metadata.address,
+        "created_at": metadata.created_at,
+        "block_height": metadata.block_height,
+        "network": metadata.network.to_string(),
+        "utxos": utxos_json,
+    });
+    envelope["checksum"] = json!(get_envelope_checksum(&envelope));
+    Ok(envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        extract_utxos_from_utxo_info::create_btc_utxo_and_value_from_tx_output,
+        test_utils::{
+            get_sample_tx,
+            get_sample_utxo,
+            get_sample_tx_hex,
+            SAMPLE_UTXO_INDEX,
+            SAMPLE_UTXO_JSON_STRING,
+            SAMPLE_TARGET_BTC_ADDRESS,
+        },
+    };
+
+    fn get_sample_v2_entry(value: u64) -> JsonValue {
+        let mut entry = create_btc_utxo_and_value_from_tx_output(&get_sample_tx(), SAMPLE_UTXO_INDEX).to_json_value();
+        entry["value"] = json!(value);
+        entry["previous_tx"] = json!(get_sample_tx_hex());
+        entry
+    }
+
+    fn get_sample_segwit_utxo() -> BtcUtxoAndValue {
+        let mut utxo = get_sample_utxo();
+        utxo.utxo.script_sig = BtcScript::from(hex::decode("001454102783c8640c5144d039cea53eb7dbb4700814").unwrap());
+        utxo
+    }
+
+    fn get_sample_metadata() -> UtxoFileMetadata {
+        UtxoFileMetadata {
+            block_height: 1_600_000,
+            created_at: 1_570_000_000,
+            network: BtcNetwork::Testnet,
+            address: SAMPLE_TARGET_BTC_ADDRESS.to_string(),
+        }
+    }
+
+    fn get_sample_v3_utxo_file_json() -> JsonValue {
+        let utxo = create_btc_utxo_and_value_from_tx_output(&get_sample_tx(), SAMPLE_UTXO_INDEX);
+        get_utxo_file_json_value(&BtcUtxosAndValues::from_vec(vec![utxo]), &[get_sample_tx()], &get_sample_metadata())
+            .unwrap()
+    }
+
+    #[test]
+    fn should_parse_v1_utxo_file() {
+        let json = format!("[{}]", SAMPLE_UTXO_JSON_STRING);
+        let result = parse_utxo_file(&json).unwrap();
+        assert_eq!(result.utxos, BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]));
+        assert_eq!(result.maybe_metadata, None);
+    }
+
+    #[test]
+    fn should_only_sign_with_v1_and_v2_utxo_files_if_allowed() {
+        let v1_json = format!("[{}]", SAMPLE_UTXO_JSON_STRING);
+        let v2_json = json!({ "version": 2, "utxos": [get_sample_v2_entry(1666)] }).to_string();
+        [v1_json, v2_json].iter().for_each(|json| {
+            let utxo_file = parse_utxo_file(json).unwrap();
+            assert!(utxo_file.check_is_verified(false).is_err());
+            assert!(utxo_file.check_is_verified(true).is_ok());
+        });
+        let v3_utxo_file = parse_utxo_file(&get_sample_v3_utxo_file_json().to_string()).unwrap();
+        assert!(v3_utxo_file.check_is_verified(false).is_ok());
+    }
+
+    #[test]
+    fn should_parse_v2_utxo_file_with_previous_tx() {
+        let json = json!({ "version": 2, "utxos": [get_sample_v2_entry(1666)] }).to_string();
+        let result = parse_utxo_file(&json).unwrap();
+        assert_eq!(result.utxos.len(), 1);
+        assert_eq!(result.utxos.sum(), 1666);
+    }
+
+    #[test]
+    fn should_err_if_v2_utxo_value_does_not_match_previous_tx() {
+        let json = json!({ "version": 2, "utxos": [get_sample_v2_entry(1_666_000)] }).to_string();
+        assert!(parse_utxo_file(&json).is_err());
+    }
+
+    #[test]
+    fn should_err_if_previous_tx_does_not_match_outpoint() {
+        let mut entry = get_sample_utxo().to_json_value();
+        entry["previous_tx"] = json!(get_sample_tx_hex());
+        let json = json!({ "version": 2, "utxos": [entry] }).to_string();
+        assert!(parse_utxo_file(&json).is_err());
+    }
+
+    #[test]
+    fn should_err_if_legacy_utxo_has_no_previous_tx() {
+        let utxo = get_sample_utxo();
+        let mut entry = utxo.to_json_value();
+        entry["script_pubkey"] = json!(hex::encode(utxo.utxo.script_sig.as_bytes()));
+        let json = json!({ "version": 2, "utxos": [entry] }).to_string();
+        assert!(parse_utxo_file(&json).is_err());
+    }
+
+    #[test]
+    fn should_parse_segwit_utxo_with_script_pubkey() {
+        let utxo = get_sample_segwit_utxo();
+        let utxos = BtcUtxosAndValues::from_vec(vec![utxo]);
+        let json = get_utxo_file_json_value(&utxos, &[], &get_sample_metadata()).unwrap().to_string();
+        assert_eq!(parse_utxo_file(&json).unwrap().utxos, utxos);
+    }
+
+    #[test]
+    fn should_err_on_unsupported_utxo_file_version() {
+        let json = json!({ "version": 99, "utxos": [] }).to_string();
+        assert!(parse_utxo_file(&json).is_err());
+    }
+
+    #[test]
+    fn should_make_v3_utxo_file_round_trip() {
+        let json = get_sample_v3_utxo_file_json();
+        let result = parse_utxo_file(&json.to_string()).unwrap();
+        assert_eq!(json["version"], 3);
+        assert_eq!(json["network"], "testnet");
+        assert_eq!(result.utxos.sum(), 1666);
+        assert_eq!(result.maybe_metadata, Some(get_sample_metadata()));
+    }
+
+    #[test]
+    fn should_make_utxo_file_with_legacy_and_segwit_utxos_round_trip() {
+        let utxos = BtcUtxosAndValues::from_vec(vec![
+            create_btc_utxo_and_value_from_tx_output(&get_sample_tx(), SAMPLE_UTXO_INDEX),
+            get_sample_segwit_utxo(),
+        ]);
+        let json = get_utxo_file_json_value(&utxos, &[get_sample_tx()], &get_sample_metadata()).unwrap();
+        assert_eq!(parse_utxo_file(&json.to_string()).unwrap().utxos, utxos);
+    }
+
+    #[test]
+    fn should_err_if_v3_utxo_file_checksum_does_not_match() {
+        let mut json = get_sample_v3_utxo_file_json();
+        json["utxos"][0]["value"] = json!(1667);
+        assert!(parse_utxo_file(&json.to_string()).is_err());
+    }
+
+    #[test]
+    fn should_err_if_v3_utxo_file_metadata_is_edited() {
+        let edited_fields = vec![
+            ("network", json!("bitcoin")),
+            ("address", json!("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM")),
+            ("block_height", json!(1_600_001)),
+            ("created_at", json!(1_570_000_001)),
+        ];
+        edited_fields.into_iter().for_each(|(field, value)| {
+            let mut json = get_sample_v3_utxo_file_json();
+            json[field] = value;
+            assert!(parse_utxo_file(&json.to_string()).is_err(), "Edited `{}` was accepted!", field);
+        });
+    }
+
+    #[test]
+    fn should_err_if_v3_utxo_file_is_missing_envelope_fields() {
+        let mut json = get_sample_v3_utxo_file_json();
+        json.as_object_mut().unwrap().remove("network");
+        assert!(parse_utxo_file(&json.to_string()).is_err());
+    }
+
+    #[test]
+    fn should_check_utxo_file_network_and_address() {
+        let result = parse_utxo_file(&get_sample_v3_utxo_file_json().to_string()).unwrap();
+        assert!(result.check_network(BtcNetwork::Testnet).is_ok());
+        assert!(result.check_network(BtcNetwork::Bitcoin).is_err());
+        assert!(result.check_address(SAMPLE_TARGET_BTC_ADDRESS).is_ok());
+        assert!(result.check_address("mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM").is_err());
+    }
+
+    #[test]
+    fn should_err_making_utxo_file_without_previous_tx() {
+        let utxos = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        assert!(get_utxo_file_json_value(&utxos, &[get_sample_tx()], &get_sample_metadata()).is_err());
+    }
+}
@@ -0,0 +1,268 @@
+use bitcoin::blockdata::transaction::OutPoint as BtcOutPoint;
+use crate::{
+    errors::AppError,
+    backend::BtcBackend,
+    btc_amount::parse_btc_amount,
+    types::{
+        Result,
+        UtxoInfo,
+        UtxosInfo,
+        BtcUtxosAndValues,
+    },
+};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UtxoFilters {
+    pub min_conf: Option<u64>,
+    pub min_value: Option<u64>,
+    pub max_value: Option<u64>,
+    pub include: Vec<BtcOutPoint>,
+    pub exclude: Vec<BtcOutPoint>,
+}
+
+pub fn parse_outpoint(outpoint: &str) -> Result<BtcOutPoint> {
+    let err = || AppError::Custom(format!("✘ Not a valid outpoint: '{}' - use the format <txid>:<vout>", outpoint));
+    let mut parts = outpoint.trim().splitn(2, ':');
+    let txid = parts.next().ok_or_else(err)?.parse().map_err(|_| err())?;
+    let vout = parts.next().ok_or_else(err)?.parse::<u32>().map_err(|_| err())?;
+    Ok(BtcOutPoint { txid, vout })
+}
+
+pub fn parse_outpoints(outpoints: &[String]) -> Result<Vec<BtcOutPoint>> {
+    outpoints.iter().map(|outpoint| parse_outpoint(outpoint)).collect()
+}
+
+fn maybe_parse_btc_amount(maybe_amount: &Option<String>) -> Result<Option<u64>> {
+    maybe_amount.as_ref().map(|amount| parse_btc_amount(amount)).transpose()
+}
+
+impl UtxoFilters {
+    pub fn parse(
+        min_conf: Option<u64>,
+        min_value: &Option<String>,
+        max_value: &Option<String>,
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Self> {
+        info!("✔ Parsing UTXO filters...");
+        let filters = UtxoFilters {
+            min_conf,
+            min_value: maybe_parse_btc_amount(min_value)?,
+            max_value: maybe_parse_btc_amount(max_value)?,
+            include: parse_outpoints(include)?,
+            exclude: parse_outpoints(exclude)?,
+        };
+        filters.validate()
+    }
+
+    /// Checks the value bounds are consistent & that no outpoint is both included & excluded.
+    pub fn validate(self) -> Result<Self> {
+        if let (Some(min_value), Some(max_value)) = (self.min_value, self.max_value) {
+            if min_value > max_value {
+                return Err(AppError::Custom(
+                    format!("✘ Minimum UTXO value of {} sats exceeds the maximum of {} sats!", min_value, max_value)
+                ))
+            }
+        };
+        if let Some(outpoint) = self.include.iter().find(|outpoint| self.exclude.contains(outpoint)) {
+            return Err(AppError::Custom(format!("✘ Cannot both include & exclude outpoint {}!", outpoint)))
+        };
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == UtxoFilters::default()
+    }
+
+    fn requires_confirmations(&self) -> bool {
+        self.min_conf.map(|min_conf| min_conf > 0).unwrap_or(false)
+    }
+
+    // NOTE: Excluded outpoints are always dropped & included ones always kept, whatever the other filters say.
+    fn is_selected(&self, outpoint: &BtcOutPoint, value: u64, maybe_confirmations: Option<u64>) -> bool {
+        if self.exclude.contains(outpoint) {
+            return false
+        };
+        if self.include.contains(outpoint) {
+            return true
+        };
+        self.min_value.map(|min_value| value >= min_value).unwrap_or(true) &&
+        self.max_value.map(|max_value| value <= max_value).unwrap_or(true) &&
+        match (self.min_conf, maybe_confirmations) {
+            (Some(min_conf), Some(confirmations)) => confirmations >= min_conf,
+            _ => true,
+        }
+    }
+}
+
+fn filter_utxos_info_with_tip_height(
+    utxos_info: UtxosInfo,
+    filters: &UtxoFilters,
+    tip_height: u64,
+) -> Result<UtxosInfo> {
+    let num_utxos = utxos_info.len();
+    let filtered_utxos_info = utxos_info
+        .into_iter()
+        .map(|utxo_info| Ok((parse_outpoint(&format!("{}:{}", utxo_info.txid, utxo_info.vout))?, utxo_info)))
+        .collect::<Result<Vec<(BtcOutPoint, UtxoInfo)>>>()?
+        .into_iter()
+        .filter(|(outpoint, utxo_info)|
+            filters.is_selected(outpoint, utxo_info.value, Some(utxo_info.get_confirmations(tip_height)))
+        )
+        .map(|(_, utxo_info)| utxo_info)
+        .collect::<UtxosInfo>();
+    info!("✔ {} of {} UTXO(s) filtered out", num_utxos - filtered_utxos_info.len(), num_utxos);
+    Ok(filtered_utxos_info)
+}
+
+pub fn filter_utxos_info(utxos_info: UtxosInfo, filters: &UtxoFilters, backend: &dyn BtcBackend) -> Result<UtxosInfo> {
+    if filters.is_empty() {
+        return Ok(utxos_info)
+    };
+    info!("✔ Filtering UTXOs info...");
+    let tip_height = match filters.requires_confirmations() {
+        true => backend.get_tip_height()?,
+        false => 0,
+    };
+    filter_utxos_info_with_tip_height(utxos_info, filters, tip_height)
+}
+
+pub fn filter_btc_utxos_and_values(utxos: BtcUtxosAndValues, filters: &UtxoFilters) -> Result<BtcUtxosAndValues> {
+    if filters.is_empty() {
+        return Ok(utxos)
+    };
+    info!("✔ Filtering UTXOs...");
+    if filters.requires_confirmations() {
+        return Err(AppError::Custom("✘ Cannot filter UTXOs by confirmations without a block explorer!".to_string()))
+    };
+    let num_utxos = utxos.len();
+    let filtered_utxos = BtcUtxosAndValues::from_vec(
+        utxos
+            .0
+            .into_iter()
+            .filter(|utxo| filters.is_selected(&utxo.utxo.previous_output, utxo.value, None))
+            .collect()
+    );
+    info!("✔ {} of {} UTXO(s) filtered out", num_utxos - filtered_utxos.len(), num_utxos);
+    Ok(filtered_utxos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        types::UtxoStatus,
+        backend::EsploraBackend,
+        test_utils::{
+            get_sample_utxo,
+            SAMPLE_TESTNET_TX_ID,
+            SAMPLE_TESTNET_ENDPOINT,
+        },
+    };
+
+    fn get_sample_utxo_info(vout: u32, value: u64, block_height: Option<u64>) -> UtxoInfo {
+        UtxoInfo {
+            vout,
+            value,
+            txid: SAMPLE_TESTNET_TX_ID.to_string(),
+            status: UtxoStatus { confirmed: block_height.is_some(), block_height, block_time: None },
+        }
+    }
+
+    fn get_sample_utxos_info() -> UtxosInfo {
+        vec![
+            get_sample_utxo_info(0, 1_000, Some(100)),
+            get_sample_utxo_info(1, 50_000, Some(105)),
+            get_sample_utxo_info(2, 200_000, None),
+        ]
+    }
+
+    fn get_sample_outpoint(vout: u32) -> BtcOutPoint {
+        parse_outpoint(&format!("{}:{}", SAMPLE_TESTNET_TX_ID, vout)).unwrap()
+    }
+
+    #[test]
+    fn should_parse_outpoint() {
+        let result = get_sample_outpoint(1);
+        assert_eq!(result.txid.to_string(), SAMPLE_TESTNET_TX_ID);
+        assert_eq!(result.vout, 1);
+    }
+
+    #[test]
+    fn should_err_parsing_invalid_outpoints() {
+        assert!(parse_outpoint(SAMPLE_TESTNET_TX_ID).is_err());
+        assert!(parse_outpoint(&format!("{}:x", SAMPLE_TESTNET_TX_ID)).is_err());
+        assert!(parse_outpoint("deadbeef:0").is_err());
+    }
+
+    #[test]
+    fn should_not_filter_utxos_info_if_no_filters() {
+        let result = filter_utxos_info(get_sample_utxos_info(), &UtxoFilters::default(), &EsploraBackend::new("")).unwrap();
+        assert_eq!(result, get_sample_utxos_info());
+    }
+
+    #[test]
+    fn should_filter_utxos_info_by_confirmations() {
+        let filters = UtxoFilters { min_conf: Some(6), ..UtxoFilters::default() };
+        let result = filter_utxos_info_with_tip_height(get_sample_utxos_info(), &filters, 105).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].vout, 0);
+    }
+
+    #[test]
+    fn should_filter_out_unconfirmed_utxos_info() {
+        let filters = UtxoFilters { min_conf: Some(1), ..UtxoFilters::default() };
+        let result = filter_utxos_info_with_tip_height(get_sample_utxos_info(), &filters, 105).unwrap();
+        assert_eq!(result.len(), 2);
+    }
+
+    #[test]
+    fn should_filter_utxos_info_by_value() {
+        let filters = UtxoFilters { min_value: Some(10_000), max_value: Some(100_000), ..UtxoFilters::default() };
+        let result = filter_utxos_info_with_tip_height(get_sample_utxos_info(), &filters, 105).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].vout, 1);
+    }
+
+    #[test]
+    fn should_exclude_and_include_utxos_info_by_outpoint() {
+        let filters = UtxoFilters {
+            min_conf: Some(1),
+            include: vec![get_sample_outpoint(2)],
+            exclude: vec![get_sample_outpoint(0)],
+            ..UtxoFilters::default()
+        };
+        let result = filter_utxos_info_with_tip_height(get_sample_utxos_info(), &filters, 105).unwrap();
+        assert_eq!(result.iter().map(|x| x.vout).collect::<Vec<u32>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn should_filter_btc_utxos_and_values_by_value() {
+        let utxos = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        let filters = UtxoFilters { max_value: Some(1_000), ..UtxoFilters::default() };
+        assert_eq!(filter_btc_utxos_and_values(utxos.clone(), &filters).unwrap().len(), 0);
+        let filters = UtxoFilters { min_value: Some(1_000), ..UtxoFilters::default() };
+        assert_eq!(filter_btc_utxos_and_values(utxos, &filters).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn should_exclude_btc_utxos_and_values_by_outpoint() {
+        let utxo = get_sample_utxo();
+        let filters = UtxoFilters { exclude: vec![utxo.utxo.previous_output], ..UtxoFilters::default() };
+        let result = filter_btc_utxos_and_values(BtcUtxosAndValues::from_vec(vec![utxo]), &filters).unwrap();
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn should_err_filtering_btc_utxos_and_values_by_confirmations() {
+        let filters = UtxoFilters { min_conf: Some(1), ..UtxoFilters::default() };
+        let utxos = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        assert!(filter_btc_utxos_and_values(utxos, &filters).is_err());
+    }
+
+    #[test]
+    fn should_get_tip_height() {
+        let result = EsploraBackend::new(SAMPLE_TESTNET_ENDPOINT).get_tip_height();
+        assert!(result.is_ok());
+    }
+}
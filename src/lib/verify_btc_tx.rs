@@ -0,0 +1,210 @@
+use serde_json::{
+    json,
+    Value as JsonValue,
+};
+use bitcoin::{
+    consensus::encode::serialize as btc_serialize,
+    blockdata::transaction::{
+        TxIn as BtcUtxo,
+        TxOut as BtcTxOut,
+        Transaction as BtcTransaction,
+    },
+};
+use crate::{
+    state::State,
+    errors::AppError,
+    decode_btc_tx::get_btc_tx_fee,
+    op_return::OpReturnPolicy,
+    utils::{
+        get_tx_output_size,
+        is_witness_program,
+    },
+    types::{
+        Byte,
+        Result,
+        BtcUtxosAndValues,
+    },
+    constants::{
+        DUST_RELAY_FEE_RATE,
+        MAX_STANDARD_TX_WEIGHT,
+    },
+};
+
+pub fn get_dust_threshold(output: &BtcTxOut) -> u64 {
+    let spending_input_size = match is_witness_program(&output.script_pubkey) {
+        true => 67,
+        false => 148,
+    };
+    (get_tx_output_size(output) + spending_input_size) * DUST_RELAY_FEE_RATE
+}
+
+fn verify_btc_tx_input(
+    tx_bytes: &[Byte],
+    index: usize,
+    input: &BtcUtxo,
+    utxos: &BtcUtxosAndValues,
+) -> Result<()> {
+    match utxos.get_utxo_and_value_for_outpoint(&input.previous_output) {
+        None => Err(AppError::Custom(
+            format!("✘ UTXO {}:{} not found in UTXO set!", input.previous_output.txid, input.previous_output.vout)
+        )),
+        // NOTE: Unsigned UTXOs carry the `script_pubkey` of the output they spend in their `script_sig`.
+        Some(utxo_and_value) => utxo_and_value
+            .utxo
+            .script_sig
+            .verify(index, utxo_and_value.value, tx_bytes)
+            .map_err(|e| AppError::Custom(format!("✘ Script verification failed: {:?}", e))),
+    }
+}
+
+fn get_standardness_errors(tx: &BtcTransaction, op_return_policy: OpReturnPolicy) -> Vec<String> {
+    let mut errors = vec![];
+    let weight = tx.get_weight() as u64;
+    if weight > MAX_STANDARD_TX_WEIGHT {
+        errors.push(format!("✘ Tx weight of {} exceeds the standard maximum of {}!", weight, MAX_STANDARD_TX_WEIGHT));
+    };
+    errors.extend(op_return_policy.get_errors(&tx.output));
+    tx.output
+        .iter()
+        .enumerate()
+        .filter(|(_, output)| !output.script_pubkey.is_op_return() && output.value < get_dust_threshold(output))
+        .for_each(|(i, output)| errors.push(format!(
+            "✘ Output #{} of {} sats is below the dust threshold of {} sats!",
+            i,
+            output.value,
+            get_dust_threshold(output),
+        )));
+    errors
+}
+
+pub fn verify_btc_tx(tx: &BtcTransaction, utxos: &BtcUtxosAndValues, op_return_policy: OpReturnPolicy) -> JsonValue {
+    info!("✔ Verifying BTC tx...");
+    let tx_bytes = btc_serialize(tx);
+    let input_results = tx
+        .input
+        .iter()
+        .enumerate()
+        .map(|(i, input)| (i, verify_btc_tx_input(&tx_bytes, i, input, utxos)))
+        .collect::<Vec<(usize, Result<()>)>>();
+    let mut errors = get_standardness_errors(tx, op_return_policy);
+    let maybe_fee = match get_btc_tx_fee(tx, utxos) {
+        Ok(fee) => Some(fee),
+        Err(e) => {
+            errors.push(e.to_string());
+            None
+        },
+    };
+    json!({
+        "fee": maybe_fee,
+        "txid": tx.txid().to_string(),
+        "valid": errors.is_empty() && input_results.iter().all(|(_, result)| result.is_ok()),
+        "inputs": input_results
+            .iter()
+            .map(|(i, result)| json!({
+                "index": i,
+                "valid": result.is_ok(),
+                "error": result.as_ref().err().map(|e| e.to_string()),
+            }))
+            .collect::<Vec<JsonValue>>(),
+        "errors": errors,
+    })
+}
+
+pub fn get_tx_verification_json_string_from_state(state: State) -> Result<String> {
+    info!("✔ Getting tx verification JSON string from state...");
+    let verification = verify_btc_tx(
+        state.get_btc_tx()?,
+        state.get_btc_utxos_and_values()?,
+        state.op_return_policy,
+    );
+    match verification["valid"].as_bool() {
+        Some(true) => Ok(verification.to_string()),
+        _ => Err(AppError::TxVerificationError(verification)),
+    }
+}
+
+/// Errs with the verification report if the tx is invalid.
+pub fn check_btc_tx_is_valid(
+    tx: &BtcTransaction,
+    utxos: &BtcUtxosAndValues,
+    op_return_policy: OpReturnPolicy,
+) -> Result<()> {
+    let verification = verify_btc_tx(tx, utxos, op_return_policy);
+    match verification["valid"].as_bool() {
+        Some(true) => {
+            info!("✔ Tx verified successfully!");
+            Ok(())
+        },
+        _ => Err(AppError::TxVerificationError(verification)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::get_op_return_output,
+        get_btc_txs_from_utxos_info::convert_hex_tx_to_btc_tx,
+        test_utils::{
+            get_sample_tx,
+            get_sample_utxo,
+            get_sample_signed_tx_hex,
+        },
+    };
+
+    fn get_sample_signed_tx() -> BtcTransaction {
+        convert_hex_tx_to_btc_tx(get_sample_signed_tx_hex()).unwrap()
+    }
+
+    #[test]
+    fn should_get_p2pkh_dust_threshold() {
+        let expected_result = 546;
+        let result = get_dust_threshold(&get_sample_tx().output[0]);
+        assert_eq!(result, expected_result);
+    }
+
+    #[test]
+    fn should_verify_valid_btc_tx() {
+        let expected_fee = 19300;
+        let utxos = BtcUtxosAndValues::from_vec(vec![get_sample_utxo()]);
+        let result = verify_btc_tx(&get_sample_signed_tx(), &utxos, OpReturnPolicy::Standard);
+        assert_eq!(result["valid"], true);
+        assert_eq!(result["fee"], expected_fee);
+        assert_eq!(result["inputs"][0]["valid"], true);
+    }
+
+    #[test]
+    fn should_fail_to_verify_btc_tx_with_wrong_script_pubkey() {
+        let mut utxo = get_sample_utxo();
+        utxo.utxo.script_sig = get_sample_tx().output[1].script_pubkey.clone();
+        let utxos = BtcUtxosAndValues::from_vec(vec![utxo]);
+
+        let result = verify_btc_tx(&get_sample_signed_tx(), &utxos, OpReturnPolicy::Standard);
+        assert_eq!(result["valid"], false);
+        assert_eq!(result["inputs"][0]["valid"], false);
+        match check_btc_tx_is_valid(&get_sample_signed_tx(), &utxos, OpReturnPolicy::Standard) {
+            Err(AppError::TxVerificationError(report)) => assert_eq!(report, result),
+            _ => panic!("Should fail verification with the report!"),
+        }
+    }
+
+    #[test]
+    fn should_fail_to_verify_btc_tx_with_missing_utxo() {
+        let utxos = BtcUtxosAndValues::from_vec(vec![]);
+        let result = verify_btc_tx(&get_sample_signed_tx(), &utxos, OpReturnPolicy::Standard);
+        assert_eq!(result["valid"], false);
+        assert_eq!(result["fee"], JsonValue::Null);
+    }
+
+    #[test]
+    fn should_flag_non_standard_outputs() {
+        let mut tx = get_sample_signed_tx();
+        tx.output[0].value = 1;
+        tx.output.push(get_op_return_output(&[0u8; 81]).unwrap());
+        tx.output.push(get_op_return_output(&[0u8; 1]).unwrap());
+        let result = get_standardness_errors(&tx, OpReturnPolicy::Standard);
+        assert_eq!(result.len(), 3);
+        let result = get_standardness_errors(&tx, OpReturnPolicy::LargeDatacarrier);
+        assert_eq!(result.len(), 1);
+    }
+}
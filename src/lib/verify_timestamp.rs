@@ -0,0 +1,15 @@
+use crate::{
+    types::Result,
+    commands::VerifyTimestampArgs,
+    save_output::maybe_save_output,
+    timestamp_proof::get_timestamp_verification_json_string_from_state,
+    get_btc_tx::get_btc_tx_and_add_to_state,
+};
+
+pub fn verify_timestamp(args: &VerifyTimestampArgs) -> Result<String> {
+    info!("✔ Verifying timestamp...");
+    args.to_state()
+        .and_then(|state| get_btc_tx_and_add_to_state(state, &args.tx_hex, &args.tx_input.tx_file))
+        .and_then(|state| get_timestamp_verification_json_string_from_state(state, &args.file, &args.proof))
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
@@ -0,0 +1,19 @@
+use crate::{
+    types::Result,
+    commands::VerifyTxArgs,
+    save_output::maybe_save_output,
+    verify_btc_tx::get_tx_verification_json_string_from_state,
+    utxo_codec::get_utxos_from_utxo_json_string_and_add_to_state,
+    get_btc_tx::get_btc_tx_and_add_to_state,
+    get_utxo_json_string::get_utxo_json_string_and_add_to_state,
+};
+
+pub fn verify_transaction(args: &VerifyTxArgs) -> Result<String> {
+    info!("✔ Verifying transaction...");
+    args.to_state()
+        .and_then(|state| get_btc_tx_and_add_to_state(state, &args.tx_hex, &args.tx_input.tx_file))
+        .and_then(|state| get_utxo_json_string_and_add_to_state(state, &None, &Some(args.utxo_file.clone())))
+        .and_then(get_utxos_from_utxo_json_string_and_add_to_state)
+        .and_then(get_tx_verification_json_string_from_state)
+        .and_then(|output| maybe_save_output(output, &args.common.output_path))
+}
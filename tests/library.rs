@@ -0,0 +1,104 @@
+use bitcoff::{
+    Result,
+    AppError,
+    FeeEstimates,
+    EsploraTx,
+    AddressInfo,
+    BtcBackend,
+    UtxoInfo,
+    UtxosInfo,
+    UtxoStatus,
+    UtxoFilters,
+    BtcPrivateKey,
+    BtcUtxoAndValue,
+    TxBuilder,
+    ChangePolicy,
+    OpReturnOutput,
+    OpReturnPolicy,
+    BtcUtxosAndValues,
+    BtcAddressAndAmount,
+    verify_btc_tx,
+    get_btc_utxos_and_values_for_address,
+};
+
+const SAMPLE_ADDRESS: &str = "moBSQbHn7N9BC9pdtAMnA7GBiALzNMQJyE";
+const SAMPLE_RECIPIENT: &str = "mudzxCq9aCQ4Una9MmayvJVCF1Tj9fypiM";
+const SAMPLE_PRIVATE_KEY: &str = "cP2Dv4mx1DwJzN8iF6CCyPZmuS27bT9MV4Qmgb9h6cNQNq2Jgpmy";
+const SAMPLE_TX_ID: &str = "85f8faf4a3da404a833d0a21b1cea215da74a4b2c1ce8187cbf6379f42c02924";
+const SAMPLE_TX_HEX: &str = "01000000018986374e3404c889f3da5fd8b07311cad5b0e81e333a994638f65c9a9cdf4742010000006a47304402201db6cfd4be08ed4605b5eed60281438ea325af6ea6f0ff7e19f46431c29fcbcb0220157d5a1773f5eaff369735ea7608fd31b603fe279a45b5ee2f5d555c25711566012103d8d40098fa07622a89491597be95836a05de0fa5fcca1e474eb6a6213fc1f33fffffffff0282060000000000001976a91454102783c8640c5144d039cea53eb7dbb470081488acb4b81b01000000001976a9148302e646c0d9bf8b7292c6da11a721149e06749d88ac00000000";
+const SAMPLE_UTXO_JSON: &str = "{\"value\":891168,\"serialized_utxo\":\"6e3fa15afcd9b579b7ed082e0ee8cfba1f27a6cf007cb7ca95b06ab0fda2880c020000001976a91454102783c8640c5144d039cea53eb7dbb470081488acffffffff\"}";
+
+struct FixtureBackend;
+
+fn get_unsupported_err() -> AppError {
+    AppError::Custom("✘ Not supported by the fixture backend!".to_string())
+}
+
+impl BtcBackend for FixtureBackend {
+    fn get_utxos_info(&self, _address: &str) -> Result<UtxosInfo> {
+        Ok(vec![
+            UtxoInfo { txid: SAMPLE_TX_ID.to_string(), vout: 0, value: 1666, status: UtxoStatus::default() },
+        ])
+    }
+
+    fn get_tx_hex(&self, tx_id: &str) -> Result<String> {
+        match tx_id {
+            SAMPLE_TX_ID => Ok(SAMPLE_TX_HEX.to_string()),
+            _ => Err(get_unsupported_err()),
+        }
+    }
+
+    fn get_tip_height(&self) -> Result<u64> {
+        Err(get_unsupported_err())
+    }
+
+    fn get_fee_estimates(&self) -> Result<FeeEstimates> {
+        Err(get_unsupported_err())
+    }
+
+    fn get_address_info(&self, _address: &str) -> Result<AddressInfo> {
+        Err(get_unsupported_err())
+    }
+
+    fn get_address_txs_page(&self, _address: &str, _maybe_last_seen_txid: Option<&str>) -> Result<Vec<EsploraTx>> {
+        Err(get_unsupported_err())
+    }
+}
+
+#[test]
+fn should_get_utxos_for_address_via_custom_backend() {
+    let result = get_btc_utxos_and_values_for_address(&FixtureBackend, SAMPLE_ADDRESS, &UtxoFilters::default()).unwrap();
+    assert_eq!(result.len(), 1);
+    assert_eq!(result.sum(), 1666);
+    assert_eq!(result.0[0].utxo.previous_output.txid.to_string(), SAMPLE_TX_ID);
+}
+
+#[test]
+fn should_filter_utxos_for_address_via_custom_backend() {
+    let filters = UtxoFilters { min_value: Some(10_000), ..UtxoFilters::default() };
+    let result = get_btc_utxos_and_values_for_address(&FixtureBackend, SAMPLE_ADDRESS, &filters).unwrap();
+    assert_eq!(result.len(), 0);
+}
+
+#[test]
+fn should_err_validating_inconsistent_utxo_filters() {
+    let filters = UtxoFilters { min_value: Some(2), max_value: Some(1), ..UtxoFilters::default() };
+    assert!(filters.validate().is_err());
+}
+
+#[test]
+fn should_sign_tx_that_passes_verification() {
+    let utxos = BtcUtxosAndValues::from_vec(vec![BtcUtxoAndValue::from_json(SAMPLE_UTXO_JSON).unwrap()]);
+    let tx = TxBuilder::new()
+        .add_recipient(BtcAddressAndAmount::new(SAMPLE_RECIPIENT, 5001).unwrap())
+        .add_output(OpReturnOutput(b"bitcoff".to_vec()))
+        .inputs(utxos.clone())
+        .fee_rate(10.0)
+        .change_policy(ChangePolicy::ToAddress(SAMPLE_ADDRESS.to_string()))
+        .rbf(true)
+        .sign(&BtcPrivateKey::from_wif(SAMPLE_PRIVATE_KEY).unwrap())
+        .unwrap();
+    let verification = verify_btc_tx(&tx, &utxos, OpReturnPolicy::Standard);
+    assert_eq!(verification["valid"], true);
+    assert_eq!(tx.output.len(), 3);
+}